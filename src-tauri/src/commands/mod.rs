@@ -1,17 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::sync::Mutex;
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{Manager, State};
 use walkdir::WalkDir;
 
 use crate::db::Database;
+use crate::presence::{build_presence, DiscordPresence};
+use crate::rom_header;
+use crate::savestates;
+use crate::sync::{self, SyncManifest, WebDavClient};
+use crate::secrets::SecretStore;
+use crate::signatures::{self, detect_platform_by_content};
 use crate::models::*;
 
 /// App state that holds the database and active sessions
+///
+/// Cloning is cheap and shares the underlying database and sessions, so the
+/// process-monitor thread can hold its own handle.
+#[derive(Clone)]
 pub struct AppState {
-    pub db: Database,
-    pub active_sessions: Mutex<HashMap<String, ActiveSession>>,
+    pub db: Arc<Database>,
+    pub active_sessions: Arc<Mutex<HashMap<String, ActiveSession>>>,
+    pub secrets: Arc<SecretStore>,
+    pub presence: Arc<DiscordPresence>,
+    pub app_data_dir: PathBuf,
+    /// Game IDs lined up to play next, front first
+    pub launch_queue: Arc<Mutex<VecDeque<String>>>,
 }
 
 /// Represents an active game session for tracking
@@ -20,6 +36,41 @@ pub struct ActiveSession {
     pub game_id: String,
     pub start_time: chrono::DateTime<chrono::Utc>,
     pub pid: Option<u32>,
+    pub child: Option<Child>,
+    /// Idle time so far, kept up to date by the process monitor when idle tracking is on
+    pub idle_seconds: i64,
+    /// Time the emulator has been the foreground window, when accurate playtime is on
+    pub focused_seconds: Option<i64>,
+}
+
+impl ActiveSession {
+    /// Check whether the process backing this session is still alive
+    fn is_running(&mut self) -> bool {
+        match self.child.as_mut() {
+            // try_wait also reaps the process if it has already exited
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+}
+
+/// Check whether a game already has a live emulator process
+fn is_game_running(game_id: &str, state: &AppState) -> bool {
+    let mut sessions = state.active_sessions.lock().unwrap();
+    sessions
+        .get_mut(game_id)
+        .map(|session| session.is_running())
+        .unwrap_or(false)
+}
+
+/// Launch result returned when a game is already running
+fn already_running_result(game: &Game) -> LaunchResult {
+    LaunchResult {
+        success: false,
+        pid: None,
+        error: Some(format!("{} is already running", game.title)),
+        warnings: Vec::new(),
+    }
 }
 
 // ==================== GAME COMMANDS ====================
@@ -34,29 +85,246 @@ pub fn get_game(id: String, state: State<AppState>) -> Result<Option<Game>, Stri
     state.db.get_game(&id).map_err(|e| e.to_string())
 }
 
+/// One page of the library, ordered by `sort` (sort title by default)
+#[tauri::command]
+pub fn get_games_paged(sort: Option<GameSort>, offset: u32, limit: u32, state: State<AppState>) -> Result<Vec<Game>, String> {
+    state.db.get_games_paged(sort.unwrap_or_default(), offset, limit).map_err(|e| e.to_string())
+}
+
+/// Find a library game by ROM checksum, e.g. to check whether a ROM is already imported
+#[tauri::command]
+pub fn get_game_by_hash(algo: HashAlgorithm, hash: String, state: State<AppState>) -> Result<Option<Game>, String> {
+    state.db.get_game_by_hash(algo, &hash).map_err(|e| e.to_string())
+}
+
+/// Compute and store the MD5 of a game's ROM file so it can be found with `get_game_by_hash`.
+/// With `skip_copier_header`, a SNES copier header is left out so the hash matches No-Intro DATs.
+#[tauri::command]
+pub fn hash_game_rom(game_id: String, skip_copier_header: Option<bool>, state: State<AppState>) -> Result<String, String> {
+    store_rom_md5(&state.db, &game_id, skip_copier_header.unwrap_or(false))
+}
+
+fn store_rom_md5(db: &Database, game_id: &str, skip_copier_header: bool) -> Result<String, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let path = Path::new(&game.rom_path);
+    let skip = if skip_copier_header && game.platform_id == "snes" && signatures::has_smc_header(path) {
+        signatures::SMC_HEADER_LEN
+    } else {
+        0
+    };
+
+    let hash = crate::retroachievements::file_md5(path, skip)
+        .map_err(|e| format!("Failed to hash ROM: {}", e))?;
+    db.set_game_hash(game_id, HashAlgorithm::Md5, &hash).map_err(|e| e.to_string())?;
+    Ok(hash)
+}
+
+/// Write a copy of a SNES ROM without its 512-byte copier header, as a .sfc beside the original
+#[tauri::command]
+pub fn strip_smc_header(path: String) -> Result<String, String> {
+    write_headerless_smc(Path::new(&path)).map(|dest| dest.to_string_lossy().to_string())
+}
+
+fn write_headerless_smc(path: &Path) -> Result<PathBuf, String> {
+    if !signatures::has_smc_header(path) {
+        return Err(format!("No copier header found: {}", path.display()));
+    }
+
+    let dest = path.with_extension("sfc");
+    if dest.exists() {
+        return Err(format!("File already exists: {}", dest.display()));
+    }
+
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    std::fs::write(&dest, &data[signatures::SMC_HEADER_LEN as usize..]).map_err(|e| e.to_string())?;
+    Ok(dest)
+}
+
 #[tauri::command]
 pub fn add_game(input: CreateGameInput, state: State<AppState>) -> Result<Game, String> {
+    let game = game_from_input(input);
+    state.db.add_game(&game).map_err(|e| e.to_string())?;
+    Ok(game)
+}
+
+/// Add many games in one transaction, e.g. from a drag-and-drop, skipping ROMs already in the library
+#[tauri::command]
+pub fn add_games_batch(inputs: Vec<CreateGameInput>, state: State<AppState>) -> Result<Vec<AddGameResult>, String> {
+    insert_games_batch(&state.db, inputs)
+}
+
+fn insert_games_batch(db: &Database, inputs: Vec<CreateGameInput>) -> Result<Vec<AddGameResult>, String> {
+    let games: Vec<Game> = inputs.into_iter().map(game_from_input).collect();
+    let inserted = db.add_games_batch(&games).map_err(|e| e.to_string())?;
+
+    Ok(games.into_iter()
+        .zip(inserted)
+        .map(|(game, inserted)| AddGameResult {
+            rom_path: game.rom_path.clone(),
+            skipped: !inserted,
+            game: inserted.then_some(game),
+        })
+        .collect())
+}
+
+fn game_from_input(input: CreateGameInput) -> Game {
     let mut game = Game::new(input.title, input.rom_path, input.platform_id);
     game.cover_art_path = input.cover_art_path;
     game.description = input.description;
-
-    state.db.add_game(&game).map_err(|e| e.to_string())?;
-    Ok(game)
+    game
 }
 
 #[tauri::command]
 pub fn update_game(id: String, updates: UpdateGameInput, state: State<AppState>) -> Result<(), String> {
-    state.db.update_game(&id, &updates).map_err(|e| e.to_string())
+    edit_game(&state.db, &id, &updates)
+}
+
+/// Apply a user's edits, remembering which metadata fields they touched so re-scrapes keep them
+fn edit_game(db: &Database, id: &str, updates: &UpdateGameInput) -> Result<(), String> {
+    db.update_game(id, updates).map_err(|e| e.to_string())?;
+    db.mark_fields_edited(id, &updates.metadata_fields()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn delete_game(id: String, state: State<AppState>) -> Result<(), String> {
-    state.db.delete_game(&id).map_err(|e| e.to_string())
+    remove_game(&state.db, &state.app_data_dir, &id)
+}
+
+/// Delete a game along with its play sessions and the artwork stored for it
+fn remove_game(db: &Database, app_data_dir: &Path, id: &str) -> Result<(), String> {
+    let game = db.get_game(id).map_err(|e| e.to_string())?;
+
+    db.delete_game(id).map_err(|e| e.to_string())?;
+
+    if let Some(game) = game {
+        remove_game_images(db, &game, app_data_dir);
+    }
+    Ok(())
 }
 
 #[tauri::command]
 pub fn delete_games_batch(ids: Vec<String>, state: State<AppState>) -> Result<usize, String> {
-    state.db.delete_games_batch(&ids).map_err(|e| e.to_string())
+    let games: Vec<Game> = ids.iter()
+        .filter_map(|id| state.db.get_game(id).ok().flatten())
+        .collect();
+
+    let deleted = state.db.delete_games_batch(&ids).map_err(|e| e.to_string())?;
+
+    for game in &games {
+        remove_game_images(&state.db, game, &state.app_data_dir);
+    }
+    Ok(deleted)
+}
+
+/// Merge duplicate games into `keep_id` without losing their play history. Returns the kept game.
+#[tauri::command]
+pub fn merge_games(keep_id: String, merge_ids: Vec<String>, state: State<AppState>) -> Result<Game, String> {
+    merge_duplicate_games(&state.db, &state.app_data_dir, &keep_id, &merge_ids)
+}
+
+fn merge_duplicate_games(db: &Database, app_data_dir: &Path, keep_id: &str, merge_ids: &[String]) -> Result<Game, String> {
+    db.get_game(keep_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let merged: Vec<Game> = merge_ids.iter()
+        .filter(|id| id.as_str() != keep_id)
+        .filter_map(|id| db.get_game(id).ok().flatten())
+        .collect();
+
+    db.merge_games(keep_id, merge_ids).map_err(|e| e.to_string())?;
+
+    let kept = db.get_game(keep_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    // Artwork the kept game took over stays on disk
+    for mut game in merged {
+        game.cover_art_path = game.cover_art_path.filter(|path| kept.cover_art_path.as_ref() != Some(path));
+        game.background_path = game.background_path.filter(|path| kept.background_path.as_ref() != Some(path));
+        remove_game_images(db, &game, app_data_dir);
+    }
+
+    Ok(kept)
+}
+
+/// Wipe the chosen metadata fields on several games, e.g. to start over after a bad scrape.
+/// Titles and ROM paths are never touched. With `delete_images`, cleared artwork is removed too.
+#[tauri::command]
+pub fn clear_metadata(
+    game_ids: Vec<String>,
+    fields: Vec<ScrapeField>,
+    delete_images: Option<bool>,
+    state: State<AppState>,
+) -> Result<usize, String> {
+    clear_game_metadata(&state.db, &state.app_data_dir, &game_ids, &fields, delete_images.unwrap_or(false))
+}
+
+fn clear_game_metadata(
+    db: &Database,
+    app_data_dir: &Path,
+    game_ids: &[String],
+    fields: &[ScrapeField],
+    delete_images: bool,
+) -> Result<usize, String> {
+    let games: Vec<Game> = game_ids.iter()
+        .filter_map(|id| db.get_game(id).ok().flatten())
+        .collect();
+
+    let cleared = db.clear_game_fields(game_ids, fields).map_err(|e| e.to_string())?;
+
+    if delete_images {
+        for game in games {
+            // Only the artwork that was cleared
+            let mut artwork = Game::new(game.title, game.rom_path, game.platform_id);
+            if fields.contains(&ScrapeField::CoverArtPath) {
+                artwork.cover_art_path = game.cover_art_path;
+            }
+            if fields.contains(&ScrapeField::BackgroundPath) {
+                artwork.background_path = game.background_path;
+            }
+            if fields.contains(&ScrapeField::Screenshots) {
+                artwork.screenshots = game.screenshots;
+            }
+            remove_game_images(db, &artwork, app_data_dir);
+        }
+    }
+    Ok(cleared)
+}
+
+/// Delete a game's cover, background and screenshots, plus their thumbnails.
+/// Only files inside the app's images directory are touched; artwork the user
+/// pointed at elsewhere on disk is theirs to keep, and so is a file another
+/// game still uses (covers can be shared after `dedup_cover_images`).
+fn remove_game_images(db: &Database, game: &Game, app_data_dir: &Path) {
+    let images_dir = app_data_dir.join("images");
+    let Ok(images_dir) = images_dir.canonicalize() else {
+        return;
+    };
+    let thumbnails_dir = images_dir.join("thumbnails");
+
+    let paths = game.cover_art_path.iter()
+        .chain(game.background_path.iter())
+        .chain(game.screenshots.iter());
+
+    for stored_path in paths {
+        // Canonicalize so ".." can't climb out of the images directory
+        let Ok(path) = Path::new(stored_path).canonicalize() else {
+            continue;
+        };
+        if !path.starts_with(&images_dir) || db.is_artwork_in_use(stored_path).unwrap_or(true) {
+            continue;
+        }
+
+        // Thumbnails are keyed by the path as stored, not the canonical one
+        crate::thumbnails::remove_thumbnails(&thumbnails_dir, Path::new(stored_path));
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("Failed to delete {}: {}", path.display(), e);
+        }
+    }
 }
 
 #[tauri::command]
@@ -78,26 +346,17 @@ pub fn set_custom_cover_art(
         return Err("Source image file does not exist".to_string());
     }
 
-    // Get the file extension
-    let extension = source
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_else(|| "jpg".to_string());
-
-    // Validate it's an image format we support
-    let valid_extensions = ["jpg", "jpeg", "png", "webp", "gif"];
-    if !valid_extensions.contains(&extension.as_str()) {
-        return Err(format!(
-            "Unsupported image format '{}'. Supported formats: {}",
-            extension,
-            valid_extensions.join(", ")
-        ));
-    }
-
     // Get app data directory for images
     let app_data_dir = app_handle.path().app_data_dir()
         .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    store_cover_art(&state.db, &app_data_dir, &game_id, source)
+}
+
+/// Copy an image into images/covers/{game_id}.{ext} and make it the game's cover
+fn store_cover_art(db: &Database, app_data_dir: &Path, game_id: &str, source: &Path) -> Result<String, String> {
+    let extension = image_extension(source)?;
+
     let covers_dir = app_data_dir.join("images").join("covers");
 
     // Create directory if it doesn't exist
@@ -108,7 +367,7 @@ pub fn set_custom_cover_art(
     let dest_path = covers_dir.join(format!("{}.{}", game_id, extension));
 
     // Copy the file
-    std::fs::copy(&source, &dest_path)
+    std::fs::copy(source, &dest_path)
         .map_err(|e| format!("Failed to copy image: {}", e))?;
 
     let dest_path_str = dest_path.to_string_lossy().to_string();
@@ -119,12 +378,337 @@ pub fn set_custom_cover_art(
         ..Default::default()
     };
 
-    state.db.update_game(&game_id, &updates)
+    db.update_game(game_id, &updates)
         .map_err(|e| format!("Failed to update game: {}", e))?;
 
     Ok(dest_path_str)
 }
 
+/// Download an image from a URL and make it the game's cover
+#[tauri::command]
+pub async fn set_cover_art_from_url(game_id: String, url: String, state: State<'_, AppState>) -> Result<String, String> {
+    cover_art_from_url(&state.db, &state.app_data_dir, &game_id, &url).await
+}
+
+async fn cover_art_from_url(db: &Database, app_data_dir: &Path, game_id: &str, url: &str) -> Result<String, String> {
+    db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let download = app_data_dir.join("images").join("covers").join(format!(".{}.download", game_id));
+    crate::scraper::download_image_with(&reqwest::Client::new(), url, &download, false).await?;
+
+    // The URL often has no (or a misleading) extension, so name the file after what it decodes as
+    let stored = std::fs::read(&download)
+        .map_err(|e| format!("Failed to read downloaded image: {}", e))
+        .and_then(|bytes| match image::guess_format(&bytes) {
+            Ok(image::ImageFormat::Png) => Ok("png"),
+            Ok(image::ImageFormat::Jpeg) => Ok("jpg"),
+            Ok(image::ImageFormat::WebP) => Ok("webp"),
+            Ok(image::ImageFormat::Gif) => Ok("gif"),
+            _ => Err("Downloaded file is not a supported image".to_string()),
+        })
+        .and_then(|extension| {
+            let source = download.with_extension(extension);
+            std::fs::rename(&download, &source).map_err(|e| format!("Failed to save image: {}", e))?;
+            let stored = store_cover_art(db, app_data_dir, game_id, &source);
+            let _ = std::fs::remove_file(&source);
+            stored
+        });
+
+    let _ = std::fs::remove_file(&download);
+    stored
+}
+
+/// Get a downscaled copy of a cover for grid views, cached under images/thumbnails
+#[tauri::command]
+pub fn get_thumbnail(cover_path: String, width: u32, state: State<AppState>) -> Result<String, String> {
+    let thumbnails_dir = state.app_data_dir.join("images").join("thumbnails");
+    crate::thumbnails::get_or_create_thumbnail(&thumbnails_dir, Path::new(&cover_path), width)
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Width of the cover thumbnails embedded in HTML reports
+const REPORT_COVER_WIDTH: u32 = 160;
+
+/// Write a self-contained HTML page of the library, grouped by platform, for sharing
+#[tauri::command]
+pub fn export_library_html(dest_path: String, state: State<AppState>) -> Result<(), String> {
+    write_library_html(&state.db, &state.app_data_dir, Path::new(&dest_path))
+}
+
+fn write_library_html(db: &Database, app_data_dir: &Path, dest_path: &Path) -> Result<(), String> {
+    use base64::Engine;
+
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+    let platforms = db.get_all_platforms().map_err(|e| e.to_string())?;
+
+    // Embed small thumbnails rather than full covers to keep the page a reasonable size
+    let thumbnails_dir = app_data_dir.join("images").join("thumbnails");
+    let covers: HashMap<String, String> = games.iter()
+        .filter_map(|game| {
+            let cover = game.cover_art_path.as_ref()?;
+            let thumbnail = crate::thumbnails::get_or_create_thumbnail(&thumbnails_dir, Path::new(cover), REPORT_COVER_WIDTH)
+                .map_err(|e| eprintln!("Skipping cover for {}: {}", game.title, e))
+                .ok()?;
+            let bytes = std::fs::read(thumbnail).ok()?;
+            let uri = format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(bytes));
+            Some((game.id.clone(), uri))
+        })
+        .collect();
+
+    let html = crate::reports::render_library_html(&games, &platforms, &covers);
+    std::fs::write(dest_path, html).map_err(|e| format!("Failed to write report: {}", e))
+}
+
+/// Write play sessions to a CSV file: one game's, or the whole library's when `game_id` is None.
+/// Returns the number of sessions written.
+#[tauri::command]
+pub fn export_play_sessions_csv(dest_path: String, game_id: Option<String>, state: State<AppState>) -> Result<usize, String> {
+    let sessions = state.db.get_play_sessions_with_titles(game_id.as_deref()).map_err(|e| e.to_string())?;
+    let csv = crate::reports::render_play_sessions_csv(&sessions);
+    std::fs::write(&dest_path, csv).map_err(|e| format!("Failed to write CSV: {}", e))?;
+    Ok(sessions.len())
+}
+
+/// Add play sessions from a CSV file in the export format, matching games by rom_path when the
+/// file has that column and by title otherwise, then recompute the affected games' play time
+#[tauri::command]
+pub fn import_play_sessions_csv(src_path: String, state: State<AppState>) -> Result<PlaySessionImportResult, String> {
+    let text = std::fs::read_to_string(&src_path).map_err(|e| format!("Failed to read CSV: {}", e))?;
+    import_play_sessions(&state.db, &text)
+}
+
+fn import_play_sessions(db: &Database, text: &str) -> Result<PlaySessionImportResult, String> {
+    let (rows, mut unmatched_rows) = crate::reports::parse_play_sessions_csv(text)?;
+
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+    let by_path: HashMap<&str, &str> = games.iter().map(|g| (g.rom_path.as_str(), g.id.as_str())).collect();
+    let mut by_title: HashMap<String, &str> = HashMap::new();
+    for game in &games {
+        by_title.entry(game.title.to_lowercase()).or_insert(&game.id);
+    }
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        let game_id = row.rom_path.as_deref()
+            .and_then(|path| by_path.get(path))
+            .or_else(|| by_title.get(&row.title.to_lowercase()));
+        let Some(game_id) = game_id else {
+            unmatched_rows.push(format!("Line {}: no game matches {}", row.line, row.title));
+            continue;
+        };
+
+        let Ok(start) = chrono::DateTime::parse_from_rfc3339(&row.start_time) else {
+            unmatched_rows.push(format!("Line {}: {} has an invalid start time", row.line, row.title));
+            continue;
+        };
+        let end = row.end_time.as_deref()
+            .and_then(|end| chrono::DateTime::parse_from_rfc3339(end).ok())
+            .unwrap_or(start + chrono::Duration::seconds(row.duration_seconds))
+            .with_timezone(&chrono::Utc);
+
+        sessions.push(PlaySession {
+            id: uuid::Uuid::new_v4().to_string(),
+            game_id: game_id.to_string(),
+            start_time: start.to_rfc3339(),
+            end_time: Some(end.to_rfc3339()),
+            duration_seconds: row.duration_seconds,
+            note: None,
+            gross_duration_seconds: None,
+        });
+    }
+
+    let sessions_imported = db.import_play_sessions(&sessions).map_err(|e| e.to_string())? as u32;
+    Ok(PlaySessionImportResult {
+        sessions_imported,
+        duplicates_skipped: sessions.len() as u32 - sessions_imported,
+        unmatched_rows,
+    })
+}
+
+/// Copy screenshots an emulator saved for a game into images/screenshots and attach them.
+/// Returns the paths of newly imported screenshots.
+#[tauri::command]
+pub fn import_emulator_screenshots(game_id: String, screenshot_dir: String, state: State<AppState>) -> Result<Vec<String>, String> {
+    import_screenshots_from(&state.db, &state.app_data_dir, &game_id, Path::new(&screenshot_dir))
+}
+
+/// Import images from `screenshot_dir` whose name starts with the ROM's basename (as RetroArch
+/// names them) or that were saved while one of the game's play sessions was running
+fn import_screenshots_from(db: &Database, app_data_dir: &Path, game_id: &str, screenshot_dir: &Path) -> Result<Vec<String>, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let rom_stem = Path::new(&game.rom_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let session_windows: Vec<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> = db
+        .get_play_sessions(game_id)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|session| {
+            let start = chrono::DateTime::parse_from_rfc3339(&session.start_time).ok()?.with_timezone(&chrono::Utc);
+            let end = match &session.end_time {
+                Some(end) => chrono::DateTime::parse_from_rfc3339(end).ok()?.with_timezone(&chrono::Utc),
+                None => chrono::Utc::now(),
+            };
+            Some((start, end))
+        })
+        .collect();
+
+    let entries = std::fs::read_dir(screenshot_dir)
+        .map_err(|e| format!("Failed to read screenshot folder: {}", e))?;
+
+    let screenshots_dir = app_data_dir.join("images").join("screenshots");
+    let mut screenshots = game.screenshots.clone();
+    let mut imported = Vec::new();
+
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if !path.is_file() || path.extension().is_none() || image_extension(&path).is_err() {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        let name_matches = !rom_stem.is_empty() && file_name.to_lowercase().starts_with(&rom_stem);
+        let taken_during_session = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(chrono::DateTime::<chrono::Utc>::from)
+            .is_ok_and(|modified| session_windows.iter().any(|(start, end)| modified >= *start && modified <= *end));
+        if !name_matches && !taken_during_session {
+            continue;
+        }
+
+        let dest = screenshots_dir.join(format!("{}_{}", game_id, file_name));
+        let dest_str = dest.to_string_lossy().to_string();
+        if screenshots.contains(&dest_str) {
+            continue;
+        }
+
+        std::fs::create_dir_all(&screenshots_dir)
+            .map_err(|e| format!("Failed to create screenshots directory: {}", e))?;
+        std::fs::copy(&path, &dest).map_err(|e| format!("Failed to copy screenshot: {}", e))?;
+
+        screenshots.push(dest_str.clone());
+        imported.push(dest_str);
+    }
+
+    if !imported.is_empty() {
+        let updates = UpdateGameInput {
+            screenshots: Some(screenshots),
+            ..Default::default()
+        };
+        db.update_game(game_id, &updates).map_err(|e| e.to_string())?;
+    }
+
+    Ok(imported)
+}
+
+/// Folders, relative to a ROM's directory or the scan root, where ROM sets keep box art.
+/// Matched ignoring case, so "Media/Box Front" is found too.
+const LOCAL_ARTWORK_FOLDERS: &[&str] = &[
+    "",
+    "media/box front",
+    "media/boxart",
+    "boxart",
+    "covers",
+    "images",
+];
+
+/// Give games without a cover the image next to their ROM, matched by basename
+#[tauri::command]
+pub fn match_local_artwork(scan_root: String, state: State<AppState>) -> Result<u32, String> {
+    match_local_artwork_in(&state.db, &state.app_data_dir, Path::new(&scan_root))
+}
+
+/// Find covers for games under `scan_root` that don't have one. Returns how many were set.
+fn match_local_artwork_in(db: &Database, app_data_dir: &Path, scan_root: &Path) -> Result<u32, String> {
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+    let mut matched = 0;
+
+    for game in games {
+        if game.cover_art_path.is_some() {
+            continue;
+        }
+
+        let rom_path = Path::new(&game.rom_path);
+        if !rom_path.starts_with(scan_root) {
+            continue;
+        }
+
+        let (Some(rom_dir), Some(stem)) = (rom_path.parent(), rom_path.file_stem().and_then(|s| s.to_str())) else {
+            continue;
+        };
+
+        let search_dirs = LOCAL_ARTWORK_FOLDERS.iter()
+            .filter_map(|folder| find_dir_ignoring_case(rom_dir, folder))
+            .chain(LOCAL_ARTWORK_FOLDERS.iter().filter_map(|folder| find_dir_ignoring_case(scan_root, folder)));
+
+        let Some(image) = search_dirs.into_iter().find_map(|dir| find_artwork_in_dir(&dir, stem)) else {
+            continue;
+        };
+
+        match store_cover_art(db, app_data_dir, &game.id, &image) {
+            Ok(_) => matched += 1,
+            Err(e) => eprintln!("Failed to set local artwork for {}: {}", game.title, e),
+        }
+    }
+
+    Ok(matched)
+}
+
+/// Resolve `relative` (separated by '/') under `base`, matching each folder name ignoring case
+fn find_dir_ignoring_case(base: &Path, relative: &str) -> Option<PathBuf> {
+    relative.split('/').filter(|name| !name.is_empty()).try_fold(base.to_path_buf(), |dir, name| {
+        std::fs::read_dir(&dir).ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| path.is_dir() && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+    })
+}
+
+/// Look for a supported image in `dir` whose name matches `stem`, ignoring case
+fn find_artwork_in_dir(dir: &Path, stem: &str) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(dir).ok()?;
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_file()
+                && path.extension().is_some()
+                && path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.eq_ignore_ascii_case(stem))
+                && image_extension(path).is_ok()
+        })
+}
+
+/// Get an image's lowercase extension, rejecting formats we don't support
+fn image_extension(source: &Path) -> Result<String, String> {
+    let extension = source
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_else(|| "jpg".to_string());
+
+    // Validate it's an image format we support
+    let valid_extensions = ["jpg", "jpeg", "png", "webp", "gif"];
+    if !valid_extensions.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported image format '{}'. Supported formats: {}",
+            extension,
+            valid_extensions.join(", ")
+        ));
+    }
+
+    Ok(extension)
+}
+
 // ==================== EMULATOR COMMANDS ====================
 
 #[tauri::command]
@@ -144,6 +728,17 @@ pub fn add_emulator(input: CreateEmulatorInput, state: State<AppState>) -> Resul
         emulator.launch_arguments = args;
     }
     emulator.supported_platform_ids = input.supported_platform_ids;
+    if let Some(capture_logs) = input.capture_logs {
+        emulator.capture_logs = capture_logs;
+    }
+    emulator.fullscreen_argument = input.fullscreen_argument;
+    if let Some(launch_type) = input.launch_type {
+        emulator.launch_type = launch_type;
+    }
+    emulator.wrapper_command = input.wrapper_command;
+    emulator.preferred_display = input.preferred_display;
+    emulator.display_argument = input.display_argument;
+    emulator.resolution_argument_template = input.resolution_argument_template;
 
     state.db.add_emulator(&emulator).map_err(|e| e.to_string())?;
     Ok(emulator)
@@ -159,6 +754,35 @@ pub fn delete_emulator(id: String, state: State<AppState>) -> Result<(), String>
     state.db.delete_emulator(&id).map_err(|e| e.to_string())
 }
 
+/// Monitors currently connected, in the order `preferred_display` indexes them
+#[tauri::command]
+pub fn list_displays(app_handle: tauri::AppHandle) -> Result<Vec<DisplayInfo>, String> {
+    let primary = app_handle.primary_monitor().map_err(|e| e.to_string())?;
+    let primary_position = primary.as_ref().map(|monitor| (monitor.position().x, monitor.position().y));
+
+    let monitors = app_handle.available_monitors().map_err(|e| e.to_string())?;
+    Ok(monitors.iter().enumerate().map(|(index, monitor)| {
+        let (position, size) = (monitor.position(), monitor.size());
+        DisplayInfo {
+            index: index as u32,
+            name: monitor.name().cloned(),
+            width: size.width,
+            height: size.height,
+            x: position.x,
+            y: position.y,
+            scale_factor: monitor.scale_factor(),
+            // Monitors don't have IDs; no two share a top-left corner
+            is_primary: primary_position == Some((position.x, position.y)),
+        }
+    }).collect())
+}
+
+/// Save a custom display order; `ids` lists emulators first to last
+#[tauri::command]
+pub fn reorder_emulators(ids: Vec<String>, state: State<AppState>) -> Result<(), String> {
+    state.db.reorder_emulators(&ids).map_err(|e| e.to_string())
+}
+
 // ==================== PLATFORM COMMANDS ====================
 
 #[tauri::command]
@@ -166,21 +790,251 @@ pub fn get_all_platforms(state: State<AppState>) -> Result<Vec<Platform>, String
     state.db.get_all_platforms().map_err(|e| e.to_string())
 }
 
+/// Platforms in display order. With `include_empty` false, systems without any games are left out.
+#[tauri::command]
+pub fn get_platforms(include_empty: Option<bool>, state: State<AppState>) -> Result<Vec<Platform>, String> {
+    list_platforms(&state.db, include_empty.unwrap_or(true))
+}
+
+fn list_platforms(db: &Database, include_empty: bool) -> Result<Vec<Platform>, String> {
+    let platforms = db.get_all_platforms().map_err(|e| e.to_string())?;
+    if include_empty {
+        return Ok(platforms);
+    }
+
+    let populated: std::collections::HashSet<String> = db.get_platform_game_counts(false)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(platform_id, _)| platform_id)
+        .collect();
+    Ok(platforms.into_iter().filter(|p| populated.contains(&p.id)).collect())
+}
+
 #[tauri::command]
 pub fn get_platform(id: String, state: State<AppState>) -> Result<Option<Platform>, String> {
     state.db.get_platform(&id).map_err(|e| e.to_string())
 }
 
+/// Save a custom display order; `ids` lists platforms first to last
 #[tauri::command]
-pub fn set_default_emulator(platform_id: String, emulator_id: String, state: State<AppState>) -> Result<(), String> {
-    state.db.set_platform_default_emulator(&platform_id, &emulator_id).map_err(|e| e.to_string())
+pub fn reorder_platforms(ids: Vec<String>, state: State<AppState>) -> Result<(), String> {
+    state.db.reorder_platforms(&ids).map_err(|e| e.to_string())
 }
 
-// ==================== COLLECTION COMMANDS ====================
-
+/// Add a user-defined platform (e.g., Amiga) so its files can be scanned
 #[tauri::command]
-pub fn get_all_collections(state: State<AppState>) -> Result<Vec<Collection>, String> {
-    state.db.get_all_collections().map_err(|e| e.to_string())
+pub fn add_platform(input: CreatePlatformInput, state: State<AppState>) -> Result<Platform, String> {
+    create_platform(&state.db, input)
+}
+
+fn create_platform(db: &Database, input: CreatePlatformInput) -> Result<Platform, String> {
+    let id = input.id.trim().to_lowercase();
+    if id.is_empty() {
+        return Err("Platform ID cannot be empty".to_string());
+    }
+    if db.get_platform(&id).map_err(|e| e.to_string())?.is_some() {
+        return Err(format!("A platform with ID '{}' already exists", id));
+    }
+
+    let platform = Platform {
+        id,
+        display_name: input.display_name,
+        manufacturer: input.manufacturer,
+        file_extensions: normalize_extensions(&input.file_extensions)?,
+        icon_path: None,
+        default_emulator_id: None,
+        color: input.color.unwrap_or_else(|| "#00f5ff".to_string()),
+        is_favorite: false,
+    };
+    db.add_platform(&platform).map_err(|e| e.to_string())?;
+    Ok(platform)
+}
+
+/// Update a platform's name, manufacturer, extensions or color
+#[tauri::command]
+pub fn update_platform(id: String, mut updates: UpdatePlatformInput, state: State<AppState>) -> Result<(), String> {
+    if state.db.get_platform(&id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Platform not found".to_string());
+    }
+    if let Some(extensions) = &updates.file_extensions {
+        updates.file_extensions = Some(normalize_extensions(extensions)?);
+    }
+    if let Some(color) = &updates.color {
+        updates.color = Some(normalize_color(color)?);
+    }
+    state.db.update_platform(&id, &updates).map_err(|e| e.to_string())
+}
+
+/// Delete a custom platform. Refuses while games still use it unless `reassign_to` names
+/// a platform to move them to first. Built-in platforms cannot be deleted.
+#[tauri::command]
+pub fn delete_platform(id: String, reassign_to: Option<String>, state: State<AppState>) -> Result<DeletePlatformResult, String> {
+    remove_platform(&state.db, &id, reassign_to.as_deref())
+}
+
+fn remove_platform(db: &Database, id: &str, reassign_to: Option<&str>) -> Result<DeletePlatformResult, String> {
+    if crate::db::default_platforms().iter().any(|(builtin_id, ..)| *builtin_id == id) {
+        return Err("Built-in platforms cannot be deleted".to_string());
+    }
+    if db.get_platform(id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Platform not found".to_string());
+    }
+
+    let game_count = db.count_games_for_platform(id).map_err(|e| e.to_string())?;
+    let mut target = None;
+
+    if game_count > 0 {
+        match reassign_to {
+            Some(reassign_to) => {
+                if reassign_to == id || db.get_platform(reassign_to).map_err(|e| e.to_string())?.is_none() {
+                    return Err(format!("Cannot reassign games to platform '{}'", reassign_to));
+                }
+                target = Some(reassign_to);
+            }
+            None => {
+                return Ok(DeletePlatformResult {
+                    deleted: false,
+                    blocking_game_count: game_count,
+                    reassigned_count: 0,
+                });
+            }
+        }
+    }
+
+    let reassigned_count = db.delete_platform(id, target).map_err(|e| e.to_string())? as u32;
+    Ok(DeletePlatformResult {
+        deleted: true,
+        blocking_game_count: 0,
+        reassigned_count,
+    })
+}
+
+/// Restore a built-in platform's default file extensions and color
+#[tauri::command]
+pub fn reset_platform_defaults(id: String, state: State<AppState>) -> Result<Platform, String> {
+    restore_platform_defaults(&state.db, &id)
+}
+
+fn restore_platform_defaults(db: &Database, id: &str) -> Result<Platform, String> {
+    if !db.reset_platform_defaults(id).map_err(|e| e.to_string())? {
+        return Err(format!("'{}' is not a built-in platform", id));
+    }
+    db.get_platform(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Platform not found".to_string())
+}
+
+/// Count games per platform so the sidebar can show counts and hide empty systems
+#[tauri::command]
+pub fn get_platform_game_counts(include_empty: Option<bool>, state: State<AppState>) -> Result<Vec<(String, u32)>, String> {
+    state.db.get_platform_game_counts(include_empty.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Set a custom icon for a platform by copying an image into the app's images directory
+#[tauri::command]
+pub fn set_platform_icon(platform_id: String, source_path: String, state: State<AppState>) -> Result<String, String> {
+    copy_platform_icon(&state.db, &state.app_data_dir, &platform_id, &source_path)
+}
+
+fn copy_platform_icon(db: &Database, app_data_dir: &Path, platform_id: &str, source_path: &str) -> Result<String, String> {
+    let source = Path::new(source_path);
+    if !source.exists() {
+        return Err("Source image file does not exist".to_string());
+    }
+    let extension = image_extension(source)?;
+
+    if db.get_platform(platform_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Platform not found".to_string());
+    }
+
+    let icons_dir = app_data_dir.join("images").join("platform_icons");
+    std::fs::create_dir_all(&icons_dir)
+        .map_err(|e| format!("Failed to create platform icons directory: {}", e))?;
+
+    let dest_path = icons_dir.join(format!("{}.{}", platform_id, extension));
+    std::fs::copy(source, &dest_path)
+        .map_err(|e| format!("Failed to copy image: {}", e))?;
+
+    let dest_path_str = dest_path.to_string_lossy().to_string();
+    db.set_platform_icon(platform_id, &dest_path_str)
+        .map_err(|e| format!("Failed to update platform: {}", e))?;
+
+    Ok(dest_path_str)
+}
+
+/// Pin or unpin a platform at the top of the sidebar, returning whether it is now pinned
+#[tauri::command]
+pub fn toggle_platform_favorite(platform_id: String, state: State<AppState>) -> Result<bool, String> {
+    state.db.toggle_platform_favorite(&platform_id).map_err(|e| e.to_string())
+}
+
+/// Change the color a platform is themed with
+#[tauri::command]
+pub fn set_platform_color(platform_id: String, color: String, state: State<AppState>) -> Result<(), String> {
+    recolor_platform(&state.db, &platform_id, &color)
+}
+
+fn recolor_platform(db: &Database, platform_id: &str, color: &str) -> Result<(), String> {
+    let color = normalize_color(color)?;
+    if db.get_platform(platform_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Platform not found".to_string());
+    }
+    db.set_platform_color(platform_id, &color).map_err(|e| e.to_string())
+}
+
+/// Lowercase a `#rrggbb` color, rejecting anything else
+fn normalize_color(color: &str) -> Result<String, String> {
+    let color = color.trim().to_lowercase();
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!("Invalid color '{}': expected a #rrggbb hex value", color));
+    }
+    Ok(color)
+}
+
+/// Lowercase file extensions, rejecting any that don't start with a dot
+fn normalize_extensions(extensions: &[String]) -> Result<Vec<String>, String> {
+    extensions.iter()
+        .map(|ext| {
+            let ext = ext.trim().to_lowercase();
+            if ext.len() < 2 || !ext.starts_with('.') {
+                return Err(format!("Invalid file extension '{}': extensions must start with a dot", ext));
+            }
+            Ok(ext)
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_default_emulator(platform_id: String, emulator_id: String, state: State<AppState>) -> Result<(), String> {
+    state.db.set_platform_default_emulator(&platform_id, &emulator_id).map_err(|e| e.to_string())
+}
+
+// ==================== COLLECTION COMMANDS ====================
+
+#[tauri::command]
+pub fn get_all_collections(state: State<AppState>) -> Result<Vec<Collection>, String> {
+    let collections = state.db.get_all_collections().map_err(|e| e.to_string())?;
+    resolve_collection_covers(&state.db, collections)
+}
+
+/// Fill in each collection's effective cover, falling back to the first member game with art
+fn resolve_collection_covers(db: &Database, mut collections: Vec<Collection>) -> Result<Vec<Collection>, String> {
+    let covers: HashMap<String, String> = db.get_all_games()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|g| g.cover_art_path.map(|path| (g.id, path)))
+        .collect();
+
+    for collection in &mut collections {
+        collection.cover_art_path = collection.cover_game_id.iter()
+            .chain(collection.game_ids.iter())
+            .find_map(|id| covers.get(id).cloned());
+    }
+
+    Ok(collections)
 }
 
 #[tauri::command]
@@ -200,6 +1054,274 @@ pub fn delete_collection(id: String, state: State<AppState>) -> Result<(), Strin
     state.db.delete_collection(&id).map_err(|e| e.to_string())
 }
 
+/// Summarize a collection's member games: count, play time, completion and platforms
+#[tauri::command]
+pub fn get_collection_stats(collection_id: String, state: State<AppState>) -> Result<CollectionStats, String> {
+    compute_collection_stats(&state.db, &collection_id)
+}
+
+fn compute_collection_stats(db: &Database, collection_id: &str) -> Result<CollectionStats, String> {
+    let collection = db.get_collection(collection_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Collection not found")?;
+
+    let members: std::collections::HashSet<&String> = collection.game_ids.iter().collect();
+    let mut stats = CollectionStats {
+        game_count: 0,
+        total_play_time_seconds: 0,
+        completed_count: 0,
+        platforms: Vec::new(),
+    };
+
+    for game in db.get_all_games().map_err(|e| e.to_string())? {
+        if !members.contains(&game.id) {
+            continue;
+        }
+        stats.game_count += 1;
+        stats.total_play_time_seconds += game.total_play_time_seconds;
+        if game.is_completed {
+            stats.completed_count += 1;
+        }
+        if !stats.platforms.contains(&game.platform_id) {
+            stats.platforms.push(game.platform_id);
+        }
+    }
+
+    stats.platforms.sort();
+    Ok(stats)
+}
+
+// ==================== VIRTUAL COLLECTIONS ====================
+
+/// How many games the Recently Played and Most Played collections show
+const VIRTUAL_COLLECTION_LIMIT: usize = 20;
+
+/// Get the games in a built-in collection (Favorites, Recently Played, ...)
+#[tauri::command]
+pub fn get_virtual_collection(kind: VirtualCollectionKind, state: State<AppState>) -> Result<Vec<Game>, String> {
+    find_virtual_collection_games(&state.db, kind)
+}
+
+fn find_virtual_collection_games(db: &Database, kind: VirtualCollectionKind) -> Result<Vec<Game>, String> {
+    let mut games = db.get_all_games().map_err(|e| e.to_string())?;
+
+    match kind {
+        VirtualCollectionKind::Favorites => {
+            games.retain(|g| g.is_favorite);
+        }
+        VirtualCollectionKind::RecentlyPlayed => {
+            games.retain(|g| g.last_played.is_some());
+            games.sort_by(|a, b| b.last_played.cmp(&a.last_played));
+            games.truncate(VIRTUAL_COLLECTION_LIMIT);
+        }
+        VirtualCollectionKind::MostPlayed => {
+            games.retain(|g| g.total_play_time_seconds > 0);
+            games.sort_by_key(|g| std::cmp::Reverse(g.total_play_time_seconds));
+            games.truncate(VIRTUAL_COLLECTION_LIMIT);
+        }
+        VirtualCollectionKind::NeverPlayed => {
+            let played = db.get_played_game_ids().map_err(|e| e.to_string())?;
+            games.retain(|g| !played.contains(&g.id));
+        }
+    }
+
+    Ok(games)
+}
+
+/// Library grouped by release decade ("1990s"), oldest first, with undated games last under "Unknown"
+#[tauri::command]
+pub fn get_games_grouped_by_decade(state: State<AppState>) -> Result<Vec<(String, Vec<Game>)>, String> {
+    let games = state.db.get_all_games().map_err(|e| e.to_string())?;
+    Ok(group_games_by_decade(games))
+}
+
+fn group_games_by_decade(games: Vec<Game>) -> Vec<(String, Vec<Game>)> {
+    let mut decades: std::collections::BTreeMap<i32, Vec<Game>> = std::collections::BTreeMap::new();
+    let mut unknown = Vec::new();
+
+    for game in games {
+        match game.release_date.as_deref().and_then(release_year) {
+            Some(year) => decades.entry(year - year % 10).or_default().push(game),
+            None => unknown.push(game),
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<Game>)> = decades.into_iter()
+        .map(|(decade, games)| (format!("{}s", decade), games))
+        .collect();
+    if !unknown.is_empty() {
+        groups.push(("Unknown".to_string(), unknown));
+    }
+    groups
+}
+
+/// Year from a stored YYYY-MM-DD release date
+fn release_year(release_date: &str) -> Option<i32> {
+    let year = release_date.get(..4)?;
+    if !year.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    year.parse().ok()
+}
+
+// ==================== SMART COLLECTION COMMANDS ====================
+
+#[tauri::command]
+pub fn get_all_smart_collections(state: State<AppState>) -> Result<Vec<SmartCollection>, String> {
+    state.db.get_all_smart_collections().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn add_smart_collection(input: CreateSmartCollectionInput, state: State<AppState>) -> Result<SmartCollection, String> {
+    let collection = SmartCollection::new(input.name, input.rule);
+    state.db.add_smart_collection(&collection).map_err(|e| e.to_string())?;
+    Ok(collection)
+}
+
+#[tauri::command]
+pub fn update_smart_collection(id: String, updates: UpdateSmartCollectionInput, state: State<AppState>) -> Result<(), String> {
+    state.db.update_smart_collection(&id, &updates).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_smart_collection(id: String, state: State<AppState>) -> Result<(), String> {
+    state.db.delete_smart_collection(&id).map_err(|e| e.to_string())
+}
+
+/// Evaluate a smart collection's rule against the current library
+#[tauri::command]
+pub fn get_smart_collection_games(id: String, state: State<AppState>) -> Result<Vec<Game>, String> {
+    find_smart_collection_games(&state.db, &id)
+}
+
+fn find_smart_collection_games(db: &Database, id: &str) -> Result<Vec<Game>, String> {
+    let collection = db.get_smart_collection(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Smart collection not found")?;
+
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+    Ok(games.into_iter().filter(|game| collection.rule.matches(game)).collect())
+}
+
+/// A random game matching `filter` (the same conditions smart collections use), or None if
+/// nothing matches
+#[tauri::command]
+pub fn get_random_game(filter: Option<SmartCollectionRule>, state: State<AppState>) -> Result<Option<Game>, String> {
+    pick_random_game(&state.db, &filter.unwrap_or_default())
+}
+
+fn pick_random_game(db: &Database, filter: &SmartCollectionRule) -> Result<Option<Game>, String> {
+    let mut games: Vec<Game> = db.get_all_games()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|game| filter.matches(game))
+        .collect();
+    if games.is_empty() {
+        return Ok(None);
+    }
+
+    // v4 UUIDs are random, which saves pulling in a dependency just for this
+    let index = (uuid::Uuid::new_v4().as_u128() % games.len() as u128) as usize;
+    Ok(Some(games.swap_remove(index)))
+}
+
+/// One game per calendar day, the same for every call that day
+#[tauri::command]
+pub fn get_game_of_the_day(favorites_only: Option<bool>, state: State<AppState>) -> Result<Option<Game>, String> {
+    pick_game_of_the_day(&state.db, chrono::Local::now().date_naive(), favorites_only.unwrap_or(false))
+}
+
+fn pick_game_of_the_day(db: &Database, date: chrono::NaiveDate, favorites_only: bool) -> Result<Option<Game>, String> {
+    let mut games: Vec<Game> = db.get_all_games()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|game| !favorites_only || game.is_favorite)
+        .collect();
+    if games.is_empty() {
+        return Ok(None);
+    }
+
+    // IDs don't change on rename, so the pick stays put unless the library itself changes
+    games.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // SplitMix64 over the day number: consecutive days land far apart
+    let mut seed = chrono::Datelike::num_days_from_ce(&date) as u64;
+    seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    seed ^= seed >> 31;
+
+    let index = (seed % games.len() as u64) as usize;
+    Ok(Some(games.swap_remove(index)))
+}
+
+// ==================== PLAYLIST COMMANDS ====================
+
+#[tauri::command]
+pub fn get_all_playlists(state: State<AppState>) -> Result<Vec<Playlist>, String> {
+    state.db.get_all_playlists().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn add_playlist(input: CreatePlaylistInput, state: State<AppState>) -> Result<Playlist, String> {
+    let playlist = Playlist::new(input.name, input.game_ids);
+    state.db.add_playlist(&playlist).map_err(|e| e.to_string())?;
+    Ok(playlist)
+}
+
+#[tauri::command]
+pub fn update_playlist(id: String, updates: UpdatePlaylistInput, state: State<AppState>) -> Result<(), String> {
+    state.db.update_playlist(&id, &updates).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_playlist(id: String, state: State<AppState>) -> Result<(), String> {
+    state.db.delete_playlist(&id).map_err(|e| e.to_string())
+}
+
+/// A playlist's games in playlist order. Games deleted from the library are skipped.
+#[tauri::command]
+pub fn get_playlist_games(id: String, state: State<AppState>) -> Result<Vec<Game>, String> {
+    find_playlist_games(&state.db, &id)
+}
+
+fn find_playlist_games(db: &Database, id: &str) -> Result<Vec<Game>, String> {
+    let playlist = db.get_playlist(id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Playlist not found")?;
+
+    let mut games = Vec::with_capacity(playlist.game_ids.len());
+    for game_id in &playlist.game_ids {
+        if let Some(game) = db.get_game(game_id).map_err(|e| e.to_string())? {
+            games.push(game);
+        }
+    }
+    Ok(games)
+}
+
+/// Move the item at position `from` to position `to`, shifting the items in between
+#[tauri::command]
+pub fn move_playlist_item(playlist_id: String, from: usize, to: usize, state: State<AppState>) -> Result<Playlist, String> {
+    move_playlist_entry(&state.db, &playlist_id, from, to)
+}
+
+fn move_playlist_entry(db: &Database, playlist_id: &str, from: usize, to: usize) -> Result<Playlist, String> {
+    let mut playlist = db.get_playlist(playlist_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Playlist not found")?;
+
+    let len = playlist.game_ids.len();
+    if from >= len || to >= len {
+        return Err(format!("Position out of range for a playlist of {} games", len));
+    }
+    let game_id = playlist.game_ids.remove(from);
+    playlist.game_ids.insert(to, game_id);
+
+    let updates = UpdatePlaylistInput { name: None, game_ids: Some(playlist.game_ids.clone()) };
+    db.update_playlist(playlist_id, &updates).map_err(|e| e.to_string())?;
+    Ok(playlist)
+}
+
 // ==================== LIBRARY SCANNING ====================
 
 /// Input for scanning with optional platform override
@@ -213,7 +1335,6 @@ pub struct ScanPath {
 /// Info about a discovered file during scanning
 struct DiscoveredFile {
     path: PathBuf,
-    extension: String,
     platform_id: String,
     disc_number: Option<u32>,
     base_name: String,
@@ -221,7 +1342,12 @@ struct DiscoveredFile {
 
 #[tauri::command]
 pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<ScanResult, String> {
-    let platforms = state.db.get_all_platforms().map_err(|e| e.to_string())?;
+    scan_paths(paths, &state.db)
+}
+
+fn scan_paths(paths: Vec<ScanPath>, db: &Database) -> Result<ScanResult, String> {
+    let platforms = db.get_all_platforms().map_err(|e| e.to_string())?;
+    let title_options = CleanTitleOptions::from_settings(db);
 
     // Build extension -> platforms mapping (one extension can map to multiple platforms)
     let mut ext_to_platforms: HashMap<String, Vec<String>> = HashMap::new();
@@ -323,7 +1449,6 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                             // Record this as a PS3 game
                             discovered_files.push(DiscoveredFile {
                                 path: entry.path().to_path_buf(),
-                                extension: ".sfb".to_string(),
                                 platform_id: "ps3".to_string(),
                                 disc_number: None,
                                 base_name: game_title,
@@ -363,7 +1488,6 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                                         // Record this as a Wii U game (store meta.xml path)
                                         discovered_files.push(DiscoveredFile {
                                             path: entry.path().to_path_buf(),
-                                            extension: ".xml".to_string(),
                                             platform_id: "wiiu".to_string(),
                                             disc_number: None,
                                             base_name: game_title,
@@ -478,7 +1602,6 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
 
                             discovered_files.push(DiscoveredFile {
                                 path: file_path.to_path_buf(),
-                                extension: ".xiso.iso".to_string(),
                                 platform_id: "xbox".to_string(),
                                 disc_number,
                                 base_name,
@@ -500,6 +1623,11 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                     } else {
                         detect_platform_from_path(&rom_path_str, &platform_hints)
                             .filter(|detected| possible_platforms.contains(detected))
+                            // Then look inside the file for a console signature
+                            .or_else(|| {
+                                detect_platform_by_content(file_path)
+                                    .filter(|detected| possible_platforms.contains(detected))
+                            })
                             .unwrap_or_else(|| {
                                 // When no folder hint matches, prefer more common platforms
                                 // Priority order for disc-based platforms with shared extensions
@@ -528,7 +1656,6 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
 
                     discovered_files.push(DiscoveredFile {
                         path: file_path.to_path_buf(),
-                        extension: ext,
                         platform_id,
                         disc_number,
                         base_name,
@@ -661,13 +1788,6 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                 continue;
             }
 
-            // Skip .m3u files we didn't generate (they might already be in library)
-            // But include ones we just generated
-            if file.extension == ".m3u" && !generated_m3u_files.contains(&file.path) {
-                // Check if this existing .m3u should be imported
-                // Only if not already in library
-            }
-
             result.games_found += 1;
 
             let rom_path = file.path.canonicalize()
@@ -675,15 +1795,21 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                 .unwrap_or_else(|_| file.path.to_string_lossy().to_string());
 
             // Check if game already exists
-            match state.db.get_game_by_path(&rom_path) {
+            match db.get_game_by_path(&rom_path) {
                 Ok(Some(_)) => {
                     result.games_updated += 1;
                 }
                 Ok(None) => {
-                    let title = clean_rom_title(&file.base_name);
-                    let game = Game::new(title, rom_path, file.platform_id.clone());
+                    let title = clean_rom_title_options(&file.base_name, &title_options);
+                    let mut game = Game::new(title, rom_path, file.platform_id.clone());
+                    if rom_header::TITLED_PLATFORMS.contains(&file.platform_id.as_str()) {
+                        game.internal_title = rom_header::read_internal_title(&file.path);
+                    }
+                    if file.platform_id == "n64" {
+                        game.n64_byte_order = signatures::read_n64_byte_order(&file.path);
+                    }
 
-                    if let Err(e) = state.db.add_game(&game) {
+                    if let Err(e) = db.add_game(&game) {
                         result.errors.push(format!("Failed to add {}: {}", file.path.display(), e));
                     } else {
                         result.games_added += 1;
@@ -695,8 +1821,15 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
             }
         }
 
-        // Also import the generated .m3u files
+        // Also import the generated .m3u files. Playlists that were already on disk were
+        // discovered and counted in Phase 3, so only new ones are handled here.
         for m3u_path in &generated_m3u_files {
+            if discovered_files.iter().any(|file| &file.path == m3u_path) {
+                continue;
+            }
+
+            result.games_found += 1;
+
             let rom_path = m3u_path.canonicalize()
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| m3u_path.to_string_lossy().to_string());
@@ -705,7 +1838,7 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
             let platform_id = detect_platform_from_path(&rom_path, &platform_hints)
                 .unwrap_or_else(|| "ps1".to_string()); // Default to PS1 for .m3u files
 
-            match state.db.get_game_by_path(&rom_path) {
+            match db.get_game_by_path(&rom_path) {
                 Ok(Some(_)) => {
                     result.games_updated += 1;
                 }
@@ -714,15 +1847,14 @@ pub fn scan_library(paths: Vec<ScanPath>, state: State<AppState>) -> Result<Scan
                         .and_then(|s| s.to_str())
                         .unwrap_or("Unknown")
                         .to_string();
-                    let title = clean_rom_title(&title);
+                    let title = clean_rom_title_options(&title, &title_options);
 
                     let game = Game::new(title, rom_path, platform_id);
 
-                    if let Err(e) = state.db.add_game(&game) {
+                    if let Err(e) = db.add_game(&game) {
                         result.errors.push(format!("Failed to add {}: {}", m3u_path.display(), e));
                     } else {
                         result.games_added += 1;
-                        result.games_found += 1;
                     }
                 }
                 Err(e) => {
@@ -757,8 +1889,134 @@ fn detect_platform_from_path(path: &str, hints: &[(&str, Vec<&str>)]) -> Option<
     None
 }
 
+/// Setting that turns on title casing of names cleaned from ROM filenames ("true"/"false")
+const TITLE_CASE_SETTING: &str = "title_case_rom_names";
+
+/// Words left lower-case inside a title
+const TITLE_SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "nor", "of", "on", "or",
+    "the", "to", "vs", "with",
+];
+
+/// Abbreviations that stay upper-case when title casing
+const TITLE_ACRONYMS: &[&str] = &[
+    "ATV", "BMX", "DK", "DX", "EX", "FIFA", "GT", "NASCAR", "NBA", "NCAA", "NES", "NFL", "NHL",
+    "PGA", "RPG", "SNES", "SSX", "TMNT", "TV", "UFC", "UFO", "USA", "WWE", "WWF",
+];
+
+const ROMAN_NUMERALS: &[&str] = &[
+    "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII", "XIII", "XIV",
+    "XV", "XVI", "XVII", "XVIII", "XIX", "XX",
+];
+
+/// Setting holding which tag groups to strip as JSON, e.g. `{"stripParentheses": false}`
+/// to keep "(USA)" while dropping "[!]". Missing groups are stripped.
+const TITLE_CLEANING_SETTING: &str = "title_cleaning";
+
+/// Setting holding a JSON list of regexes to remove from titles, in order. When present it
+/// replaces the built-in tag patterns and the `title_cleaning` groups.
+const TITLE_CLEANING_RULES_SETTING: &str = "title_cleaning_rules";
+
+/// Built-in tag patterns, in the order they are applied
+const DEFAULT_TITLE_CLEANING_RULES: [&str; 3] = [
+    r"\s*\([^)]*\)",      // (anything)
+    r"\s*\[[^\]]*\]",     // [anything]
+    r"\s*\{[^}]*\}",      // {anything}
+];
+
+/// What `clean_rom_title_options` does to a filename besides tidying whitespace
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CleanTitleOptions {
+    /// Drop "(USA)", "(Rev A)" and other parenthesised tags
+    strip_parentheses: bool,
+    /// Drop dump flags such as "[!]" and "[b1]"
+    strip_brackets: bool,
+    /// Drop "{...}" tags
+    strip_braces: bool,
+    /// Re-case titles, e.g. "SUPER MARIO WORLD" to "Super Mario World"
+    #[serde(skip)]
+    title_case: bool,
+    /// User rules used instead of the built-in patterns
+    #[serde(skip)]
+    custom_rules: Option<Vec<regex::Regex>>,
+}
+
+impl Default for CleanTitleOptions {
+    fn default() -> Self {
+        Self {
+            strip_parentheses: true,
+            strip_brackets: true,
+            strip_braces: true,
+            title_case: false,
+            custom_rules: None,
+        }
+    }
+}
+
+impl CleanTitleOptions {
+    fn from_settings(db: &Database) -> Self {
+        let cleaning: CleanTitleOptions = db.get_setting(TITLE_CLEANING_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        let custom_rules = match db.get_setting_json::<Vec<String>>(TITLE_CLEANING_RULES_SETTING) {
+            Ok(rules) => rules.map(|patterns| compile_title_rules(&patterns).0),
+            Err(e) => {
+                eprintln!("Ignoring title cleaning rules, using the defaults: {}", e);
+                None
+            }
+        };
+
+        Self { title_case: setting_enabled(db, TITLE_CASE_SETTING), custom_rules, ..cleaning }
+    }
+}
+
+/// Compile title cleaning rules, skipping (and returning) the patterns that aren't valid regexes
+fn compile_title_rules(patterns: &[String]) -> (Vec<regex::Regex>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut invalid = Vec::new();
+    for pattern in patterns {
+        match regex::Regex::new(pattern) {
+            Ok(re) => rules.push(re),
+            Err(e) => {
+                eprintln!("Skipping invalid title cleaning rule {:?}: {}", pattern, e);
+                invalid.push(pattern.clone());
+            }
+        }
+    }
+    (rules, invalid)
+}
+
+/// The title cleaning rules in effect: the user's list, or the built-in patterns
+#[tauri::command]
+pub fn get_title_cleaning_rules(state: State<AppState>) -> Result<Vec<String>, String> {
+    let rules = state.db.get_setting_json::<Vec<String>>(TITLE_CLEANING_RULES_SETTING).ok().flatten();
+    Ok(rules.unwrap_or_else(|| DEFAULT_TITLE_CLEANING_RULES.iter().map(|rule| rule.to_string()).collect()))
+}
+
+/// Replace the title cleaning rules, or go back to the built-in ones with `None`. Returns the
+/// patterns that don't compile; they are kept so they can be fixed, but skipped when scanning.
+#[tauri::command]
+pub fn set_title_cleaning_rules(rules: Option<Vec<String>>, state: State<AppState>) -> Result<Vec<String>, String> {
+    let Some(rules) = rules else {
+        state.db.delete_setting(TITLE_CLEANING_RULES_SETTING).map_err(|e| e.to_string())?;
+        return Ok(Vec::new());
+    };
+
+    state.db.set_setting_json(TITLE_CLEANING_RULES_SETTING, &rules).map_err(|e| e.to_string())?;
+    Ok(compile_title_rules(&rules).1)
+}
+
 /// Clean up common ROM naming patterns
 fn clean_rom_title(title: &str) -> String {
+    clean_rom_title_options(title, &CleanTitleOptions::default())
+}
+
+/// Clean up common ROM naming patterns, then apply the requested normalisation
+fn clean_rom_title_options(title: &str, options: &CleanTitleOptions) -> String {
     let mut clean = title.to_string();
 
     // Remove secondary file extensions that weren't stripped by file_stem()
@@ -770,21 +2028,94 @@ fn clean_rom_title(title: &str) -> String {
         }
     }
 
-    // Remove common ROM tags like (USA), [!], (Rev A), etc.
-    let patterns = [
-        r"\s*\([^)]*\)",      // (anything)
-        r"\s*\[[^\]]*\]",     // [anything]
-        r"\s*\{[^}]*\}",      // {anything}
-    ];
-
-    for pattern in patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
+    if let Some(rules) = &options.custom_rules {
+        for re in rules {
             clean = re.replace_all(&clean, "").to_string();
         }
+    } else {
+        // Remove common ROM tags like (USA), [!], (Rev A), etc.
+        let strip = [options.strip_parentheses, options.strip_brackets, options.strip_braces];
+        for (pattern, strip) in DEFAULT_TITLE_CLEANING_RULES.iter().zip(strip) {
+            if !strip {
+                continue;
+            }
+            if let Ok(re) = regex::Regex::new(pattern) {
+                clean = re.replace_all(&clean, "").to_string();
+            }
+        }
     }
 
     // Trim and clean up multiple spaces
-    clean.split_whitespace().collect::<Vec<_>>().join(" ")
+    let clean = clean.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if options.title_case {
+        title_case(&clean)
+    } else {
+        clean
+    }
+}
+
+/// Title-case a cleaned title: small words stay lower-case except at the start of the title
+/// or a subtitle, acronyms and Roman numerals stay upper-case, and words that already mix
+/// cases (e.g. "McDonald's") are left alone.
+fn title_case(title: &str) -> String {
+    let mut starts_phrase = true;
+    title
+        .split(' ')
+        .map(|word| {
+            let cased = word
+                .split('-')
+                .enumerate()
+                .map(|(index, part)| title_case_word(part, starts_phrase && index == 0))
+                .collect::<Vec<_>>()
+                .join("-");
+            starts_phrase = word.ends_with(':') || word == "-";
+            cased
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn title_case_word(word: &str, starts_phrase: bool) -> String {
+    let has_upper = word.chars().any(|c| c.is_uppercase());
+    let has_lower = word.chars().any(|c| c.is_lowercase());
+    if has_upper && has_lower {
+        return word.to_string();
+    }
+
+    let bare = word.trim_matches(|c: char| !c.is_alphanumeric()).to_uppercase();
+    if bare.is_empty() {
+        return word.to_string();
+    }
+
+    if bare.chars().any(|c| c.is_ascii_digit()) {
+        // "3D" and "2K" stay upper-case, ordinals like "1st" don't
+        let ordinal = ["ST", "ND", "RD", "TH"].iter().any(|suffix| bare.ends_with(suffix))
+            && bare.chars().next().is_some_and(|c| c.is_ascii_digit());
+        return if ordinal { word.to_lowercase() } else { word.to_uppercase() };
+    }
+
+    if TITLE_ACRONYMS.contains(&bare.as_str()) || ROMAN_NUMERALS.contains(&bare.as_str()) {
+        return word.to_uppercase();
+    }
+
+    let lower = word.to_lowercase();
+    if !starts_phrase && TITLE_SMALL_WORDS.contains(&bare.to_lowercase().as_str()) {
+        return lower;
+    }
+
+    // Capitalise the first letter, skipping leading punctuation such as quotes
+    let mut capitalised = String::with_capacity(lower.len());
+    let mut done = false;
+    for c in lower.chars() {
+        if !done && c.is_alphabetic() {
+            capitalised.extend(c.to_uppercase());
+            done = true;
+        } else {
+            capitalised.push(c);
+        }
+    }
+    capitalised
 }
 
 /// Check if a filename indicates it's part of a multi-disc set
@@ -903,6 +2234,244 @@ fn get_base_game_prefix(filename: &str) -> String {
     result.trim().to_string()
 }
 
+/// Replace a multi-disc .m3u game with one game per disc, the inverse of the scanner grouping
+/// discs into a playlist. Returns the disc games.
+#[tauri::command]
+pub fn split_m3u_game(game_id: String, delete_playlist: bool, state: State<AppState>) -> Result<Vec<Game>, String> {
+    split_playlist_game(&state.db, &game_id, delete_playlist)
+}
+
+/// Disc 1 takes over the playlist game's play history, collections and artwork. Without
+/// `delete_playlist` the .m3u stays on disk, and the next scan will import it again.
+fn split_playlist_game(db: &Database, game_id: &str, delete_playlist: bool) -> Result<Vec<Game>, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let playlist = Path::new(&game.rom_path);
+    if !is_m3u_path(playlist) {
+        return Err("Game is not an .m3u playlist".to_string());
+    }
+
+    let disc_paths: Vec<String> = read_m3u_entries(playlist)?
+        .into_iter()
+        .map(|path| path.canonicalize().unwrap_or(path).to_string_lossy().to_string())
+        .collect();
+    if disc_paths.is_empty() {
+        return Err("Playlist lists no discs".to_string());
+    }
+
+    let mut discs = Vec::new();
+    let mut new_discs = Vec::new();
+    for (index, rom_path) in disc_paths.into_iter().enumerate() {
+        // A disc may already be in the library on its own
+        if let Some(existing) = db.get_game_by_path(&rom_path).map_err(|e| e.to_string())? {
+            discs.push(existing);
+            continue;
+        }
+
+        let mut disc = Game::new(format!("{} (Disc {})", game.title, index + 1), rom_path, game.platform_id.clone());
+        disc.description = game.description.clone();
+        disc.release_date = game.release_date.clone();
+        disc.genre = game.genre.clone();
+        disc.developer = game.developer.clone();
+        disc.publisher = game.publisher.clone();
+        disc.preferred_emulator_id = game.preferred_emulator_id.clone();
+        new_discs.push(disc.clone());
+        discs.push(disc);
+    }
+
+    db.split_into_discs(&game.id, &new_discs, &discs[0].id).map_err(|e| e.to_string())?;
+
+    if delete_playlist {
+        std::fs::remove_file(playlist).map_err(|e| format!("Failed to delete .m3u file: {}", e))?;
+    }
+
+    discs.iter()
+        .map(|disc| {
+            db.get_game(&disc.id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "Game not found".to_string())
+        })
+        .collect()
+}
+
+/// Rewrite a game's .m3u from the disc files currently beside it, e.g. after adding a disc.
+/// Returns the disc paths now in the playlist, in order.
+#[tauri::command]
+pub fn regenerate_m3u(game_id: String, state: State<AppState>) -> Result<Vec<String>, String> {
+    regenerate_playlist(&state.db, &game_id)
+}
+
+fn regenerate_playlist(db: &Database, game_id: &str) -> Result<Vec<String>, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let playlist = Path::new(&game.rom_path);
+    if !is_m3u_path(playlist) {
+        return Err("Game is not an .m3u playlist".to_string());
+    }
+    let Some(dir) = playlist.parent() else {
+        return Err("Invalid playlist path".to_string());
+    };
+    let base_name = playlist.file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| "Invalid playlist path".to_string())?;
+
+    let discs = find_playlist_discs(dir, base_name)?;
+    if discs.is_empty() {
+        return Err(format!("No disc files found for {}", base_name));
+    }
+
+    generate_m3u_playlist(base_name, &discs, dir)?;
+
+    Ok(discs.into_iter().map(|(_, path)| path.to_string_lossy().to_string()).collect())
+}
+
+/// Disc files for `base_name` in `dir`, matched the way the scanner groups them: either
+/// `Game (Disc 1).cue` beside the playlist or `Game (Disc 1)/whatever.cue` one folder down.
+/// Keeps one file per disc number, sorted by disc number.
+fn find_playlist_discs(dir: &Path, base_name: &str) -> Result<Vec<(u32, PathBuf)>, String> {
+    let is_disc_file = |path: &Path| {
+        path.is_file() && path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| is_disc_extension(&format!(".{}", e.to_lowercase())))
+    };
+    let sorted_entries = |dir: &Path| -> Result<Vec<PathBuf>, String> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect();
+        paths.sort();
+        Ok(paths)
+    };
+
+    let mut discs: HashMap<u32, PathBuf> = HashMap::new();
+    for path in sorted_entries(dir)? {
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let Some(number) = get_disc_number(name) else {
+            continue;
+        };
+        if !get_base_game_name(name).eq_ignore_ascii_case(base_name) {
+            continue;
+        }
+
+        if is_disc_file(&path) {
+            discs.entry(number).or_insert(path);
+        } else if path.is_dir() {
+            // A folder per disc: the folder name carries the disc number
+            let folder_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if get_disc_number(folder_name) == Some(number) {
+                if let Some(disc_file) = sorted_entries(&path)?.into_iter().find(|p| is_disc_file(p)) {
+                    discs.entry(number).or_insert(disc_file);
+                }
+            }
+        }
+    }
+
+    let mut discs: Vec<(u32, PathBuf)> = discs.into_iter().collect();
+    discs.sort_by_key(|(number, _)| *number);
+    Ok(discs)
+}
+
+/// List the disc files an .m3u references that don't exist. Accepts a game id or a playlist path.
+#[tauri::command]
+pub fn validate_m3u(game_id_or_path: String, state: State<AppState>) -> Result<Vec<String>, String> {
+    let playlist = match state.db.get_game(&game_id_or_path).map_err(|e| e.to_string())? {
+        Some(game) => game.rom_path,
+        None => game_id_or_path,
+    };
+    find_missing_m3u_entries(Path::new(&playlist))
+}
+
+fn find_missing_m3u_entries(playlist: &Path) -> Result<Vec<String>, String> {
+    if !is_m3u_path(playlist) {
+        return Err("Not an .m3u playlist".to_string());
+    }
+
+    Ok(read_m3u_entries(playlist)?
+        .into_iter()
+        .filter(|path| !path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Rewrite a game's .m3u with its entries in the given order. `ordered_filenames` must be
+/// exactly the entries already in the playlist, as written in it.
+#[tauri::command]
+pub fn reorder_m3u(game_id: String, ordered_filenames: Vec<String>, state: State<AppState>) -> Result<(), String> {
+    let game = state.db.get_game(&game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+    reorder_playlist(Path::new(&game.rom_path), &ordered_filenames)
+}
+
+fn reorder_playlist(playlist: &Path, ordered_filenames: &[String]) -> Result<(), String> {
+    if !is_m3u_path(playlist) {
+        return Err("Game is not an .m3u playlist".to_string());
+    }
+
+    let content = std::fs::read_to_string(playlist)
+        .map_err(|e| format!("Failed to read .m3u file: {}", e))?;
+    let mut current: Vec<&str> = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let mut requested: Vec<&str> = ordered_filenames.iter().map(|f| f.trim()).collect();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err("New order must list exactly the discs already in the playlist".to_string());
+    }
+
+    let playlist_dir = playlist.parent().unwrap_or(Path::new(""));
+    if let Some(missing) = ordered_filenames.iter().find(|f| !playlist_dir.join(f.trim()).is_file()) {
+        return Err(format!("Disc file not found: {}", missing));
+    }
+
+    // Only the entries move; #EXTM3U, other directives, comments and blank lines stay put
+    let mut entries = ordered_filenames.iter().map(|f| f.trim());
+    let line_ending = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut rewritten = content.lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                line
+            } else {
+                entries.next().unwrap_or(trimmed)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(line_ending);
+    if content.ends_with('\n') {
+        rewritten.push_str(line_ending);
+    }
+    std::fs::write(playlist, rewritten).map_err(|e| format!("Failed to write .m3u file: {}", e))
+}
+
+/// Paths listed in an .m3u, resolved against its directory. Lines starting with # are comments.
+fn read_m3u_entries(playlist: &Path) -> Result<Vec<PathBuf>, String> {
+    let content = std::fs::read_to_string(playlist)
+        .map_err(|e| format!("Failed to read .m3u file: {}", e))?;
+    let playlist_dir = playlist.parent().unwrap_or(Path::new(""));
+
+    Ok(content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| playlist_dir.join(line))
+        .collect())
+}
+
+fn is_m3u_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("m3u"))
+}
+
 /// Disc-based file extensions that could be multi-disc games
 fn is_disc_extension(ext: &str) -> bool {
     matches!(ext, ".cue" | ".iso" | ".chd" | ".mdf" | ".nrg" | ".img" | ".ccd")
@@ -1006,7 +2575,11 @@ fn get_executable_path(path: &str) -> Result<String, String> {
 }
 
 /// Launch a Windows game directly without an emulator
-fn launch_windows_game_direct(game: &Game, state: &State<AppState>) -> Result<LaunchResult, String> {
+fn launch_windows_game_direct(game: &Game, state: &AppState) -> Result<LaunchResult, String> {
+    if is_game_running(&game.id, state) {
+        return Ok(already_running_result(game));
+    }
+
     let rom_path = std::path::Path::new(&game.rom_path);
     let absolute_path = if rom_path.is_absolute() {
         game.rom_path.clone()
@@ -1016,6 +2589,8 @@ fn launch_windows_game_direct(game: &Game, state: &State<AppState>) -> Result<La
             .unwrap_or_else(|_| game.rom_path.clone())
     };
 
+    let warnings = run_pre_launch_hook(game, &absolute_path, state);
+
     // Launch the executable directly
     // On Windows: run the .exe directly
     // On macOS: use 'open' command (works with Wine/Crossover if configured)
@@ -1035,46 +2610,40 @@ fn launch_windows_game_direct(game: &Game, state: &State<AppState>) -> Result<La
 
     match result {
         Ok(child) => {
-            let pid = child.id();
-
-            // Start tracking the session
-            let session = PlaySession::new(game.id.clone());
-            if let Err(e) = state.db.create_play_session(&session) {
-                eprintln!("Failed to create play session: {}", e);
-            }
-
-            // Store active session
-            {
-                let mut sessions = state.active_sessions.lock().unwrap();
-                sessions.insert(game.id.clone(), ActiveSession {
-                    session_id: session.id,
-                    game_id: game.id.clone(),
-                    start_time: chrono::Utc::now(),
-                    pid: Some(pid),
-                });
-            }
+            let pid = track_launched_process(game, &absolute_path, child, state);
 
             Ok(LaunchResult {
                 success: true,
                 pid: Some(pid),
                 error: None,
+                warnings,
             })
         }
         Err(e) => Ok(LaunchResult {
             success: false,
             pid: None,
             error: Some(format!("Failed to launch game: {}", e)),
+            warnings,
         }),
     }
 }
 
 #[tauri::command]
 pub fn launch_game(game_id: String, state: State<AppState>) -> Result<LaunchResult, String> {
+    launch_game_by_id(&game_id, &state)
+}
+
+/// Launch a game with its preferred emulator, or its platform's default
+fn launch_game_by_id(game_id: &str, state: &AppState) -> Result<LaunchResult, String> {
     // Get the game
-    let game = state.db.get_game(&game_id)
+    let game = state.db.get_game(game_id)
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Game not found".to_string())?;
 
+    launch_with_configured_emulator(&game, state)
+}
+
+fn launch_with_configured_emulator(game: &Game, state: &AppState) -> Result<LaunchResult, String> {
     // Get the emulator (prefer game's preferred, then platform default)
     let emulator_id = game.preferred_emulator_id.clone()
         .or_else(|| {
@@ -1089,12 +2658,13 @@ pub fn launch_game(game_id: String, state: State<AppState>) -> Result<LaunchResu
         None => {
             // For Windows platform, launch the game directly without an emulator
             if game.platform_id == "windows" {
-                return launch_windows_game_direct(&game, &state);
+                return launch_windows_game_direct(game, state);
             }
             return Ok(LaunchResult {
                 success: false,
                 pid: None,
                 error: Some("No emulator configured for this game or platform".to_string()),
+                warnings: Vec::new(),
             });
         }
     };
@@ -1103,7 +2673,81 @@ pub fn launch_game(game_id: String, state: State<AppState>) -> Result<LaunchResu
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Emulator not found".to_string())?;
 
-    launch_game_with_emulator_internal(&game, &emulator, &state)
+    launch_game_with_emulator_internal(game, &emulator, state)
+}
+
+/// Pick a random game matching `filter` and launch it with its configured emulator.
+/// Returns None if no game matches.
+#[tauri::command]
+pub fn launch_random_game(filter: Option<SmartCollectionRule>, state: State<AppState>) -> Result<Option<RandomLaunchResult>, String> {
+    launch_random_with(&state.db, &filter.unwrap_or_default(), |game| launch_with_configured_emulator(game, &state))
+}
+
+fn launch_random_with(
+    db: &Database,
+    filter: &SmartCollectionRule,
+    launch: impl FnOnce(&Game) -> Result<LaunchResult, String>,
+) -> Result<Option<RandomLaunchResult>, String> {
+    let Some(game) = pick_random_game(db, filter)? else {
+        return Ok(None);
+    };
+    let launch_result = launch(&game)?;
+    Ok(Some(RandomLaunchResult { game, launch_result }))
+}
+
+// ==================== LAUNCH QUEUE ====================
+
+/// Setting that starts the next queued game when the current one's emulator exits
+const AUTO_LAUNCH_QUEUE_SETTING: &str = "auto_launch_queue";
+
+/// Add a game to the end of the launch queue. Returns the queue's new length.
+#[tauri::command]
+pub fn enqueue_game(game_id: String, state: State<AppState>) -> Result<usize, String> {
+    enqueue(&state, game_id)
+}
+
+fn enqueue(state: &AppState, game_id: String) -> Result<usize, String> {
+    if state.db.get_game(&game_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Game not found".to_string());
+    }
+    let mut queue = state.launch_queue.lock().unwrap();
+    queue.push_back(game_id);
+    Ok(queue.len())
+}
+
+/// Queued games, next up first. Games deleted since they were queued are left out.
+#[tauri::command]
+pub fn get_launch_queue(state: State<AppState>) -> Result<Vec<Game>, String> {
+    let ids: Vec<String> = state.launch_queue.lock().unwrap().iter().cloned().collect();
+    let mut games = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(game) = state.db.get_game(&id).map_err(|e| e.to_string())? {
+            games.push(game);
+        }
+    }
+    Ok(games)
+}
+
+/// Launch the game at the front of the queue, or return None if the queue is empty
+#[tauri::command]
+pub fn dequeue_and_launch(state: State<AppState>) -> Result<Option<LaunchResult>, String> {
+    launch_next_queued(&state).transpose()
+}
+
+fn launch_next_queued(state: &AppState) -> Option<Result<LaunchResult, String>> {
+    while let Some(game_id) = dequeue(state) {
+        match state.db.get_game(&game_id) {
+            Ok(Some(game)) => return Some(launch_with_configured_emulator(&game, state)),
+            // Deleted while queued
+            Ok(None) => continue,
+            Err(e) => return Some(Err(e.to_string())),
+        }
+    }
+    None
+}
+
+fn dequeue(state: &AppState) -> Option<String> {
+    state.launch_queue.lock().unwrap().pop_front()
 }
 
 #[tauri::command]
@@ -1123,616 +2767,4916 @@ pub fn launch_game_with_emulator(
     launch_game_with_emulator_internal(&game, &emulator, &state)
 }
 
-fn launch_game_with_emulator_internal(
-    game: &Game,
-    emulator: &Emulator,
-    state: &State<AppState>,
-) -> Result<LaunchResult, String> {
-    // Ensure ROM path is absolute (fixes Windows path resolution issues)
-    let rom_path = std::path::Path::new(&game.rom_path);
-    let mut absolute_rom_path = if rom_path.is_absolute() {
-        game.rom_path.clone()
-    } else {
-        rom_path.canonicalize()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| game.rom_path.clone())
-    };
+/// Check whether a boolean setting is switched on
+fn setting_enabled(db: &Database, key: &str) -> bool {
+    matches!(db.get_setting(key).ok().flatten().as_deref(), Some("true"))
+}
 
-    // For PS3 games, convert PS3_DISC.SFB path to EBOOT.BIN path
-    // RPCS3 expects: GameFolder/PS3_GAME/USRDIR/EBOOT.BIN
-    if game.platform_id == "ps3" {
-        let sfb_path = std::path::Path::new(&absolute_rom_path);
-        if sfb_path.file_name().and_then(|n| n.to_str()).map(|n| n.eq_ignore_ascii_case("PS3_DISC.SFB")).unwrap_or(false) {
-            if let Some(game_folder) = sfb_path.parent() {
-                let eboot_path = game_folder.join("PS3_GAME").join("USRDIR").join("EBOOT.BIN");
-                if eboot_path.exists() {
-                    absolute_rom_path = eboot_path.to_string_lossy().to_string();
-                }
-            }
-        }
-    }
+/// Tell optional integrations (Discord, the now-playing file) that a game has started
+fn announce_session_start(game: &Game, start_time: chrono::DateTime<chrono::Utc>, state: &AppState) {
+    set_discord_presence(game, start_time, state);
 
-    // For Wii U Loadiine games, convert meta.xml path to the .rpx file in code/ folder
-    // CEMU expects: GameFolder/code/game.rpx (or similar .rpx file)
-    if game.platform_id == "wiiu" {
-        let meta_path = std::path::Path::new(&absolute_rom_path);
-        if meta_path.file_name().and_then(|n| n.to_str()).map(|n| n.eq_ignore_ascii_case("meta.xml")).unwrap_or(false) {
-            // meta.xml is at GameFolder/meta/meta.xml, so parent.parent is game folder
-            if let Some(meta_dir) = meta_path.parent() {
-                if let Some(game_folder) = meta_dir.parent() {
-                    let code_dir = game_folder.join("code");
-                    if code_dir.exists() && code_dir.is_dir() {
-                        // Find the .rpx file in the code folder
-                        if let Ok(entries) = std::fs::read_dir(&code_dir) {
-                            for entry in entries.filter_map(|e| e.ok()) {
-                                let path = entry.path();
-                                if let Some(ext) = path.extension() {
-                                    if ext.to_str().map(|e| e.eq_ignore_ascii_case("rpx")).unwrap_or(false) {
-                                        absolute_rom_path = path.to_string_lossy().to_string();
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    if setting_enabled(&state.db, "now_playing_file") {
+        if let Err(e) = write_now_playing_file(game, start_time, state) {
+            eprintln!("Failed to write now-playing file: {}", e);
         }
     }
+}
 
-    // On Windows, escape backslashes so shell_words doesn't interpret them as escape chars
-    #[cfg(target_os = "windows")]
-    let absolute_rom_path = absolute_rom_path.replace("\\", "\\\\");
-
-    #[cfg(target_os = "windows")]
-    let game_title = game.title.replace("\\", "\\\\");
-    #[cfg(not(target_os = "windows"))]
-    let game_title = game.title.clone();
-
-    // Auto-quote paths/titles with spaces if not already quoted in template
-    let rom_for_template = if absolute_rom_path.contains(' ') && !emulator.launch_arguments.contains("\"{rom}\"") {
-        format!("\"{}\"", absolute_rom_path)
-    } else {
-        absolute_rom_path
-    };
+/// Display name of a platform, falling back to its ID
+fn platform_display_name(db: &Database, platform_id: &str) -> String {
+    db.get_platform(platform_id).ok().flatten()
+        .map(|p| p.display_name)
+        .unwrap_or_else(|| platform_id.to_string())
+}
 
-    let title_for_template = if game_title.contains(' ') && !emulator.launch_arguments.contains("\"{title}\"") {
-        format!("\"{}\"", game_title)
-    } else {
-        game_title
+/// Show the game on the user's Discord profile when the `discord_presence` setting is on
+fn set_discord_presence(game: &Game, start_time: chrono::DateTime<chrono::Utc>, state: &AppState) {
+    if !setting_enabled(&state.db, "discord_presence") {
+        return;
+    }
+    let Some(application_id) = state.db.get_setting("discord_application_id").ok().flatten() else {
+        return;
     };
 
-    // Build the command arguments
-    let args_template = emulator.launch_arguments
-        .replace("{rom}", &rom_for_template)
-        .replace("{title}", &title_for_template);
+    let platform_name = platform_display_name(&state.db, &game.platform_id);
+    state.presence.set(&application_id, &build_presence(game, &platform_name, start_time));
+}
 
-    // Parse arguments properly handling quoted strings
-    let args: Vec<String> = match shell_words::split(&args_template) {
-        Ok(args) => args,
-        Err(e) => return Ok(LaunchResult {
-            success: false,
-            pid: None,
-            error: Some(format!("Failed to parse launch arguments: {}", e)),
-        }),
-    };
+/// File in app data describing the running game, for stream overlays (e.g., an OBS text source)
+const NOW_PLAYING_FILE: &str = "now_playing.json";
 
-    // Determine the actual executable path
-    let executable_path = get_executable_path(&emulator.executable_path)?;
+/// Contents of the now-playing file
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NowPlaying {
+    title: String,
+    platform: String,
+    cover_path: Option<String>,
+    start_time: String,
+}
 
-    // Launch the emulator
-    let result = Command::new(&executable_path)
-        .args(&args)
-        .spawn();
+fn write_now_playing_file(game: &Game, start_time: chrono::DateTime<chrono::Utc>, state: &AppState) -> Result<(), String> {
+    let now_playing = NowPlaying {
+        title: game.title.clone(),
+        platform: platform_display_name(&state.db, &game.platform_id),
+        cover_path: game.cover_art_path.clone(),
+        start_time: start_time.to_rfc3339(),
+    };
 
-    match result {
-        Ok(child) => {
-            let pid = child.id();
-
-            // Start tracking the session
-            let session = PlaySession::new(game.id.clone());
-            if let Err(e) = state.db.create_play_session(&session) {
-                eprintln!("Failed to create play session: {}", e);
-            }
-
-            // Store active session
-            {
-                let mut sessions = state.active_sessions.lock().unwrap();
-                sessions.insert(game.id.clone(), ActiveSession {
-                    session_id: session.id,
-                    game_id: game.id.clone(),
-                    start_time: chrono::Utc::now(),
-                    pid: Some(pid),
-                });
-            }
+    let json = serde_json::to_string_pretty(&now_playing).map_err(|e| e.to_string())?;
+    std::fs::write(state.app_data_dir.join(NOW_PLAYING_FILE), json).map_err(|e| e.to_string())
+}
 
-            Ok(LaunchResult {
-                success: true,
-                pid: Some(pid),
-                error: None,
-            })
+/// Remove the now-playing file, e.g. when a session ends or a stale one is left from a crash
+pub fn clear_now_playing_file(app_data_dir: &Path) {
+    let path = app_data_dir.join(NOW_PLAYING_FILE);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("Failed to remove now-playing file: {}", e);
         }
-        Err(e) => Ok(LaunchResult {
-            success: false,
-            pid: None,
-            error: Some(e.to_string()),
-        }),
     }
 }
 
-#[tauri::command]
-pub fn end_game_session(game_id: String, state: State<AppState>) -> Result<(), String> {
-    let mut sessions = state.active_sessions.lock().unwrap();
-
-    if let Some(session) = sessions.remove(&game_id) {
-        let end_time = chrono::Utc::now();
-        let duration = (end_time - session.start_time).num_seconds();
-
-        // End the play session
-        state.db.end_play_session(
-            &session.session_id,
-            &end_time.to_rfc3339(),
-            duration,
-        ).map_err(|e| e.to_string())?;
-
-        // Update game's total play time
-        state.db.update_game_play_time(&game_id, duration).map_err(|e| e.to_string())?;
+/// Expand an emulator's launch template into the argv passed to the executable
+fn build_launch_args(
+    emulator: &Emulator,
+    rom_path: String,
+    title: &str,
+    fullscreen: bool,
+) -> Result<Vec<String>, String> {
+    let mut args = expand_launch_template(&emulator.launch_arguments, rom_path, title)
+        .map_err(|e| format!("Failed to parse launch arguments: {}", e))?;
+
+    // Append the emulator's fullscreen flag when fullscreen launching is enabled
+    if fullscreen {
+        if let Some(fullscreen_argument) = &emulator.fullscreen_argument {
+            let fullscreen_args = shell_words::split(fullscreen_argument)
+                .map_err(|e| format!("Failed to parse fullscreen argument: {}", e))?;
+            args.extend(fullscreen_args);
+        }
     }
 
-    Ok(())
+    Ok(args)
 }
 
-// ==================== PLAY SESSION COMMANDS ====================
+/// Settings holding the resolution emulators are launched at, for those with a resolution template
+const LAUNCH_WIDTH_SETTING: &str = "launch_resolution_width";
+const LAUNCH_HEIGHT_SETTING: &str = "launch_resolution_height";
 
-#[tauri::command]
-pub fn get_play_sessions(game_id: String, state: State<AppState>) -> Result<Vec<PlaySession>, String> {
-    state.db.get_play_sessions(&game_id).map_err(|e| e.to_string())
+/// The configured launch resolution, if both dimensions are set
+fn launch_resolution(db: &Database) -> Option<(u32, u32)> {
+    let dimension = |key| db.get_setting(key).ok().flatten()?.trim().parse::<u32>().ok().filter(|v| *v > 0);
+    Some((dimension(LAUNCH_WIDTH_SETTING)?, dimension(LAUNCH_HEIGHT_SETTING)?))
 }
 
-// ==================== UTILITY COMMANDS ====================
+/// Expand the emulator's resolution template for `resolution`. Nothing is added unless both
+/// the template and a resolution are set.
+fn resolution_args(emulator: &Emulator, resolution: Option<(u32, u32)>) -> Result<Vec<String>, String> {
+    let (Some(template), Some((width, height))) = (&emulator.resolution_argument_template, resolution) else {
+        return Ok(Vec::new());
+    };
 
-#[tauri::command]
-pub fn validate_emulator_path(path: String) -> Result<bool, String> {
-    let path = Path::new(&path);
+    let args = shell_words::split(template)
+        .map_err(|e| format!("Failed to parse resolution arguments: {}", e))?;
+    Ok(args.iter()
+        .map(|arg| arg.replace("{w}", &width.to_string()).replace("{h}", &height.to_string()))
+        .collect())
+}
 
-    if !path.exists() {
-        return Ok(false);
+/// Environment variables SDL reads to choose the display it goes fullscreen on
+/// (SDL 1.2, and SDL 2 on X11), which covers most emulators without a display flag
+const SDL_DISPLAY_ENV_VARS: [&str; 2] = ["SDL_VIDEO_FULLSCREEN_DISPLAY", "SDL_VIDEO_FULLSCREEN_HEAD"];
+
+/// Extra launch arguments, and environment variables to set
+type DisplayOverrides = (Vec<String>, Vec<(&'static str, String)>);
+
+/// Arguments and environment variables that open the emulator on its preferred display.
+/// The emulator's display flag is used when it has one; the SDL variables are always set.
+fn display_overrides(emulator: &Emulator) -> Result<DisplayOverrides, String> {
+    let Some(display) = emulator.preferred_display else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let display = display.to_string();
+
+    let args = match &emulator.display_argument {
+        Some(template) => shell_words::split(template)
+            .map_err(|e| format!("Failed to parse display argument: {}", e))?
+            .iter()
+            .map(|arg| arg.replace("{display}", &display))
+            .collect(),
+        None => Vec::new(),
+    };
+    let env = SDL_DISPLAY_ENV_VARS.iter().map(|name| (*name, display.clone())).collect();
+
+    Ok((args, env))
+}
+
+/// Build the full argv for an emulator: `[wrapper..., executable, args...]`
+fn build_command_line(emulator: &Emulator, executable_path: String, args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut argv = match &emulator.wrapper_command {
+        Some(wrapper) => shell_words::split(wrapper)
+            .map_err(|e| format!("Failed to parse wrapper command: {}", e))?,
+        None => Vec::new(),
+    };
+
+    argv.push(executable_path);
+    argv.extend(args);
+    Ok(argv)
+}
+
+/// Split a command template into argv, then substitute {rom} and {title} into each element.
+///
+/// Splitting before substituting means the ROM path and title never pass through shell
+/// parsing, so backslashes and spaces in Windows paths reach the emulator unchanged.
+fn expand_launch_template(template: &str, rom_path: String, title: &str) -> Result<Vec<String>, shell_words::ParseError> {
+    let args = shell_words::split(template)?;
+
+    Ok(args
+        .iter()
+        .map(|arg| substitute_placeholders(arg, &rom_path, title))
+        .collect())
+}
+
+/// Replace {rom} and {title} in a single argument, without rescanning substituted text
+fn substitute_placeholders(arg: &str, rom_path: &str, title: &str) -> String {
+    let mut result = String::with_capacity(arg.len());
+    let mut rest = arg;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+
+        if let Some(after) = candidate.strip_prefix("{rom}") {
+            result.push_str(rom_path);
+            rest = after;
+        } else if let Some(after) = candidate.strip_prefix("{title}") {
+            result.push_str(title);
+            rest = after;
+        } else {
+            result.push('{');
+            rest = &candidate[1..];
+        }
     }
 
-    // On macOS, .app bundles are directories, not files
-    #[cfg(target_os = "macos")]
+    result.push_str(rest);
+    result
+}
+
+/// Run a hook command template to completion, returning an error describing any failure
+fn run_hook(template: &str, rom_path: &str, title: &str) -> Result<(), String> {
+    let argv = expand_launch_template(template, rom_path.to_string(), title)
+        .map_err(|e| format!("Failed to parse hook command: {}", e))?;
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| "Hook command is empty".to_string())?;
+
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run hook '{}': {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Hook '{}' exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get a hook command from settings, ignoring blank values
+fn get_hook_setting(db: &Database, key: &str) -> Option<String> {
+    db.get_setting(key)
+        .ok()
+        .flatten()
+        .filter(|command| !command.trim().is_empty())
+}
+
+/// Run the pre-launch hook if configured. Failures are returned as warnings
+/// so a broken hook never blocks the game from starting.
+fn run_pre_launch_hook(game: &Game, rom_path: &str, state: &AppState) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(hook) = get_hook_setting(&state.db, "pre_launch_command") {
+        if let Err(e) = run_hook(&hook, rom_path, &game.title) {
+            eprintln!("Pre-launch hook failed: {}", e);
+            warnings.push(format!("Pre-launch command failed: {}", e));
+        }
+    }
+    warnings
+}
+
+/// Launch a game by opening its expanded launch template as a URI
+///
+/// The URI handler runs detached, with no process to watch and nothing to say when the
+/// game closes, so no play session is tracked; the game is only marked as last played.
+fn launch_uri(
+    game: &Game,
+    emulator: &Emulator,
+    rom_path: &str,
+    state: &AppState,
+    open: impl FnOnce(&str) -> Result<(), String>,
+) -> LaunchResult {
+    let uri = emulator.launch_arguments
+        .trim()
+        .replace("{rom}", &encode_uri_component(rom_path))
+        .replace("{title}", &encode_uri_component(&game.title));
+
+    let warnings = run_pre_launch_hook(game, rom_path, state);
+
+    if let Err(e) = open(&uri) {
+        return LaunchResult {
+            success: false,
+            pid: None,
+            error: Some(format!("Failed to open {}: {}", uri, e)),
+            warnings,
+        };
+    }
+
+    if let Err(e) = state.db.update_game_play_time(&game.id, 0) {
+        eprintln!("Failed to update last played: {}", e);
+    }
+
+    LaunchResult {
+        success: true,
+        pid: None,
+        error: None,
+        warnings,
+    }
+}
+
+/// Hand a URI to the OS handler for its scheme
+#[cfg(not(test))]
+fn open_uri(uri: &str) -> Result<(), String> {
+    tauri_plugin_opener::open_url(uri, None::<&str>).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+thread_local! {
+    /// URIs tests have "opened", in order, in place of starting real handlers
+    static OPENED_URIS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+#[cfg(test)]
+fn open_uri(uri: &str) -> Result<(), String> {
+    OPENED_URIS.with(|opened| opened.borrow_mut().push(uri.to_string()));
+    Ok(())
+}
+
+/// Percent-encode a value substituted into a URI, leaving only unreserved characters as-is
+fn encode_uri_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// How often the process monitor checks whether an emulator has exited
+const PROCESS_MONITOR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Record a new play session for a spawned process and start monitoring it
+fn track_launched_process(game: &Game, rom_path: &str, child: Child, state: &AppState) -> u32 {
+    let pid = child.id();
+
+    // Start tracking the session
+    let session = PlaySession::new(game.id.clone());
+    if let Err(e) = state.db.create_play_session(&session) {
+        eprintln!("Failed to create play session: {}", e);
+    }
+
+    // Store active session
+    let start_time = chrono::Utc::now();
     {
-        // Accept .app bundles (directories) or regular executable files
-        if path.is_dir() {
-            // Check if it's a .app bundle
-            if let Some(ext) = path.extension() {
-                if ext == "app" {
-                    return Ok(true);
+        let mut sessions = state.active_sessions.lock().unwrap();
+        sessions.insert(game.id.clone(), ActiveSession {
+            session_id: session.id.clone(),
+            game_id: game.id.clone(),
+            start_time,
+            pid: Some(pid),
+            child: Some(child),
+            idle_seconds: 0,
+            focused_seconds: None,
+        });
+    }
+
+    announce_session_start(game, start_time, state);
+
+    spawn_process_monitor(state.clone(), game.clone(), rom_path.to_string(), session.id, pid);
+
+    pid
+}
+
+/// Setting that turns on subtracting idle time from play sessions
+const TRACK_IDLE_SETTING: &str = "track_idle";
+/// Minutes an emulator must sit idle before the time stops counting
+const IDLE_THRESHOLD_SETTING: &str = "idle_threshold_minutes";
+/// Setting that only counts time while the emulator is the foreground window
+const ACCURATE_PLAYTIME_SETTING: &str = "accurate_playtime";
+
+/// Idle tracking for a new session, or None when it's turned off
+fn idle_tracking(db: &Database, pid: u32) -> Option<(crate::idle::IdleTracker, crate::idle::CpuSampler)> {
+    if !setting_enabled(db, TRACK_IDLE_SETTING) {
+        return None;
+    }
+    let threshold = db.get_setting(IDLE_THRESHOLD_SETTING).ok().flatten()
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|minutes| Duration::from_secs(minutes * 60))
+        .unwrap_or(crate::idle::DEFAULT_IDLE_THRESHOLD);
+    Some((crate::idle::IdleTracker::new(threshold), crate::idle::CpuSampler::new(pid)))
+}
+
+/// Watch a launched process in the background. When it exits, the play session
+/// is closed out and the post-exit hook runs. With idle tracking on, the process's CPU
+/// usage is sampled along the way and long idle stretches are left out of the session.
+/// With accurate playtime on, only time spent as the foreground window counts, and the
+/// stored duration is updated each time the emulator loses focus.
+fn spawn_process_monitor(state: AppState, game: Game, rom_path: String, session_id: String, pid: u32) {
+    std::thread::spawn(move || {
+        let mut idle = idle_tracking(&state.db, pid);
+        let mut focus = setting_enabled(&state.db, ACCURATE_PLAYTIME_SETTING)
+            .then(|| crate::focus::FocusWatcher::new(pid));
+        let mut last_sample = std::time::Instant::now();
+        let mut idle_seconds = 0;
+
+        loop {
+            std::thread::sleep(PROCESS_MONITOR_INTERVAL);
+
+            // Sample outside the sessions lock; refreshing process info can take a moment
+            let segment_closed = focus.as_mut().is_some_and(|watcher| watcher.poll());
+            let focused = focus.as_ref().is_none_or(|watcher| watcher.is_focused());
+
+            if let Some((tracker, sampler)) = idle.as_mut() {
+                let elapsed = last_sample.elapsed();
+                if elapsed >= crate::idle::SAMPLE_INTERVAL {
+                    match sampler.sample() {
+                        // Unfocused time is already left out, so it can't also count as idle
+                        Some(_) if !focused => tracker.interrupt(),
+                        Some(cpu) => tracker.sample(cpu, elapsed),
+                        None => {}
+                    }
+                    idle_seconds = tracker.idle_seconds();
+                    last_sample = std::time::Instant::now();
                 }
             }
-            return Ok(false);
+
+            let focused_seconds = focus.as_ref().map(|watcher| watcher.focused_seconds());
+            if let (true, Some(focused_seconds)) = (segment_closed, focused_seconds) {
+                let played = (focused_seconds - idle_seconds).max(0);
+                if let Err(e) = state.db.update_play_session_duration(&session_id, played) {
+                    eprintln!("Failed to save play session progress: {}", e);
+                }
+            }
+
+            let mut sessions = state.active_sessions.lock().unwrap();
+            match sessions.get_mut(&game.id) {
+                // Still our session; keep waiting while the process is alive
+                Some(session) if session.session_id == session_id => {
+                    session.idle_seconds = idle_seconds;
+                    session.focused_seconds = focused_seconds;
+                    if !session.is_running() {
+                        break;
+                    }
+                }
+                // Session was ended manually or replaced by a newer launch
+                _ => return,
+            }
         }
-        return Ok(path.is_file());
+
+        if let Err(e) = finish_game_session(&game.id, None, &state) {
+            eprintln!("Failed to end play session: {}", e);
+        }
+
+        if let Some(hook) = get_hook_setting(&state.db, "post_exit_command") {
+            if let Err(e) = run_hook(&hook, &rom_path, &game.title) {
+                eprintln!("Post-exit hook failed: {}", e);
+            }
+        }
+
+        if setting_enabled(&state.db, AUTO_LAUNCH_QUEUE_SETTING) {
+            if let Some(Err(e)) = launch_next_queued(&state) {
+                eprintln!("Failed to launch next queued game: {}", e);
+            }
+        }
+    });
+}
+
+fn launch_game_with_emulator_internal(
+    game: &Game,
+    emulator: &Emulator,
+    state: &AppState,
+) -> Result<LaunchResult, String> {
+    // Refuse to start a second instance while the previous one is still running
+    if is_game_running(&game.id, state) {
+        return Ok(already_running_result(game));
     }
 
-    // On Windows and Linux, just check if it's a file
-    #[cfg(not(target_os = "macos"))]
-    {
-        Ok(path.is_file())
+    // Ensure ROM path is absolute (fixes Windows path resolution issues)
+    let rom_path = std::path::Path::new(&game.rom_path);
+    let mut absolute_rom_path = if rom_path.is_absolute() {
+        game.rom_path.clone()
+    } else {
+        rom_path.canonicalize()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| game.rom_path.clone())
+    };
+
+    // For PS3 games, convert PS3_DISC.SFB path to EBOOT.BIN path
+    // RPCS3 expects: GameFolder/PS3_GAME/USRDIR/EBOOT.BIN
+    if game.platform_id == "ps3" {
+        let sfb_path = std::path::Path::new(&absolute_rom_path);
+        if sfb_path.file_name().and_then(|n| n.to_str()).map(|n| n.eq_ignore_ascii_case("PS3_DISC.SFB")).unwrap_or(false) {
+            if let Some(game_folder) = sfb_path.parent() {
+                let eboot_path = game_folder.join("PS3_GAME").join("USRDIR").join("EBOOT.BIN");
+                if eboot_path.exists() {
+                    absolute_rom_path = eboot_path.to_string_lossy().to_string();
+                }
+            }
+        }
     }
-}
 
-#[tauri::command]
-pub fn get_rom_info(rom_path: String, state: State<AppState>) -> Result<Option<(String, String)>, String> {
-    let path = Path::new(&rom_path);
+    // For Wii U Loadiine games, convert meta.xml path to the .rpx file in code/ folder
+    // CEMU expects: GameFolder/code/game.rpx (or similar .rpx file)
+    if game.platform_id == "wiiu" {
+        let meta_path = std::path::Path::new(&absolute_rom_path);
+        if meta_path.file_name().and_then(|n| n.to_str()).map(|n| n.eq_ignore_ascii_case("meta.xml")).unwrap_or(false) {
+            // meta.xml is at GameFolder/meta/meta.xml, so parent.parent is game folder
+            if let Some(meta_dir) = meta_path.parent() {
+                if let Some(game_folder) = meta_dir.parent() {
+                    let code_dir = game_folder.join("code");
+                    if code_dir.exists() && code_dir.is_dir() {
+                        // Find the .rpx file in the code folder
+                        if let Ok(entries) = std::fs::read_dir(&code_dir) {
+                            for entry in entries.filter_map(|e| e.ok()) {
+                                let path = entry.path();
+                                if let Some(ext) = path.extension() {
+                                    if ext.to_str().map(|e| e.eq_ignore_ascii_case("rpx")).unwrap_or(false) {
+                                        absolute_rom_path = path.to_string_lossy().to_string();
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-    if !path.exists() {
-        return Ok(None);
+    // URI emulators hand off to the OS URI handler instead of spawning a process
+    if emulator.launch_type == LaunchType::Uri {
+        return Ok(launch_uri(game, emulator, &absolute_rom_path, state, open_uri));
     }
 
-    let extension = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| format!(".{}", e.to_lowercase()));
+    // Build the command arguments
+    let rom_path_for_hooks = absolute_rom_path.clone();
+    let fullscreen = setting_enabled(&state.db, "launch_fullscreen");
+    let resolution = launch_resolution(&state.db);
+    let launch_options = build_launch_args(emulator, absolute_rom_path, &game.title, fullscreen)
+        .and_then(|args| Ok((args, resolution_args(emulator, resolution)?, display_overrides(emulator)?)));
+    let (mut args, resolution_args, (display_args, display_env)) = match launch_options {
+        Ok(options) => options,
+        Err(e) => return Ok(LaunchResult {
+            success: false,
+            pid: None,
+            error: Some(e),
+            warnings: Vec::new(),
+        }),
+    };
+    args.extend(resolution_args);
+    args.extend(display_args);
 
-    if extension.is_none() {
-        return Ok(None);
+    // Determine the actual executable path
+    let executable_path = get_executable_path(&emulator.executable_path)?;
+
+    // Launch the emulator, through the wrapper command if one is configured
+    let argv = match build_command_line(emulator, executable_path, args) {
+        Ok(argv) => argv,
+        Err(e) => return Ok(LaunchResult {
+            success: false,
+            pid: None,
+            error: Some(e),
+            warnings: Vec::new(),
+        }),
+    };
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]);
+    command.envs(display_env);
+
+    // Redirect stdout/stderr to a log file so failed boots can be diagnosed
+    if emulator.capture_logs {
+        match create_launch_log(&state.app_data_dir, &game.id) {
+            Ok(log_file) => match log_file.try_clone() {
+                Ok(stderr_file) => {
+                    command.stdout(log_file).stderr(stderr_file);
+                }
+                Err(e) => eprintln!("Failed to open launch log: {}", e),
+            },
+            Err(e) => eprintln!("Failed to create launch log: {}", e),
+        }
     }
 
-    let ext = extension.unwrap();
-    let platforms = state.db.get_all_platforms().map_err(|e| e.to_string())?;
+    let warnings = run_pre_launch_hook(game, &rom_path_for_hooks, state);
 
-    for platform in platforms {
-        if platform.file_extensions.iter().any(|e| e.to_lowercase() == ext) {
-            let title = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
+    let result = command.spawn();
+
+    match result {
+        Ok(child) => {
+            let pid = track_launched_process(game, &rom_path_for_hooks, child, state);
 
-            return Ok(Some((clean_rom_title(&title), platform.id)));
+            Ok(LaunchResult {
+                success: true,
+                pid: Some(pid),
+                error: None,
+                warnings,
+            })
         }
+        Err(e) => Ok(LaunchResult {
+            success: false,
+            pid: None,
+            error: Some(e.to_string()),
+            warnings,
+        }),
     }
+}
 
-    Ok(None)
+#[tauri::command]
+pub fn end_game_session(game_id: String, note: Option<String>, state: State<AppState>) -> Result<(), String> {
+    finish_game_session(&game_id, note.as_deref(), &state)
 }
 
-// ==================== SETTINGS COMMANDS ====================
+/// Close out a game's active session, recording its duration, play time and optional note
+fn finish_game_session(game_id: &str, note: Option<&str>, state: &AppState) -> Result<(), String> {
+    let session = state.active_sessions.lock().unwrap().remove(game_id);
 
-#[tauri::command]
-pub fn get_setting(key: String, state: State<AppState>) -> Result<Option<String>, String> {
-    state.db.get_setting(&key).map_err(|e| e.to_string())
+    if let Some(session) = session {
+        let end_time = chrono::Utc::now();
+        let gross_duration = (end_time - session.start_time).num_seconds();
+        let played = session.focused_seconds.unwrap_or(gross_duration);
+        let duration = (played - session.idle_seconds).max(0);
+
+        // End the play session
+        state.db.end_play_session(
+            &session.session_id,
+            &end_time.to_rfc3339(),
+            duration,
+            gross_duration,
+            note,
+        ).map_err(|e| e.to_string())?;
+
+        // Update game's total play time
+        state.db.update_game_play_time(game_id, duration).map_err(|e| e.to_string())?;
+
+        state.presence.clear();
+        clear_now_playing_file(&state.app_data_dir);
+    }
+
+    Ok(())
 }
 
-#[tauri::command]
-pub fn set_setting(key: String, value: String, state: State<AppState>) -> Result<(), String> {
-    state.db.set_setting(&key, &value).map_err(|e| e.to_string())
+// ==================== LAUNCH LOGS ====================
+
+/// Create a new log file for a launch at logs/{game_id}-{timestamp}.log. The timestamp
+/// includes milliseconds so quick relaunches don't overwrite each other's logs.
+fn create_launch_log(app_data_dir: &Path, game_id: &str) -> std::io::Result<std::fs::File> {
+    let logs_dir = app_data_dir.join("logs");
+    std::fs::create_dir_all(&logs_dir)?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S-%3f");
+    std::fs::File::create(logs_dir.join(format!("{}-{}.log", game_id, timestamp)))
 }
 
-// ==================== RETROARCH COMMANDS ====================
+/// Read the most recent launch log for a game, if one exists
+fn read_latest_launch_log(app_data_dir: &Path, game_id: &str) -> std::io::Result<Option<String>> {
+    let logs_dir = app_data_dir.join("logs");
+    if !logs_dir.exists() {
+        return Ok(None);
+    }
 
-/// Information about a RetroArch core
-#[derive(Debug, Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct RetroArchCore {
-    pub file_name: String,
-    pub display_name: String,
-    pub full_path: String,
+    // Timestamps sort lexically, so the greatest file name is the latest log
+    let prefix = format!("{}-", game_id);
+    let latest = std::fs::read_dir(&logs_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with(&prefix) && n.ends_with(".log"))
+                .unwrap_or(false)
+        })
+        .max();
+
+    match latest {
+        Some(path) => Ok(Some(std::fs::read_to_string(path)?)),
+        None => Ok(None),
+    }
 }
 
-/// Get the default RetroArch cores folder path
+/// Get the captured stdout/stderr from the latest launch of a game
 #[tauri::command]
-pub fn get_default_retroarch_cores_path() -> Result<Option<String>, String> {
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = dirs::home_dir() {
-            let cores_path = home.join("Library/Application Support/RetroArch/cores");
-            if cores_path.exists() {
-                return Ok(Some(cores_path.to_string_lossy().to_string()));
-            }
-        }
-    }
+pub fn get_launch_log(game_id: String, state: State<AppState>) -> Result<Option<String>, String> {
+    read_latest_launch_log(&state.app_data_dir, &game_id).map_err(|e| e.to_string())
+}
 
-    #[cfg(target_os = "windows")]
+// ==================== PLAY SESSION COMMANDS ====================
+
+#[tauri::command]
+pub fn get_play_sessions(game_id: String, state: State<AppState>) -> Result<Vec<PlaySession>, String> {
+    state.db.get_play_sessions(&game_id).map_err(|e| e.to_string())
+}
+
+/// Log a completed session played outside RetroVoid (e.g., on original hardware)
+#[tauri::command]
+pub fn add_manual_play_session(
+    game_id: String,
+    start_time: String,
+    duration_seconds: i64,
+    note: Option<String>,
+    state: State<AppState>,
+) -> Result<PlaySession, String> {
+    record_manual_play_session(&state.db, &game_id, &start_time, duration_seconds, note)
+}
+
+fn record_manual_play_session(
+    db: &Database,
+    game_id: &str,
+    start_time: &str,
+    duration_seconds: i64,
+    note: Option<String>,
+) -> Result<PlaySession, String> {
+    if db.get_game(game_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Game not found".to_string());
+    }
+
+    let start = chrono::DateTime::parse_from_rfc3339(start_time)
+        .map_err(|e| format!("Invalid start time: {}", e))?
+        .with_timezone(&chrono::Utc);
+
+    if duration_seconds <= 0 {
+        return Err("Session must end after it starts".to_string());
+    }
+
+    let end = start + chrono::Duration::seconds(duration_seconds);
+    if end > chrono::Utc::now() {
+        return Err("Session cannot end in the future".to_string());
+    }
+
+    let session = PlaySession {
+        id: uuid::Uuid::new_v4().to_string(),
+        game_id: game_id.to_string(),
+        start_time: start.to_rfc3339(),
+        end_time: Some(end.to_rfc3339()),
+        duration_seconds,
+        note,
+        gross_duration_seconds: None,
+    };
+    db.create_play_session(&session).map_err(|e| e.to_string())?;
+
+    // last_played uses SQLite's CURRENT_TIMESTAMP format so values compare correctly
+    let played_at = end.format("%Y-%m-%d %H:%M:%S").to_string();
+    db.add_past_play_time(game_id, duration_seconds, &played_at).map_err(|e| e.to_string())?;
+
+    Ok(session)
+}
+
+/// Delete a play session (e.g., an accidental launch) and recompute the game's play time
+#[tauri::command]
+pub fn delete_play_session(session_id: String, state: State<AppState>) -> Result<(), String> {
+    remove_play_session(&state.db, &session_id)
+}
+
+fn remove_play_session(db: &Database, session_id: &str) -> Result<(), String> {
+    let session = db.get_play_session(session_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Play session not found")?;
+
+    db.delete_play_session(session_id).map_err(|e| e.to_string())?;
+    db.recompute_game_play_time(&session.game_id).map_err(|e| e.to_string())
+}
+
+/// Change a play session's duration and recompute the game's play time
+#[tauri::command]
+pub fn update_play_session(session_id: String, duration_seconds: i64, state: State<AppState>) -> Result<(), String> {
+    change_play_session_duration(&state.db, &session_id, duration_seconds)
+}
+
+fn change_play_session_duration(db: &Database, session_id: &str, duration_seconds: i64) -> Result<(), String> {
+    if duration_seconds < 0 {
+        return Err("Duration cannot be negative".to_string());
+    }
+
+    let session = db.get_play_session(session_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Play session not found")?;
+
+    db.update_play_session_duration(session_id, duration_seconds).map_err(|e| e.to_string())?;
+    db.recompute_game_play_time(&session.game_id).map_err(|e| e.to_string())
+}
+
+/// Forget play history for one game, or the whole library when `game_id` is None.
+/// Returns the number of sessions deleted.
+#[tauri::command]
+pub fn clear_play_history(game_id: Option<String>, state: State<AppState>) -> Result<usize, String> {
+    state.db.clear_play_history(game_id.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Play time per week or month, for charting trends. Returns (bucket, seconds) pairs, oldest first.
+#[tauri::command]
+pub fn get_playtime_report(
+    period: ReportPeriod,
+    from: Option<String>,
+    to: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<(String, i64)>, String> {
+    state.db.get_playtime_report(period, from.as_deref(), to.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Games ranked by time played over the last `days` days
+#[tauri::command]
+pub fn get_most_played_recent(days: u32, limit: u32, state: State<AppState>) -> Result<Vec<GamePlayTime>, String> {
+    find_most_played_since(&state.db, chrono::Utc::now() - chrono::Duration::days(days as i64), limit)
+}
+
+fn find_most_played_since(db: &Database, since: chrono::DateTime<chrono::Utc>, limit: u32) -> Result<Vec<GamePlayTime>, String> {
+    let totals = db.get_play_time_since(&since.to_rfc3339(), limit).map_err(|e| e.to_string())?;
+
+    let mut ranked = Vec::new();
+    for (game_id, play_time_seconds) in totals {
+        if let Some(game) = db.get_game(&game_id).map_err(|e| e.to_string())? {
+            ranked.push(GamePlayTime { game, play_time_seconds });
+        }
+    }
+    Ok(ranked)
+}
+
+/// Current and longest runs of consecutive days played, using the local calendar
+#[tauri::command]
+pub fn get_play_streak(state: State<AppState>) -> Result<PlayStreak, String> {
+    let start_times = state.db.get_play_session_start_times().map_err(|e| e.to_string())?;
+
+    let dates = start_times.iter()
+        .filter_map(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Local).date_naive())
+        .collect();
+
+    Ok(compute_play_streak(&dates, chrono::Local::now().date_naive()))
+}
+
+fn compute_play_streak(dates: &std::collections::BTreeSet<chrono::NaiveDate>, today: chrono::NaiveDate) -> PlayStreak {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<chrono::NaiveDate> = None;
+
+    for &date in dates {
+        run = match previous {
+            Some(p) if p.succ_opt() == Some(date) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(date);
+    }
+
+    // The last run is still going if it reaches today or yesterday
+    let yesterday = today.pred_opt();
+    let current = match previous {
+        Some(last) if last == today || Some(last) == yesterday => run,
+        _ => 0,
+    };
+
+    PlayStreak {
+        current_streak_days: current,
+        longest_streak_days: longest,
+    }
+}
+
+// ==================== SAVE STATE COMMANDS ====================
+
+/// Setting holding a JSON map of emulator id to save-state folder
+const SAVE_STATE_DIRS_SETTING: &str = "save_state_dirs";
+
+#[tauri::command]
+pub fn list_save_states(game_id: String, state: State<AppState>) -> Result<Vec<SaveState>, String> {
+    list_game_save_states(&state.db, &game_id)
+}
+
+#[tauri::command]
+pub fn delete_save_state(path: String, state: State<AppState>) -> Result<(), String> {
+    let overrides = save_state_dir_overrides(&state.db)?;
+    let known_dirs: Vec<PathBuf> = state.db.get_all_emulators()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|emulator| savestates::save_state_dir(emulator, &overrides))
+        .collect();
+
+    savestates::delete_save_state(Path::new(&path), &known_dirs)
+}
+
+/// Zip a game's save states and in-game saves into a timestamped archive in `dest_dir`.
+/// Returns the archive path.
+#[tauri::command]
+pub fn backup_save_states(game_id: String, dest_dir: String, state: State<AppState>) -> Result<String, String> {
+    backup_game_save_states(&state.db, &game_id, Path::new(&dest_dir))
+}
+
+/// Save states for a game, looked up in the folder of the emulator it launches with
+fn list_game_save_states(db: &Database, game_id: &str) -> Result<Vec<SaveState>, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    Ok(game_save_state_dir(db, &game)?
+        .map(|dir| savestates::find_save_states(&dir, &game.rom_path))
+        .unwrap_or_default())
+}
+
+fn backup_game_save_states(db: &Database, game_id: &str, dest_dir: &Path) -> Result<String, String> {
+    let game = db.get_game(game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    let state_dir = game_save_state_dir(db, &game)?
+        .ok_or_else(|| "No save-state folder known for this game's emulator".to_string())?;
+
+    let archive = savestates::backup_save_files(&savestates::backup_dirs(&state_dir), &game.rom_path, dest_dir, chrono::Local::now())?;
+    Ok(archive.to_string_lossy().to_string())
+}
+
+/// Save-state folder of the emulator a game launches with
+fn game_save_state_dir(db: &Database, game: &Game) -> Result<Option<PathBuf>, String> {
+    // Same emulator resolution as launch_game: the game's preferred, then the platform default
+    let emulator_id = game.preferred_emulator_id.clone()
+        .or_else(|| {
+            db.get_platform(&game.platform_id)
+                .ok()
+                .flatten()
+                .and_then(|p| p.default_emulator_id)
+        });
+    let Some(emulator) = emulator_id
+        .map(|id| db.get_emulator(&id))
+        .transpose()
+        .map_err(|e| e.to_string())?
+        .flatten()
+    else {
+        return Ok(None);
+    };
+
+    let overrides = save_state_dir_overrides(db)?;
+    Ok(savestates::save_state_dir(&emulator, &overrides))
+}
+
+fn save_state_dir_overrides(db: &Database) -> Result<HashMap<String, String>, String> {
+    Ok(db.get_setting_json(SAVE_STATE_DIRS_SETTING)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default())
+}
+
+// ==================== PERSONAL ACHIEVEMENT COMMANDS ====================
+
+#[tauri::command]
+pub fn get_achievements(game_id: String, state: State<AppState>) -> Result<Vec<CustomAchievement>, String> {
+    state.db.get_achievements(&game_id).map_err(|e| e.to_string())
+}
+
+/// Add a goal to a game's personal checklist
+#[tauri::command]
+pub fn add_achievement(input: CreateAchievementInput, state: State<AppState>) -> Result<CustomAchievement, String> {
+    create_achievement(&state.db, input)
+}
+
+fn create_achievement(db: &Database, input: CreateAchievementInput) -> Result<CustomAchievement, String> {
+    let title = input.title.trim();
+    if title.is_empty() {
+        return Err("Achievement title cannot be empty".to_string());
+    }
+    if db.get_game(&input.game_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("Game not found".to_string());
+    }
+
+    let achievement = CustomAchievement::new(input.game_id, title.to_string(), input.description);
+    db.add_achievement(&achievement).map_err(|e| e.to_string())?;
+    Ok(achievement)
+}
+
+/// Lock or unlock a personal achievement, returning whether it is now unlocked
+#[tauri::command]
+pub fn toggle_achievement(id: String, state: State<AppState>) -> Result<bool, String> {
+    state.db.toggle_achievement(&id, &chrono::Utc::now().to_rfc3339())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Achievement not found".to_string())
+}
+
+// ==================== UTILITY COMMANDS ====================
+
+#[tauri::command]
+pub fn validate_emulator_path(path: String) -> Result<bool, String> {
+    let path = Path::new(&path);
+
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    // On macOS, .app bundles are directories, not files
+    #[cfg(target_os = "macos")]
     {
-        // Common Windows paths for RetroArch
-        let possible_paths = [
-            dirs::config_dir().map(|p| p.join("RetroArch/cores")),
-            dirs::data_dir().map(|p| p.join("RetroArch/cores")),
-            Some(std::path::PathBuf::from("C:/RetroArch/cores")),
+        // Accept .app bundles (directories) or regular executable files
+        if path.is_dir() {
+            // Check if it's a .app bundle
+            if let Some(ext) = path.extension() {
+                if ext == "app" {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        return Ok(path.is_file());
+    }
+
+    // On Windows and Linux, just check if it's a file
+    #[cfg(not(target_os = "macos"))]
+    {
+        Ok(path.is_file())
+    }
+}
+
+#[tauri::command]
+pub fn get_rom_info(rom_path: String, state: State<AppState>) -> Result<Option<(String, String)>, String> {
+    let path = Path::new(&rom_path);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let Some(platform_id) = platform_for_extension(&state.db, path)? else {
+        return Ok(None);
+    };
+
+    let title = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+
+    Ok(Some((clean_rom_title_options(&title, &CleanTitleOptions::from_settings(&state.db)), platform_id)))
+}
+
+/// Check a ROM before adding it: whether it exists, can be read, and which platform claims its extension
+#[tauri::command]
+pub fn validate_rom_path(path: String, state: State<AppState>) -> Result<RomPathValidation, String> {
+    check_rom_path(&state.db, Path::new(&path))
+}
+
+fn check_rom_path(db: &Database, path: &Path) -> Result<RomPathValidation, String> {
+    let exists = path.exists();
+    let readable = if path.is_dir() {
+        std::fs::read_dir(path).is_ok()
+    } else {
+        std::fs::File::open(path).is_ok()
+    };
+
+    Ok(RomPathValidation {
+        exists,
+        readable,
+        detected_platform: platform_for_extension(db, path)?,
+    })
+}
+
+/// Title stored in a ROM's header (GB, GBA, SNES, Genesis), or None if the format has none
+#[tauri::command]
+pub fn read_internal_title(path: String) -> Option<String> {
+    rom_header::read_internal_title(Path::new(&path))
+}
+
+/// Titles this far apart are reported as mislabeled
+const MISLABELED_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Games on header-carrying platforms whose filename title differs clearly from the title in
+/// the ROM's header, e.g. a dump renamed to the wrong game
+#[tauri::command]
+pub fn find_mislabeled_roms(state: State<AppState>) -> Result<Vec<MislabeledRom>, String> {
+    mislabeled_roms(&state.db)
+}
+
+fn mislabeled_roms(db: &Database) -> Result<Vec<MislabeledRom>, String> {
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+
+    Ok(games.into_iter()
+        .filter(|game| rom_header::TITLED_PLATFORMS.contains(&game.platform_id.as_str()))
+        .filter_map(|game| {
+            let path = Path::new(&game.rom_path);
+            // Games scanned before titles were stored are read from disk
+            let internal_title = game.internal_title.clone()
+                .or_else(|| rom_header::read_internal_title(path))?;
+            let file_title = clean_rom_title(path.file_stem()?.to_str()?);
+
+            let similarity = title_similarity(&file_title, &internal_title);
+            (similarity < MISLABELED_SIMILARITY_THRESHOLD).then_some(MislabeledRom {
+                game_id: game.id,
+                file_title,
+                internal_title,
+                similarity,
+            })
+        })
+        .collect())
+}
+
+/// Fuzzy similarity of two titles. Header titles are short, upper-case and often truncated,
+/// so one title starting with the other counts as a match; otherwise the Dice coefficient
+/// of their letter pairs is used.
+fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (compact_title(a), compact_title(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a.starts_with(&b) || b.starts_with(&a) {
+        return 1.0;
+    }
+    dice_coefficient(&a, &b)
+}
+
+/// Dice coefficient of two titles' letter pairs, ignoring case, spacing and punctuation
+fn letter_pair_similarity(a: &str, b: &str) -> f64 {
+    dice_coefficient(&compact_title(a), &compact_title(b))
+}
+
+/// A title's letters and digits in lowercase, for comparing titles
+fn compact_title(title: &str) -> Vec<char> {
+    title.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+fn dice_coefficient(a: &[char], b: &[char]) -> f64 {
+    let pairs = |chars: &[char]| chars.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>();
+    let (a_pairs, mut b_pairs) = (pairs(a), pairs(b));
+    let total = a_pairs.len() + b_pairs.len();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let mut shared = 0;
+    for pair in &a_pairs {
+        if let Some(index) = b_pairs.iter().position(|p| p == pair) {
+            b_pairs.swap_remove(index);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+/// Byte order of an N64 game's ROM, or None for other platforms and unrecognised dumps
+#[tauri::command]
+pub fn get_n64_byte_order(game_id: String, state: State<AppState>) -> Result<Option<N64ByteOrder>, String> {
+    let game = state.db.get_game(&game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Game not found: {}", game_id))?;
+    if game.platform_id != "n64" {
+        return Ok(None);
+    }
+
+    let mut header = [0u8; 4];
+    let mut file = std::fs::File::open(&game.rom_path).map_err(|e| e.to_string())?;
+    std::io::Read::read_exact(&mut file, &mut header).map_err(|e| e.to_string())?;
+    Ok(signatures::n64_byte_order(&header))
+}
+
+/// Write a copy of an N64 ROM in the given byte order beside the original, returning its path
+#[tauri::command]
+pub fn convert_n64_byteorder(path: String, target: N64ByteOrder) -> Result<String, String> {
+    write_n64_copy(Path::new(&path), target).map(|dest| dest.to_string_lossy().to_string())
+}
+
+fn write_n64_copy(path: &Path, target: N64ByteOrder) -> Result<PathBuf, String> {
+    let mut data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let source = signatures::n64_byte_order(&data)
+        .ok_or_else(|| format!("Not an N64 ROM: {}", path.display()))?;
+    if source == target {
+        return Err("ROM is already in that byte order".to_string());
+    }
+
+    let dest = path.with_extension(target.extension());
+    if dest.exists() {
+        return Err(format!("File already exists: {}", dest.display()));
+    }
+
+    signatures::convert_n64_bytes(&mut data, source, target);
+    std::fs::write(&dest, data).map_err(|e| e.to_string())?;
+    Ok(dest)
+}
+
+/// The first platform whose extensions include the path's extension
+fn platform_for_extension(db: &Database, path: &Path) -> Result<Option<String>, String> {
+    let Some(ext) = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e.to_lowercase()))
+    else {
+        return Ok(None);
+    };
+
+    let platforms = db.get_all_platforms().map_err(|e| e.to_string())?;
+    Ok(platforms.into_iter()
+        .find(|platform| platform.file_extensions.iter().any(|e| e.to_lowercase() == ext))
+        .map(|platform| platform.id))
+}
+
+/// Re-encode downloaded covers, backgrounds and screenshots as WebP to save disk space
+#[tauri::command]
+pub fn convert_images_to_webp(state: State<AppState>) -> Result<ImageConversionResult, String> {
+    convert_library_images(&state.db, &state.app_data_dir.join("images"))
+}
+
+/// Convert every game image stored under `images_dir` to WebP, updating the stored paths.
+/// Images elsewhere on disk belong to the user and are left alone.
+fn convert_library_images(db: &Database, images_dir: &Path) -> Result<ImageConversionResult, String> {
+    let mut result = ImageConversionResult {
+        images_converted: 0,
+        bytes_saved: 0,
+        errors: Vec::new(),
+    };
+
+    let mut convert = |path: &str| -> Option<String> {
+        let source = Path::new(path);
+        if !source.starts_with(images_dir) || crate::webp::is_webp(source) || !source.exists() {
+            return None;
+        }
+        match crate::webp::convert_to_webp(source) {
+            Ok(Some((dest, saved))) => {
+                result.images_converted += 1;
+                result.bytes_saved += saved;
+                Some(dest.to_string_lossy().to_string())
+            }
+            Ok(None) => None,
+            Err(e) => {
+                result.errors.push(format!("{}: {}", path, e));
+                None
+            }
+        }
+    };
+
+    for game in db.get_all_games().map_err(|e| e.to_string())? {
+        let mut updates = UpdateGameInput {
+            cover_art_path: game.cover_art_path.as_deref().and_then(&mut convert),
+            background_path: game.background_path.as_deref().and_then(&mut convert),
+            ..Default::default()
+        };
+
+        let screenshots: Vec<(String, Option<String>)> = game.screenshots.iter()
+            .map(|path| (path.clone(), convert(path)))
+            .collect();
+        if screenshots.iter().any(|(_, converted)| converted.is_some()) {
+            updates.screenshots = Some(screenshots.into_iter()
+                .map(|(original, converted)| converted.unwrap_or(original))
+                .collect());
+        }
+
+        db.update_game(&game.id, &updates).map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+/// Game images that are empty or fail to decode, e.g. truncated by an interrupted download.
+/// With `clear`, their paths are removed from the games and the files deleted.
+#[tauri::command]
+pub fn find_corrupt_images(clear: Option<bool>, state: State<AppState>) -> Result<Vec<CorruptImage>, String> {
+    corrupt_images(&state.db, &state.app_data_dir, clear.unwrap_or(false))
+}
+
+fn corrupt_images(db: &Database, app_data_dir: &Path, clear: bool) -> Result<Vec<CorruptImage>, String> {
+    let mut found = Vec::new();
+
+    for game in db.get_all_games().map_err(|e| e.to_string())? {
+        // The bad artwork, for clearing and deleting afterwards
+        let mut bad = Game::new(game.title.clone(), game.rom_path.clone(), game.platform_id.clone());
+        let mut report = |field: ScrapeField, path: &str| -> bool {
+            let Some(reason) = image_problem(path) else {
+                return false;
+            };
+            found.push(CorruptImage { game_id: game.id.clone(), field, path: path.to_string(), reason });
+            true
+        };
+
+        bad.cover_art_path = game.cover_art_path.clone().filter(|path| report(ScrapeField::CoverArtPath, path));
+        bad.background_path = game.background_path.clone().filter(|path| report(ScrapeField::BackgroundPath, path));
+        bad.screenshots = game.screenshots.iter()
+            .filter(|path| report(ScrapeField::Screenshots, path))
+            .cloned()
+            .collect();
+
+        if !clear {
+            continue;
+        }
+        let mut fields = Vec::new();
+        if bad.cover_art_path.is_some() {
+            fields.push(ScrapeField::CoverArtPath);
+        }
+        if bad.background_path.is_some() {
+            fields.push(ScrapeField::BackgroundPath);
+        }
+        db.clear_game_fields(std::slice::from_ref(&game.id), &fields).map_err(|e| e.to_string())?;
+        if !bad.screenshots.is_empty() {
+            let updates = UpdateGameInput {
+                screenshots: Some(game.screenshots.into_iter().filter(|path| !bad.screenshots.contains(path)).collect()),
+                ..Default::default()
+            };
+            db.update_game(&game.id, &updates).map_err(|e| e.to_string())?;
+        }
+        remove_game_images(db, &bad, app_data_dir);
+    }
+
+    Ok(found)
+}
+
+/// Why an image won't display, or None if it decodes. Missing files aren't reported.
+fn image_problem(path: &str) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() == 0 {
+        return Some("File is empty".to_string());
+    }
+    // Guess from the content, since downloads don't always get the right extension
+    let reader = match image::ImageReader::open(path).and_then(|reader| reader.with_guessed_format()) {
+        Ok(reader) => reader,
+        Err(e) => return Some(e.to_string()),
+    };
+    reader.decode().err().map(|e| e.to_string())
+}
+
+/// Point games whose covers have identical bytes at one shared file under covers/shared
+/// and delete the copies
+#[tauri::command]
+pub fn dedup_cover_images(state: State<AppState>) -> Result<CoverDedupResult, String> {
+    dedup_library_covers(&state.db, &state.app_data_dir.join("images"))
+}
+
+/// Group covers stored under `images_dir` by content hash and move each group to one file
+/// named after the hash under covers/shared. Cover writers replace `{game_id}.ext` in place,
+/// so no game may be left sharing another game's file. A copy that some game also uses as
+/// its background stays on disk.
+fn dedup_library_covers(db: &Database, images_dir: &Path) -> Result<CoverDedupResult, String> {
+    let mut result = CoverDedupResult {
+        files_removed: 0,
+        games_updated: 0,
+        bytes_saved: 0,
+    };
+    let games = db.get_all_games().map_err(|e| e.to_string())?;
+
+    // Content hash -> every distinct path holding those bytes
+    let mut by_hash: HashMap<String, std::collections::BTreeSet<String>> = HashMap::new();
+    let mut users: HashMap<&str, usize> = HashMap::new();
+    for path in games.iter().filter_map(|game| game.cover_art_path.as_deref()) {
+        if !Path::new(path).starts_with(images_dir) {
+            continue;
+        }
+        *users.entry(path).or_default() += 1;
+        let Ok(contents) = std::fs::read(path) else {
+            continue;
+        };
+        by_hash.entry(format!("{:x}", md5::compute(&contents)))
+            .or_default()
+            .insert(path.to_string());
+    }
+
+    // Redundant path -> the shared file replacing it
+    let shared_dir = images_dir.join("covers").join("shared");
+    let mut bytes_written = 0;
+    let mut replacements: HashMap<String, String> = HashMap::new();
+    for (hash, paths) in &by_hash {
+        let first = paths.first().unwrap();
+        let extension = Path::new(first).extension().and_then(|e| e.to_str()).unwrap_or("jpg");
+        let shared = shared_dir.join(format!("{}.{}", hash, extension));
+        let shared_str = shared.to_string_lossy().to_string();
+        // A file only one game uses, or one already shared, can stay where it is
+        if paths.len() == 1 && (*first == shared_str || users[first.as_str()] < 2) {
+            continue;
+        }
+
+        if !shared.exists() {
+            std::fs::create_dir_all(&shared_dir)
+                .map_err(|e| format!("Failed to create shared covers directory: {}", e))?;
+            bytes_written += std::fs::copy(first, &shared)
+                .map_err(|e| format!("Failed to copy {}: {}", first, e))?;
+        }
+        for path in paths.iter().filter(|path| **path != shared_str) {
+            replacements.insert(path.clone(), shared_str.clone());
+        }
+    }
+
+    for game in &games {
+        let Some(shared) = game.cover_art_path.as_ref().and_then(|path| replacements.get(path)) else {
+            continue;
+        };
+        let updates = UpdateGameInput { cover_art_path: Some(shared.clone()), ..Default::default() };
+        db.update_game(&game.id, &updates).map_err(|e| e.to_string())?;
+        result.games_updated += 1;
+    }
+
+    let thumbnails_dir = images_dir.join("thumbnails");
+    for duplicate in replacements.keys() {
+        if db.is_artwork_in_use(duplicate).map_err(|e| e.to_string())? {
+            continue;
+        }
+        let size = std::fs::metadata(duplicate).map(|m| m.len()).unwrap_or(0);
+        crate::thumbnails::remove_thumbnails(&thumbnails_dir, Path::new(duplicate));
+        match std::fs::remove_file(duplicate) {
+            Ok(()) => {
+                result.files_removed += 1;
+                result.bytes_saved += size;
+            }
+            Err(e) => eprintln!("Failed to delete {}: {}", duplicate, e),
+        }
+    }
+    result.bytes_saved = result.bytes_saved.saturating_sub(bytes_written);
+
+    Ok(result)
+}
+
+/// Compact the database file and refresh its statistics
+#[tauri::command]
+pub fn optimize_database(state: State<AppState>) -> Result<OptimizeResult, String> {
+    state.db.optimize().map_err(|e| e.to_string())
+}
+
+/// Current database schema (migration) version
+#[tauri::command]
+pub fn get_schema_version(state: State<AppState>) -> i32 {
+    state.db.schema_version()
+}
+
+// ==================== SETTINGS COMMANDS ====================
+
+#[tauri::command]
+pub fn get_setting(key: String, state: State<AppState>) -> Result<Option<String>, String> {
+    state.db.get_setting(&key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_setting(key: String, value: String, state: State<AppState>) -> Result<(), String> {
+    state.db.set_setting(&key, &value).map_err(|e| e.to_string())
+}
+
+/// Settings left out of exports and never overwritten by imports: the schema version
+/// drives migrations, and credentials shouldn't travel in a plain file
+const NON_PORTABLE_SETTINGS: &[&str] = &["schema_version", "igdb_client_secret"];
+
+fn is_portable_setting(key: &str) -> bool {
+    // Encrypted secrets are tied to this machine's key
+    !NON_PORTABLE_SETTINGS.contains(&key) && !crate::secrets::is_secret_setting(key)
+}
+
+/// Write all portable settings to a JSON file
+#[tauri::command]
+pub fn export_settings(dest_path: String, state: State<AppState>) -> Result<(), String> {
+    write_settings_file(&state.db, Path::new(&dest_path))
+}
+
+fn write_settings_file(db: &Database, dest_path: &Path) -> Result<(), String> {
+    let settings: std::collections::BTreeMap<String, String> = db.get_all_settings()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(key, _)| is_portable_setting(key))
+        .collect();
+
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(dest_path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+/// Restore settings from a JSON file written by export_settings, returning how many were applied
+#[tauri::command]
+pub fn import_settings(src_path: String, state: State<AppState>) -> Result<usize, String> {
+    read_settings_file(&state.db, Path::new(&src_path))
+}
+
+fn read_settings_file(db: &Database, src_path: &Path) -> Result<usize, String> {
+    let json = std::fs::read_to_string(src_path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    let settings: HashMap<String, String> = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid settings file: {}", e))?;
+
+    let mut imported = 0;
+    for (key, value) in &settings {
+        if !is_portable_setting(key) {
+            continue;
+        }
+        db.set_setting(key, value).map_err(|e| e.to_string())?;
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Store a secret (API key, client secret) encrypted at rest
+#[tauri::command]
+pub fn set_secret(name: String, value: String, state: State<AppState>) -> Result<(), String> {
+    state.secrets.set_secret(&state.db, &name, &value)
+}
+
+/// Read a decrypted secret
+#[tauri::command]
+pub fn get_secret(name: String, state: State<AppState>) -> Result<Option<String>, String> {
+    state.secrets.get_secret(&state.db, &name)
+}
+
+/// Get a structured setting, parsed from its stored JSON
+#[tauri::command]
+pub fn get_setting_json(key: String, state: State<AppState>) -> Result<Option<serde_json::Value>, String> {
+    state.db.get_setting_json(&key).map_err(|e| e.to_string())
+}
+
+/// Store a structured setting as JSON
+#[tauri::command]
+pub fn set_setting_json(key: String, value: serde_json::Value, state: State<AppState>) -> Result<(), String> {
+    state.db.set_setting_json(&key, &value).map_err(|e| e.to_string())
+}
+
+/// Setting holding the user's keyboard shortcuts as JSON
+const KEYBINDINGS_SETTING: &str = "keybindings";
+
+/// Keyboard shortcuts: the defaults, overridden by whatever the user has changed
+#[tauri::command]
+pub fn get_keybindings(state: State<AppState>) -> Result<Keybindings, String> {
+    load_keybindings(&state.db)
+}
+
+fn load_keybindings(db: &Database) -> Result<Keybindings, String> {
+    let mut bindings = default_keybindings();
+    if let Some(stored) = db.get_setting_json::<Keybindings>(KEYBINDINGS_SETTING).map_err(|e| e.to_string())? {
+        bindings.extend(stored);
+    }
+    Ok(bindings)
+}
+
+/// Replace the keyboard shortcuts. Actions left out keep their defaults. Fails if a key is
+/// empty or bound to more than one action once the defaults are filled in.
+#[tauri::command]
+pub fn set_keybindings(bindings: Keybindings, state: State<AppState>) -> Result<(), String> {
+    save_keybindings(&state.db, &bindings)
+}
+
+fn save_keybindings(db: &Database, bindings: &Keybindings) -> Result<(), String> {
+    // Check what `load_keybindings` will hand back, not just what was sent
+    let mut merged = default_keybindings();
+    merged.extend(bindings.clone());
+
+    let mut seen: HashMap<String, KeyAction> = HashMap::new();
+    for (action, key) in &merged {
+        if key.trim().is_empty() {
+            return Err(format!("No key given for {:?}", action));
+        }
+        // Letters are matched without regard to Shift
+        if let Some(other) = seen.insert(key.to_lowercase(), *action) {
+            return Err(format!("\"{}\" is bound to both {:?} and {:?}", key, other, action));
+        }
+    }
+
+    db.set_setting_json(KEYBINDINGS_SETTING, bindings).map_err(|e| e.to_string())
+}
+
+// ==================== SYNC COMMANDS ====================
+
+/// Settings naming the WebDAV remote; the password lives in the secret store
+const SYNC_URL_SETTING: &str = "sync_webdav_url";
+const SYNC_USERNAME_SETTING: &str = "sync_webdav_username";
+const SYNC_PASSWORD_SECRET: &str = "sync_webdav_password";
+
+/// Upload the database and any changed images to the configured WebDAV remote
+#[tauri::command]
+pub async fn sync_push(state: State<'_, AppState>) -> Result<SyncResult, String> {
+    let client = sync_client(&state.db, &state.secrets)?;
+
+    let backup = state.app_data_dir.join("sync-push.db");
+    state.db.backup_to(&backup).map_err(|e| format!("Failed to back up database: {}", e))?;
+
+    let result = sync::push(&client, &backup, &state.app_data_dir.join("images")).await;
+    let _ = std::fs::remove_file(&backup);
+    result
+}
+
+/// Download changed images and replace the local database with the one on the remote
+#[tauri::command]
+pub async fn sync_pull(state: State<'_, AppState>) -> Result<SyncResult, String> {
+    let client = sync_client(&state.db, &state.secrets)?;
+    let images_dir = state.app_data_dir.join("images");
+
+    let download = state.app_data_dir.join("sync-pull.db");
+    let result = match sync::pull(&client, &download, &images_dir).await {
+        Ok((result, manifest)) => restore_synced_database(&state.db, &download, &manifest, &images_dir).map(|_| result),
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_file(&download);
+    result
+}
+
+fn sync_client(db: &Database, secrets: &SecretStore) -> Result<WebDavClient, String> {
+    let url = db.get_setting(SYNC_URL_SETTING)
+        .map_err(|e| e.to_string())?
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| "No sync remote configured".to_string())?;
+    let username = db.get_setting(SYNC_USERNAME_SETTING)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    let password = secrets.get_secret(db, SYNC_PASSWORD_SECRET)?.unwrap_or_default();
+
+    WebDavClient::new(url.trim(), username, password)
+}
+
+/// Swap in a pulled database while keeping what belongs to this machine: encrypted secrets
+/// (the other machine's can't be decrypted with this key) and the sync remote itself
+fn restore_synced_database(db: &Database, src: &Path, manifest: &SyncManifest, images_dir: &Path) -> Result<(), String> {
+    let keep: Vec<(String, String)> = db.get_all_settings()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(key, _)| crate::secrets::is_secret_setting(key) || key == SYNC_URL_SETTING || key == SYNC_USERNAME_SETTING)
+        .collect();
+
+    db.restore_from(src).map_err(|e| format!("Failed to restore database: {}", e))?;
+
+    for (key, _) in db.get_all_settings().map_err(|e| e.to_string())? {
+        if crate::secrets::is_secret_setting(&key) {
+            db.delete_setting(&key).map_err(|e| e.to_string())?;
+        }
+    }
+    for (key, value) in &keep {
+        db.set_setting(key, value).map_err(|e| e.to_string())?;
+    }
+
+    let local_images_dir = images_dir.to_string_lossy();
+    if !manifest.images_dir.is_empty() && manifest.images_dir != local_images_dir {
+        db.rebase_image_paths(&manifest.images_dir, &local_images_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Zip the whole images folder (covers, screenshots, backgrounds) so it can be moved to
+/// another machine. Returns how many files were archived.
+#[tauri::command]
+pub fn backup_images(dest_zip: String, state: State<AppState>) -> Result<usize, String> {
+    sync::archive_images(&state.app_data_dir.join("images"), Path::new(&dest_zip))
+}
+
+/// Unzip an images backup into the images folder. Returns how many files were restored.
+#[tauri::command]
+pub fn restore_images(src_zip: String, state: State<AppState>) -> Result<usize, String> {
+    sync::extract_images(Path::new(&src_zip), &state.app_data_dir.join("images"))
+}
+
+/// Setting for how many startup backups to keep
+const BACKUP_RETENTION_SETTING: &str = "backup_retention";
+const DEFAULT_BACKUP_RETENTION: usize = 5;
+
+/// Back up the database into `backups_dir` and delete all but the newest `backup_retention` backups.
+/// Run at startup so a crash mid-write can always be rolled back.
+pub fn rotate_database_backups(db: &Database, backups_dir: &Path, now: chrono::DateTime<chrono::Local>) -> Result<PathBuf, String> {
+    let retention = db.get_setting(BACKUP_RETENTION_SETTING)
+        .map_err(|e| e.to_string())?
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_BACKUP_RETENTION)
+        .max(1);
+
+    std::fs::create_dir_all(backups_dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    let backup = backups_dir.join(format!("retrovoid-{}.db", now.format("%Y%m%d-%H%M%S")));
+    db.backup_to(&backup).map_err(|e| format!("Failed to back up database: {}", e))?;
+
+    // Timestamped names sort oldest first
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backups_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("retrovoid-") && n.ends_with(".db"))
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(retention);
+    for old in &backups[..excess] {
+        if let Err(e) = std::fs::remove_file(old) {
+            eprintln!("Failed to remove old backup {}: {}", old.display(), e);
+        }
+    }
+
+    Ok(backup)
+}
+
+// ==================== STORAGE ====================
+
+/// How much space the database, images, logs and backups take up
+#[tauri::command]
+pub fn get_storage_report(state: State<AppState>) -> Result<StorageReport, String> {
+    Ok(storage_report(&state.app_data_dir))
+}
+
+fn storage_report(app_data_dir: &Path) -> StorageReport {
+    let mut report = StorageReport {
+        database_bytes: std::fs::metadata(app_data_dir.join("retrovoid.db")).map(|m| m.len()).unwrap_or(0),
+        logs_bytes: dir_size(&app_data_dir.join("logs")),
+        backups_bytes: dir_size(&app_data_dir.join("backups")),
+        ..Default::default()
+    };
+
+    if let Ok(entries) = std::fs::read_dir(app_data_dir.join("images")) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let (name, bytes) = if path.is_dir() {
+                (entry.file_name().to_string_lossy().to_string(), dir_size(&path))
+            } else {
+                ("other".to_string(), entry.metadata().map(|m| m.len()).unwrap_or(0))
+            };
+            *report.image_folders.entry(name).or_insert(0) += bytes;
+        }
+    }
+
+    report.total_bytes = report.database_bytes
+        + report.image_folders.values().sum::<u64>()
+        + report.logs_bytes
+        + report.backups_bytes;
+    report
+}
+
+/// Total size of every file under `dir`; 0 if it doesn't exist
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+// ==================== RETROARCH COMMANDS ====================
+
+/// Information about a RetroArch core
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetroArchCore {
+    pub file_name: String,
+    pub display_name: String,
+    pub full_path: String,
+}
+
+/// Get the default RetroArch cores folder path
+#[tauri::command]
+pub fn get_default_retroarch_cores_path() -> Result<Option<String>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(home) = dirs::home_dir() {
+            let cores_path = home.join("Library/Application Support/RetroArch/cores");
+            if cores_path.exists() {
+                return Ok(Some(cores_path.to_string_lossy().to_string()));
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Common Windows paths for RetroArch
+        let possible_paths = [
+            dirs::config_dir().map(|p| p.join("RetroArch/cores")),
+            dirs::data_dir().map(|p| p.join("RetroArch/cores")),
+            Some(std::path::PathBuf::from("C:/RetroArch/cores")),
+        ];
+
+        for path_opt in possible_paths.iter() {
+            if let Some(path) = path_opt {
+                if path.exists() {
+                    return Ok(Some(path.to_string_lossy().to_string()));
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(home) = dirs::home_dir() {
+            let cores_path = home.join(".config/retroarch/cores");
+            if cores_path.exists() {
+                return Ok(Some(cores_path.to_string_lossy().to_string()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Scan a folder for RetroArch cores
+#[tauri::command]
+pub fn scan_retroarch_cores(cores_path: String) -> Result<Vec<RetroArchCore>, String> {
+    let path = Path::new(&cores_path);
+
+    if !path.exists() || !path.is_dir() {
+        return Err("Cores folder does not exist".to_string());
+    }
+
+    // Core file extension based on platform
+    #[cfg(target_os = "macos")]
+    let core_ext = "dylib";
+    #[cfg(target_os = "windows")]
+    let core_ext = "dll";
+    #[cfg(target_os = "linux")]
+    let core_ext = "so";
+
+    let mut cores = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_path = entry.path();
+
+            // Check if it's a core file (ends with _libretro.dylib/dll/so)
+            if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
+                if file_name.ends_with(&format!("_libretro.{}", core_ext)) {
+                    // Extract display name from file name
+                    // e.g., "snes9x_libretro.dylib" -> "snes9x"
+                    let display_name = file_name
+                        .trim_end_matches(&format!("_libretro.{}", core_ext))
+                        .replace('_', " ")
+                        .split_whitespace()
+                        .map(|word| {
+                            let mut chars = word.chars();
+                            match chars.next() {
+                                Some(first) => first.to_uppercase().chain(chars).collect(),
+                                None => String::new(),
+                            }
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" ");
+
+                    cores.push(RetroArchCore {
+                        file_name: file_name.to_string(),
+                        display_name,
+                        full_path: file_path.to_string_lossy().to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Sort by display name
+    cores.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    Ok(cores)
+}
+
+// ==================== METADATA SCRAPING COMMANDS ====================
+
+use crate::scraper::{IgdbClient, IgdbSearchResult, OfflineMetadata, ScrapeResult, BatchScrapeResult};
+
+/// Validate IGDB credentials.
+/// Falls back to the stored secret when `client_secret` isn't given
+#[tauri::command]
+pub async fn validate_igdb_credentials(
+    client_id: String,
+    client_secret: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let client_secret = match client_secret {
+        Some(secret) => secret,
+        None => state.secrets.get_secret(&state.db, "igdb_client_secret")?
+            .ok_or_else(|| "IGDB Client Secret not configured".to_string())?,
+    };
+    let client = IgdbClient::new(client_id, client_secret);
+    client.validate_credentials().await
+}
+
+/// JSON object of our platform ID -> IGDB platform ID, for platforms the built-in table lacks
+const IGDB_PLATFORM_OVERRIDES_SETTING: &str = "igdb_platform_overrides";
+
+/// Build an IGDB client from the stored client ID and encrypted client secret
+fn igdb_client(state: &AppState) -> Result<IgdbClient, String> {
+    configured_igdb_client(state)?
+        .ok_or_else(|| "IGDB Client ID and Client Secret not configured".to_string())
+}
+
+/// Like `igdb_client`, but `None` when either credential is missing, so callers
+/// can tell an unconfigured IGDB apart from a broken secret store or bad settings
+fn configured_igdb_client(state: &AppState) -> Result<Option<IgdbClient>, String> {
+    let Some(client_id) = state.db.get_setting("igdb_client_id").map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let Some(client_secret) = state.secrets.get_secret(&state.db, "igdb_client_secret")? else {
+        return Ok(None);
+    };
+    let platform_overrides = state.db.get_setting_json(IGDB_PLATFORM_OVERRIDES_SETTING)
+        .map_err(|e| format!("Invalid IGDB platform overrides: {}", e))?
+        .unwrap_or_default();
+
+    Ok(Some(IgdbClient::new(client_id, client_secret).with_platform_overrides(platform_overrides)))
+}
+
+/// Search IGDB for games matching a query
+#[tauri::command]
+pub async fn search_igdb(
+    query: String,
+    platform_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<IgdbSearchResult>, String> {
+    let client = igdb_client(&state)?;
+    client.search_games(&query, platform_id.as_deref()).await
+}
+
+type ProviderSearch<'a> = futures_util::future::BoxFuture<'a, Result<Vec<ProviderSearchResult>, String>>;
+
+/// Search every provider that supports searching at once and merge matches for the same
+/// game, so the best one can be picked whatever its source. For now that is IGDB alone:
+/// offline metadata comes from filenames, so it has nothing to search.
+#[tauri::command]
+pub async fn search_all_providers(
+    query: String,
+    platform_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProviderSearchResult>, String> {
+    let client = igdb_client(&state)?;
+    let searches: Vec<(MetadataProvider, ProviderSearch)> = vec![
+        (MetadataProvider::Igdb, Box::pin(async move {
+            let results = client.search_games(&query, platform_id.as_deref()).await?;
+            Ok(results.into_iter()
+                .map(|result| ProviderSearchResult {
+                    name: result.name,
+                    release_date: result.release_date,
+                    cover_url: result.cover_url,
+                    platforms: result.platforms,
+                    summary: result.summary,
+                    igdb_id: Some(result.igdb_id),
+                    sources: vec![MetadataProvider::Igdb],
+                })
+                .collect())
+        })),
+    ];
+
+    search_providers(searches).await
+}
+
+/// Run the searches concurrently and merge their results in provider order. A provider
+/// that fails is skipped; the search only fails if all of them do.
+async fn search_providers(searches: Vec<(MetadataProvider, ProviderSearch<'_>)>) -> Result<Vec<ProviderSearchResult>, String> {
+    let (providers, futures): (Vec<_>, Vec<_>) = searches.into_iter().unzip();
+    let outcomes = futures_util::future::join_all(futures).await;
+
+    let mut merged: Vec<ProviderSearchResult> = Vec::new();
+    let mut errors = Vec::new();
+    for (provider, outcome) in providers.into_iter().zip(outcomes) {
+        let results = match outcome {
+            Ok(results) => results,
+            Err(e) => {
+                errors.push(format!("{:?}: {}", provider, e));
+                continue;
+            }
+        };
+
+        for result in results {
+            match merged.iter_mut().find(|existing| same_search_match(existing, &result)) {
+                Some(existing) => {
+                    existing.release_date = existing.release_date.take().or(result.release_date);
+                    existing.cover_url = existing.cover_url.take().or(result.cover_url);
+                    existing.summary = existing.summary.take().or(result.summary);
+                    existing.igdb_id = existing.igdb_id.or(result.igdb_id);
+                    for platform in result.platforms {
+                        if !existing.platforms.contains(&platform) {
+                            existing.platforms.push(platform);
+                        }
+                    }
+                    if !existing.sources.contains(&provider) {
+                        existing.sources.push(provider);
+                    }
+                }
+                None => merged.push(ProviderSearchResult { sources: vec![provider], ..result }),
+            }
+        }
+    }
+
+    if merged.is_empty() && !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(merged)
+}
+
+/// Titles this close are the same game when their numbers and years agree
+const SEARCH_MERGE_SIMILARITY: f64 = 0.85;
+
+/// Whether two search results are the same game. Sequels differ by little more than a
+/// number, so any numbers in the titles and the release years must match too.
+fn same_search_match(a: &ProviderSearchResult, b: &ProviderSearchResult) -> bool {
+    let numbers = |title: &str| -> Vec<String> {
+        title.split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let year = |date: &Option<String>| date.as_deref().and_then(|date| date.get(..4)).map(String::from);
+
+    if numbers(&a.name) != numbers(&b.name) {
+        return false;
+    }
+    if let (Some(a_year), Some(b_year)) = (year(&a.release_date), year(&b.release_date)) {
+        if a_year != b_year {
+            return false;
+        }
+    }
+    letter_pair_similarity(&a.name, &b.name) >= SEARCH_MERGE_SIMILARITY
+}
+
+/// Scrape metadata for a single game. Fields the user edited by hand are left alone unless
+/// `overwrite` is set. Without an `igdb_id`, providers are tried in `provider_chain` order
+/// until one finds the game.
+#[tauri::command]
+pub async fn scrape_game_metadata(
+    game_id: String,
+    igdb_id: Option<u64>,
+    overwrite: Option<bool>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ScrapeResult, String> {
+    // Get the game
+    let game = state.db.get_game(&game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+
+    if igdb_id.is_some() {
+        return scrape_igdb(&game, igdb_id, overwrite, &app_handle, &state).await;
+    }
+
+    let chain = provider_chain(&state.db, &game.platform_id);
+    let (game, app_handle, state) = (&game, &app_handle, &*state);
+    scrape_with_chain(&chain, |provider| async move {
+        match provider {
+            MetadataProvider::Igdb => scrape_igdb(game, None, overwrite, app_handle, state).await,
+            MetadataProvider::Offline => scrape_offline(&state.db, game, overwrite),
+        }
+    }).await
+}
+
+/// JSON array of providers to fall back through, in order, when the preferred one has no match
+const PROVIDER_CHAIN_SETTING: &str = "provider_chain";
+
+/// Providers to try for games on `platform_id`: the preferred one, then the rest of
+/// `provider_chain`
+fn provider_chain(db: &Database, platform_id: &str) -> Vec<MetadataProvider> {
+    let mut chain = vec![preferred_provider(db, platform_id)];
+    let fallbacks: Vec<MetadataProvider> = db.get_setting_json(PROVIDER_CHAIN_SETTING)
+        .unwrap_or_else(|e| {
+            eprintln!("Invalid provider chain: {}", e);
+            None
+        })
+        .unwrap_or_default();
+    for provider in fallbacks {
+        if !chain.contains(&provider) {
+            chain.push(provider);
+        }
+    }
+    chain
+}
+
+/// Try each provider until one finds the game; the provider records itself as the
+/// game's `metadata_source`. A provider that errors (e.g. IGDB being down) counts as a
+/// miss. Returns the last miss, or the last error if every provider failed.
+async fn scrape_with_chain<F, Fut>(chain: &[MetadataProvider], scrape: F) -> Result<ScrapeResult, String>
+where
+    F: Fn(MetadataProvider) -> Fut,
+    Fut: std::future::Future<Output = Result<ScrapeResult, String>>,
+{
+    let mut last_miss = None;
+    let mut last_error = None;
+    for provider in chain {
+        match scrape(*provider).await {
+            Ok(result) if result.success => return Ok(result),
+            Ok(result) => last_miss = Some(result),
+            Err(e) => {
+                eprintln!("{:?} scrape failed: {}", provider, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match (last_miss, last_error) {
+        (Some(miss), _) => Ok(miss),
+        (None, Some(e)) => Err(e),
+        (None, None) => Err("No metadata provider configured".to_string()),
+    }
+}
+
+/// Scrape a game from IGDB, matching it by title unless `igdb_id` picks the entry
+async fn scrape_igdb(
+    game: &Game,
+    igdb_id: Option<u64>,
+    overwrite: Option<bool>,
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+) -> Result<ScrapeResult, String> {
+    let game_id = game.id.clone();
+
+    let client = match configured_igdb_client(state)? {
+        Some(client) => client,
+        // Without credentials, fall back to what the filename tells us
+        None if igdb_id.is_none() => return scrape_offline(&state.db, game, overwrite),
+        None => return Err("IGDB Client ID and Client Secret not configured".to_string()),
+    };
+
+    // If no IGDB ID provided, search for the game
+    let target_igdb_id = if let Some(id) = igdb_id {
+        id
+    } else {
+        // Search for the game by title and platform
+        let results = client.search_games(&game.title, Some(&game.platform_id)).await?;
+
+        if results.is_empty() {
+            // Try without platform filter
+            let results = client.search_games(&game.title, None).await?;
+            if results.is_empty() {
+                return Ok(ScrapeResult {
+                    success: false,
+                    game_id: game_id.clone(),
+                    fields_updated: vec![],
+                    error: Some("No matching games found on IGDB".to_string()),
+                    source: "igdb".to_string(),
+                    offline_metadata: None,
+                });
+            }
+            results[0].igdb_id
+        } else {
+            results[0].igdb_id
+        }
+    };
+
+    // Get full metadata
+    let metadata = client.get_game_metadata(target_igdb_id).await?;
+
+    // Get app data directory for images
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let images_dir = app_data_dir.join("images");
+
+    let mut fields_updated = Vec::new();
+    let as_webp = setting_enabled(&state.db, "convert_artwork_to_webp");
+    let protected = protected_fields(game, overwrite);
+    let is_protected = |field: &str| protected.iter().any(|f| f == field);
+
+    println!("Metadata cover_url: {:?}", metadata.cover_url);
+
+    // Download cover art with high-res fallback, unless the user picked their own
+    let cover_url = metadata.cover_url.as_ref().filter(|_| !is_protected("cover_art_path"));
+    let cover_download = async {
+        let url = cover_url?;
+        let cover_dir = images_dir.join("covers");
+        let cover_path = cover_dir.join(format!("{}.jpg", game_id));
+
+        println!("Downloading cover to: {:?}", cover_path);
+
+        // Extract image_id from URL (format: .../t_cover_big/{image_id}.jpg)
+        let image_id = url
+            .rsplit('/')
+            .next()
+            .and_then(|s| s.strip_suffix(".jpg"))
+            .unwrap_or("");
+
+        if image_id.is_empty() {
+            eprintln!("Could not extract image_id from cover URL");
+            return None;
+        }
+
+        // Use fallback method: tries t_cover_big_2x (528x748) first, then t_cover_big (264x374)
+        if let Err(e) = client.download_cover_with_fallback(image_id, &cover_path).await {
+            eprintln!("Failed to download cover: {}", e);
+            return None;
+        }
+        println!("Cover downloaded successfully (with high-res fallback)");
+
+        let mut saved_path = cover_path.clone();
+        if as_webp {
+            match crate::webp::convert_to_webp(&cover_path) {
+                Ok(Some((webp_path, _))) => saved_path = webp_path,
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to convert cover to WebP: {}", e),
+            }
+        }
+        Some(saved_path.to_string_lossy().to_string())
+    };
+    if cover_url.is_none() {
+        println!("No cover URL in metadata");
+    }
+
+    // Download screenshots alongside the cover
+    let screenshots_dir = images_dir.join("screenshots");
+    let screenshot_urls = if is_protected("screenshots") { &[][..] } else { &metadata.screenshot_urls[..] };
+    let screenshot_downloads = screenshot_urls.iter()
+        .enumerate()
+        .map(|(i, url)| (url.clone(), screenshots_dir.join(format!("{}_{}.jpg", game_id, i))))
+        .collect();
+
+    let (cover_path, screenshot_results) = futures_util::future::join(
+        cover_download,
+        client.download_images(screenshot_downloads, as_webp),
+    ).await;
+
+    if cover_path.is_some() {
+        fields_updated.push("cover_art_path".to_string());
+    }
+
+    let mut screenshot_paths = Vec::new();
+    for (i, result) in screenshot_results.into_iter().enumerate() {
+        match result {
+            Ok(saved_path) => screenshot_paths.push(saved_path.to_string_lossy().to_string()),
+            Err(e) => eprintln!("Failed to download screenshot {}: {}", i, e),
+        }
+    }
+
+    if !screenshot_paths.is_empty() {
+        fields_updated.push("screenshots".to_string());
+    }
+
+    // Build update input
+    let mut updates = crate::models::UpdateGameInput::default();
+
+    if let Some(cover) = cover_path {
+        updates.cover_art_path = Some(cover);
+    }
+
+    if !screenshot_paths.is_empty() {
+        updates.screenshots = Some(screenshot_paths);
+    }
+
+    if metadata.summary.is_some() {
+        updates.description = metadata.summary;
+        fields_updated.push("description".to_string());
+    }
+
+    if metadata.release_date.is_some() {
+        updates.release_date = metadata.release_date;
+        fields_updated.push("release_date".to_string());
+    }
+
+    if !metadata.genres.is_empty() {
+        updates.genre = Some(metadata.genres);
+        fields_updated.push("genre".to_string());
+    }
+
+    if metadata.developer.is_some() {
+        updates.developer = metadata.developer;
+        fields_updated.push("developer".to_string());
+    }
+
+    if metadata.publisher.is_some() {
+        updates.publisher = metadata.publisher;
+        fields_updated.push("publisher".to_string());
+    }
+
+    // Update the game in the database
+    let updates = updates.without_fields(&protected);
+    fields_updated.retain(|field| !is_protected(field));
+    state.db.update_game(&game_id, &updates)
+        .map_err(|e| e.to_string())?;
+    state.db.set_scrape_info(&game_id, "igdb").map_err(|e| e.to_string())?;
+    state.db.set_igdb_id(&game_id, metadata.igdb_id).map_err(|e| e.to_string())?;
+
+    Ok(ScrapeResult {
+        success: true,
+        game_id,
+        fields_updated,
+        error: None,
+        source: "igdb".to_string(),
+        offline_metadata: None,
+    })
+}
+
+/// Re-download covers for games scraped from IGDB whose cover file is gone, or that have no
+/// cover at all, using the stored IGDB ID instead of a full re-scrape
+#[tauri::command]
+pub async fn repair_missing_covers(state: State<'_, AppState>) -> Result<CoverRepairResult, String> {
+    let client = igdb_client(&state)?;
+    restore_missing_covers(&state.db, &state.app_data_dir, |igdb_id| {
+        let client = &client;
+        async move {
+            // Same pacing as batch scrapes
+            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+            client.get_game_metadata(igdb_id).await.map(|metadata| metadata.cover_url)
+        }
+    }).await
+}
+
+/// `cover_url` looks up the cover URL for an IGDB ID
+async fn restore_missing_covers<F, Fut>(db: &Database, app_data_dir: &Path, cover_url: F) -> Result<CoverRepairResult, String>
+where
+    F: Fn(u64) -> Fut,
+    Fut: std::future::Future<Output = Result<Option<String>, String>>,
+{
+    let mut result = CoverRepairResult { covers_restored: 0, errors: Vec::new() };
+
+    for game in db.get_all_games().map_err(|e| e.to_string())? {
+        let Some(igdb_id) = game.igdb_id else {
+            continue;
+        };
+        let missing = match &game.cover_art_path {
+            Some(path) => !Path::new(path).exists(),
+            // A cover the user cleared on purpose stays cleared
+            None => !game.manually_edited_fields.iter().any(|field| field == "cover_art_path"),
+        };
+        if !missing {
+            continue;
+        }
+
+        let url = match cover_url(igdb_id).await {
+            Ok(Some(url)) => url,
+            Ok(None) => {
+                result.errors.push(format!("{}: IGDB has no cover for this game", game.title));
+                continue;
+            }
+            Err(e) => {
+                result.errors.push(format!("{}: {}", game.title, e));
+                continue;
+            }
+        };
+        match cover_art_from_url(db, app_data_dir, &game.id, &url).await {
+            Ok(_) => result.covers_restored += 1,
+            Err(e) => result.errors.push(format!("{}: {}", game.title, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+/// JSON object of platform ID -> provider ("igdb" or "offline") used to scrape its games
+const SCRAPER_PREFERENCE_SETTING: &str = "scraper_preference";
+/// Provider for platforms `scraper_preference` doesn't mention
+const DEFAULT_SCRAPER_SETTING: &str = "default_scraper";
+
+/// Provider to scrape games on `platform_id` with: the platform's preference, else the
+/// default, else IGDB. Unknown provider names are skipped over.
+fn preferred_provider(db: &Database, platform_id: &str) -> MetadataProvider {
+    let parse = |name: String| -> Option<MetadataProvider> {
+        match serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase())) {
+            Ok(provider) => Some(provider),
+            Err(_) => {
+                eprintln!("Unknown metadata provider '{}'", name);
+                None
+            }
+        }
+    };
+
+    let preferences: HashMap<String, String> = db.get_setting_json(SCRAPER_PREFERENCE_SETTING)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    preferences.get(platform_id).cloned().and_then(parse)
+        .or_else(|| db.get_setting(DEFAULT_SCRAPER_SETTING).ok().flatten().and_then(parse))
+        .unwrap_or_default()
+}
+
+/// Fields a scrape must not touch: the user's edits, unless overwriting was asked for
+fn protected_fields(game: &Game, overwrite: Option<bool>) -> Vec<String> {
+    if overwrite.unwrap_or(false) {
+        Vec::new()
+    } else {
+        game.manually_edited_fields.clone()
+    }
+}
+
+/// Baseline metadata from the ROM's filename and platform, used when no provider is
+/// configured. Only fills the release date when the game has none.
+fn scrape_offline(db: &Database, game: &Game, overwrite: Option<bool>) -> Result<ScrapeResult, String> {
+    let file_name = Path::new(&game.rom_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&game.title);
+    let (region, year) = crate::scraper::parse_filename_tags(file_name);
+    let platform_name = db.get_platform(&game.platform_id)
+        .map_err(|e| e.to_string())?
+        .map(|platform| platform.display_name);
+
+    let mut fields_updated = Vec::new();
+    let protected = protected_fields(game, overwrite).contains(&"release_date".to_string());
+    if let (Some(year), None, false) = (year, &game.release_date, protected) {
+        let updates = UpdateGameInput { release_date: Some(year.to_string()), ..Default::default() };
+        db.update_game(&game.id, &updates).map_err(|e| e.to_string())?;
+        fields_updated.push("release_date".to_string());
+    }
+    db.set_scrape_info(&game.id, "offline").map_err(|e| e.to_string())?;
+
+    Ok(ScrapeResult {
+        success: true,
+        game_id: game.id.clone(),
+        fields_updated,
+        error: None,
+        source: "offline".to_string(),
+        offline_metadata: Some(OfflineMetadata {
+            title: clean_rom_title(file_name),
+            region,
+            year,
+            platform_name,
+        }),
+    })
+}
+
+/// Games a library scrape has finished, so an interrupted one can pick up where it left off
+const SCRAPE_CHECKPOINT_SETTING: &str = "scrape_checkpoint";
+
+/// Batch scrape metadata for all games (or only those missing metadata).
+/// With `resume`, games finished by an interrupted run are skipped.
+#[tauri::command]
+pub async fn scrape_library_metadata(
+    only_missing: bool,
+    resume: Option<bool>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<BatchScrapeResult, String> {
+    // Get all games
+    let games = state.db.get_all_games().map_err(|e| e.to_string())?;
+
+    // Skip games that already have metadata if only_missing is true
+    // Consider a game as "having metadata" if it has cover art OR any text metadata
+    let games = games.into_iter().filter(|game| {
+        let has_metadata = game.cover_art_path.is_some()
+            || game.description.is_some()
+            || game.developer.is_some()
+            || game.publisher.is_some()
+            || game.release_date.is_some()
+            || !game.genre.is_empty();
+
+        !(only_missing && has_metadata)
+    });
+
+    let completed = if resume.unwrap_or(false) {
+        state.db.get_setting_json(SCRAPE_CHECKPOINT_SETTING)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default()
+    } else {
+        std::collections::HashSet::new()
+    };
+
+    scrape_games(games.collect(), Some(completed), app_handle, state).await
+}
+
+/// Re-scrape games never scraped, or last scraped more than `older_than_days` ago
+#[tauri::command]
+pub async fn rescrape_stale(
+    older_than_days: u32,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<BatchScrapeResult, String> {
+    let games = state.db.get_all_games().map_err(|e| e.to_string())?;
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(older_than_days as i64);
+
+    scrape_games(stale_games(games, cutoff), None, app_handle, state).await
+}
+
+/// Games whose last scrape is missing, unreadable or before `cutoff`
+fn stale_games(games: Vec<Game>, cutoff: chrono::DateTime<chrono::Utc>) -> Vec<Game> {
+    games.into_iter()
+        .filter(|game| {
+            game.last_scraped_at.as_deref()
+                .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                .is_none_or(|at| at < cutoff)
+        })
+        .collect()
+}
+
+/// Scrape each game in turn, collecting successes and failures.
+/// `checkpoint` holds the IDs of games already done; see `scrape_batch`.
+async fn scrape_games(
+    games: Vec<Game>,
+    checkpoint: Option<std::collections::HashSet<String>>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<BatchScrapeResult, String> {
+    scrape_batch(&state.db, games, checkpoint, |game_id| {
+        let (app_handle, state) = (app_handle.clone(), state.clone());
+        async move {
+            // Rate limiting - IGDB allows 4 requests/second, be conservative
+            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+            scrape_game_metadata(game_id, None, None, app_handle, state).await
+        }
+    }).await
+}
+
+/// Games finished between writes of the scrape checkpoint
+const SCRAPE_CHECKPOINT_INTERVAL: usize = 10;
+
+/// Games `scrape_batch` has finished. They are written to the `scrape_checkpoint` setting
+/// every `SCRAPE_CHECKPOINT_INTERVAL` games and once more when dropped, so a batch that is
+/// cancelled part way still records everything it finished.
+struct ScrapeCheckpoint<'a> {
+    db: &'a Database,
+    completed: std::collections::HashSet<String>,
+    unsaved: usize,
+}
+
+impl ScrapeCheckpoint<'_> {
+    fn finish(&mut self, game_id: String) -> Result<(), String> {
+        if self.completed.insert(game_id) {
+            self.unsaved += 1;
+        }
+        if self.unsaved >= SCRAPE_CHECKPOINT_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        self.db.set_setting_json(SCRAPE_CHECKPOINT_SETTING, &self.completed).map_err(|e| e.to_string())?;
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    /// The batch is through, so there is nothing left to resume
+    fn clear(mut self) -> Result<(), String> {
+        self.unsaved = 0;
+        self.db.delete_setting(SCRAPE_CHECKPOINT_SETTING).map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for ScrapeCheckpoint<'_> {
+    fn drop(&mut self) {
+        if self.unsaved > 0 {
+            if let Err(e) = self.save() {
+                eprintln!("Failed to save scrape checkpoint: {}", e);
+            }
+        }
+    }
+}
+
+/// With a checkpoint, games in it are skipped and each game that gets an answer from the
+/// scraper is added to it as it finishes (see `ScrapeCheckpoint` for when that is saved).
+/// Errors (e.g. the network dropping) leave the game out so a resumed run retries it. The
+/// checkpoint is cleared once the whole batch is through.
+async fn scrape_batch<F, Fut>(
+    db: &Database,
+    games: Vec<Game>,
+    checkpoint: Option<std::collections::HashSet<String>>,
+    scrape: F,
+) -> Result<BatchScrapeResult, String>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<ScrapeResult, String>>,
+{
+    let mut total = 0u32;
+    let mut successful = 0u32;
+    let mut failed = 0u32;
+    let mut errors = Vec::new();
+    let mut checkpoint = checkpoint.map(|completed| ScrapeCheckpoint { db, completed, unsaved: 0 });
+
+    for game in games {
+        if checkpoint.as_ref().is_some_and(|c| c.completed.contains(&game.id)) {
+            continue;
+        }
+        total += 1;
+
+        match scrape(game.id.clone()).await {
+            Ok(result) => {
+                if result.success {
+                    successful += 1;
+                } else {
+                    failed += 1;
+                    if let Some(err) = result.error {
+                        errors.push(format!("{}: {}", game.title, err));
+                    }
+                }
+                if let Some(checkpoint) = checkpoint.as_mut() {
+                    checkpoint.finish(game.id)?;
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                errors.push(format!("{}: {}", game.title, e));
+            }
+        }
+    }
+
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.clear()?;
+    }
+
+    Ok(BatchScrapeResult {
+        total,
+        successful,
+        failed,
+        errors,
+    })
+}
+
+// ==================== RETROACHIEVEMENTS COMMANDS ====================
+
+use crate::retroachievements::{Achievement, AchievementSummary, RaClient};
+
+/// Build a RetroAchievements client from the stored username and API key
+fn ra_client(state: &AppState) -> Result<RaClient, String> {
+    let username = state.db.get_setting("ra_username")
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "RetroAchievements username not configured".to_string())?;
+    let api_key = state.secrets.get_secret(&state.db, "ra_api_key")?
+        .ok_or_else(|| "RetroAchievements API key not configured".to_string())?;
+
+    Ok(RaClient::new(username, api_key))
+}
+
+/// Verify RetroAchievements credentials and store them, keeping the API key in the secret store
+#[tauri::command]
+pub async fn ra_login(username: String, api_key: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let client = RaClient::new(username.clone(), api_key.clone());
+    if !client.validate_credentials().await? {
+        return Ok(false);
+    }
+
+    state.db.set_setting("ra_username", &username).map_err(|e| e.to_string())?;
+    state.secrets.set_secret(&state.db, "ra_api_key", &api_key)?;
+    Ok(true)
+}
+
+/// Find a game on RetroAchievements, by ROM hash where the platform supports it
+/// and otherwise by title within the platform's console
+async fn resolve_ra_game_id(client: &RaClient, game: &Game) -> Result<Option<u64>, String> {
+    // Hashing reads the whole ROM, so keep it off the async runtime
+    let (platform_id, rom_path) = (game.platform_id.clone(), PathBuf::from(&game.rom_path));
+    let hash = tokio::task::spawn_blocking(move || crate::retroachievements::hash_rom_file(&platform_id, &rom_path))
+        .await
+        .map_err(|e| e.to_string())??;
+    if let Some(hash) = hash {
+        if let Some(ra_game_id) = client.resolve_game_id(&hash).await? {
+            return Ok(Some(ra_game_id));
+        }
+    }
+
+    match crate::retroachievements::get_ra_console_id(&game.platform_id) {
+        Some(console_id) => client.find_game_by_title(console_id, &game.title).await,
+        None => Ok(None),
+    }
+}
+
+/// Get a game's achievement progress. Returns `None` when RetroAchievements
+/// doesn't recognize the game.
+#[tauri::command]
+pub async fn get_achievement_summary(
+    game_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<AchievementSummary>, String> {
+    let game = state.db.get_game(&game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+    let client = ra_client(&state)?;
+
+    match resolve_ra_game_id(&client, &game).await? {
+        Some(ra_game_id) => client.get_summary(ra_game_id).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get a game's full achievement list with earned flags. Empty when RetroAchievements
+/// doesn't recognize the game.
+#[tauri::command]
+pub async fn get_game_achievements(
+    game_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Achievement>, String> {
+    let game = state.db.get_game(&game_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Game not found".to_string())?;
+    let client = ra_client(&state)?;
+
+    match resolve_ra_game_id(&client, &game).await? {
+        Some(ra_game_id) => client.get_achievements(ra_game_id).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+// ==================== GAMEPAD COMMANDS ====================
+
+/// Setting for how far a stick must move before it navigates, from 0.05 to 0.95
+const GAMEPAD_DEADZONE_SETTING: &str = "gamepad_deadzone";
+
+/// Start emitting `gamepad-input` events for controller navigation. Returns false if the
+/// gamepad thread was already running.
+#[tauri::command]
+pub fn start_gamepad_input(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<bool, String> {
+    let deadzone = state.db.get_setting(GAMEPAD_DEADZONE_SETTING)
+        .map_err(|e| e.to_string())?
+        .and_then(|value| value.parse::<f32>().ok())
+        .unwrap_or(crate::input::DEFAULT_DEADZONE);
+
+    Ok(crate::input::start_polling(app_handle, deadzone))
+}
+
+// ==================== DEVICE COMMANDS ====================
+
+/// Setting overriding the detected layout: "auto", "desktop" or "controller"
+const UI_MODE_SETTING: &str = "ui_mode";
+
+/// Detect whether we're on a Steam Deck or another handheld, so the frontend can switch
+/// to its controller-first layout
+#[tauri::command]
+pub fn get_device_profile(state: State<AppState>) -> Result<DeviceProfile, String> {
+    let ui_mode = state.db.get_setting_json::<UiMode>(UI_MODE_SETTING).map_err(|e| e.to_string())?.unwrap_or_default();
+    Ok(crate::device::detect_profile(&crate::device::DeviceInfo::current(), ui_mode))
+}
+
+#[tauri::command]
+pub fn set_ui_mode(mode: UiMode, state: State<AppState>) -> Result<(), String> {
+    state.db.set_setting_json(UI_MODE_SETTING, &mode).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{block_on, serve_routes};
+
+    fn test_state(app_data_dir: &Path) -> AppState {
+        AppState {
+            db: Arc::new(Database::new(PathBuf::from(":memory:")).unwrap()),
+            active_sessions: Arc::new(Mutex::new(HashMap::new())),
+            secrets: Arc::new(SecretStore::open(app_data_dir).unwrap()),
+            presence: Arc::new(DiscordPresence::new()),
+            app_data_dir: app_data_dir.to_path_buf(),
+            launch_queue: Arc::default(),
+        }
+    }
+
+    /// Wait for a launched game's process to exit
+    fn wait_for_game(state: &AppState, game_id: &str) {
+        let session = state.active_sessions.lock().unwrap().remove(game_id);
+        if let Some(mut child) = session.and_then(|s| s.child) {
+            let _ = child.wait();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_second_launch_rejected_while_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Test Game".to_string(), "/roms/test.nes".to_string(), "nes".to_string());
+        state.db.add_game(&game).unwrap();
+
+        let mut emulator = Emulator::new("Sleeper".to_string(), "sleep".to_string());
+        emulator.launch_arguments = "5".to_string();
+
+        let first = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(first.success);
+
+        let second = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(!second.success);
+        assert!(second.error.unwrap().contains("already running"));
+
+        // Clean up the spawned process
+        let session = state.active_sessions.lock().unwrap().remove(&game.id);
+        if let Some(mut session) = session {
+            if let Some(child) = session.child.as_mut() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_log_captures_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Noisy Game".to_string(), "/roms/noisy.nes".to_string(), "nes".to_string());
+        state.db.add_game(&game).unwrap();
+
+        let mut emulator = Emulator::new("Shell".to_string(), "sh".to_string());
+        emulator.launch_arguments = "-c 'echo boot failed >&2'".to_string();
+        emulator.capture_logs = true;
+
+        let result = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(result.success);
+        wait_for_game(&state, &game.id);
+
+        let log = read_latest_launch_log(dir.path(), &game.id).unwrap();
+        assert_eq!(log.as_deref().map(str::trim), Some("boot failed"));
+    }
+
+    #[test]
+    fn test_launch_logs_in_the_same_second_are_kept_apart() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        create_launch_log(dir.path(), "game").unwrap().write_all(b"first").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        create_launch_log(dir.path(), "game").unwrap().write_all(b"second").unwrap();
+
+        assert_eq!(std::fs::read_dir(dir.path().join("logs")).unwrap().count(), 2);
+        assert_eq!(read_latest_launch_log(dir.path(), "game").unwrap().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_fullscreen_argument_appended_only_when_enabled() {
+        let mut emulator = Emulator::new("Emu".to_string(), "emu".to_string());
+        emulator.launch_arguments = "--batch {rom}".to_string();
+        emulator.fullscreen_argument = Some("--fullscreen".to_string());
+
+        let windowed = build_launch_args(&emulator, "/roms/game.sfc".to_string(), "Game", false).unwrap();
+        assert_eq!(windowed, vec!["--batch", "/roms/game.sfc"]);
+
+        let fullscreen = build_launch_args(&emulator, "/roms/game.sfc".to_string(), "Game", true).unwrap();
+        assert_eq!(fullscreen, vec!["--batch", "/roms/game.sfc", "--fullscreen"]);
+    }
+
+    #[test]
+    fn test_move_playlist_item() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let games: Vec<Game> = (1..=4)
+            .map(|n| Game::new(format!("Mega Man {}", n), format!("/roms/mm{}.nes", n), "nes".to_string()))
+            .collect();
+        for game in &games {
+            db.add_game(game).unwrap();
+        }
+        let playlist = Playlist::new("Marathon".to_string(), games.iter().map(|g| g.id.clone()).collect());
+        db.add_playlist(&playlist).unwrap();
+
+        let titles = |db: &Database| -> Vec<String> {
+            find_playlist_games(db, &playlist.id).unwrap().into_iter().map(|g| g.title).collect()
+        };
+
+        // Forward: the item lands at `to` and the ones it passes shift back
+        move_playlist_entry(&db, &playlist.id, 0, 2).unwrap();
+        assert_eq!(titles(&db), ["Mega Man 2", "Mega Man 3", "Mega Man 1", "Mega Man 4"]);
+
+        // Backward
+        let moved = move_playlist_entry(&db, &playlist.id, 3, 0).unwrap();
+        assert_eq!(moved.game_ids[0], games[3].id);
+        assert_eq!(titles(&db), ["Mega Man 4", "Mega Man 2", "Mega Man 3", "Mega Man 1"]);
+
+        assert!(move_playlist_entry(&db, &playlist.id, 4, 0).is_err());
+        assert_eq!(titles(&db), ["Mega Man 4", "Mega Man 2", "Mega Man 3", "Mega Man 1"]);
+
+        // Positions stay in step with the listed games after one is deleted
+        let dir = tempfile::tempdir().unwrap();
+        remove_game(&db, dir.path(), &games[1].id).unwrap();
+        let moved = move_playlist_entry(&db, &playlist.id, 2, 0).unwrap();
+        assert_eq!(moved.game_ids.len(), 3);
+        assert_eq!(titles(&db), ["Mega Man 1", "Mega Man 4", "Mega Man 3"]);
+
+        // Merged games are replaced by the kept one
+        merge_duplicate_games(&db, dir.path(), &games[3].id, &[games[2].id.clone()]).unwrap();
+        assert_eq!(titles(&db), ["Mega Man 1", "Mega Man 4", "Mega Man 4"]);
+
+        db.delete_games_batch(&[games[0].id.clone(), games[3].id.clone()]).unwrap();
+        assert!(db.get_playlist(&playlist.id).unwrap().unwrap().game_ids.is_empty());
+    }
+
+    #[test]
+    fn test_launch_random_game_matches_filter() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let mut favorite = Game::new("Super Metroid".to_string(), "/roms/sm.sfc".to_string(), "snes".to_string());
+        favorite.is_favorite = true;
+        db.add_game(&favorite).unwrap();
+        for title in ["F-Zero", "Pilotwings", "ActRaiser"] {
+            db.add_game(&Game::new(title.to_string(), format!("/roms/{}.sfc", title), "snes".to_string())).unwrap();
+        }
+        db.add_game(&Game::new("Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string())).unwrap();
+
+        let filter = SmartCollectionRule { is_favorite: Some(true), ..Default::default() };
+        for _ in 0..5 {
+            let picked = launch_random_with(&db, &filter, |game| Ok(LaunchResult {
+                success: true,
+                pid: Some(1),
+                error: None,
+                warnings: vec![game.title.clone()],
+            })).unwrap().unwrap();
+            assert_eq!(picked.game.id, favorite.id);
+            assert_eq!(picked.launch_result.warnings, vec!["Super Metroid".to_string()]);
+        }
+
+        let nes = SmartCollectionRule { platform_ids: Some(vec!["nes".to_string()]), ..Default::default() };
+        assert_eq!(pick_random_game(&db, &nes).unwrap().unwrap().title, "Zelda");
+
+        let nothing = SmartCollectionRule { platform_ids: Some(vec!["n64".to_string()]), ..Default::default() };
+        assert!(launch_random_with(&db, &nothing, |_| panic!("nothing should launch")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_storage_report_totals() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |relative: &str, len: usize| {
+            let path = root.join(relative);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, vec![0u8; len]).unwrap();
+        };
+        write("retrovoid.db", 4096);
+        write("images/covers/a.jpg", 1000);
+        write("images/covers/a_bg.jpg", 500);
+        write("images/screenshots/nested/shot.png", 300);
+        write("images/thumbnails/a.webp", 50);
+        write("images/stray.png", 7);
+        write("logs/a-20260101-120000.log", 20);
+        write("backups/retrovoid-20260101-120000.db", 4000);
+
+        let report = storage_report(root);
+        assert_eq!(report.database_bytes, 4096);
+        assert_eq!(report.image_folders["covers"], 1500);
+        assert_eq!(report.image_folders["screenshots"], 300);
+        assert_eq!(report.image_folders["thumbnails"], 50);
+        assert_eq!(report.image_folders["other"], 7);
+        assert_eq!(report.logs_bytes, 20);
+        assert_eq!(report.backups_bytes, 4000);
+        assert_eq!(report.total_bytes, 4096 + 1500 + 300 + 50 + 7 + 20 + 4000);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(storage_report(empty.path()).total_bytes, 0);
+    }
+
+    #[test]
+    fn test_game_of_the_day_is_stable_per_date() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        for n in 0..20 {
+            let mut game = Game::new(format!("Game {}", n), format!("/roms/{}.gb", n), "gb".to_string());
+            game.is_favorite = n == 7;
+            db.add_game(&game).unwrap();
+        }
+
+        let day = chrono::NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let first = pick_game_of_the_day(&db, day, false).unwrap().unwrap();
+        let second = pick_game_of_the_day(&db, day, false).unwrap().unwrap();
+        assert_eq!(first.id, second.id);
+
+        let picks: std::collections::HashSet<String> = (0..30)
+            .map(|offset| {
+                let date = day + chrono::Duration::days(offset);
+                pick_game_of_the_day(&db, date, false).unwrap().unwrap().id
+            })
+            .collect();
+        assert!(picks.len() > 1);
+
+        assert_eq!(pick_game_of_the_day(&db, day, true).unwrap().unwrap().title, "Game 7");
+    }
+
+    #[test]
+    fn test_launch_queue_is_fifo() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let games: Vec<Game> = ["Metroid", "Zelda", "Kid Icarus"].iter()
+            .map(|title| Game::new(title.to_string(), format!("/roms/{}.nes", title), "nes".to_string()))
+            .collect();
+        for game in &games {
+            state.db.add_game(game).unwrap();
+        }
+
+        assert_eq!(enqueue(&state, games[1].id.clone()).unwrap(), 1);
+        assert_eq!(enqueue(&state, games[0].id.clone()).unwrap(), 2);
+        assert_eq!(enqueue(&state, games[2].id.clone()).unwrap(), 3);
+        assert!(enqueue(&state, "missing".to_string()).is_err());
+
+        assert_eq!(dequeue(&state), Some(games[1].id.clone()));
+        assert_eq!(dequeue(&state), Some(games[0].id.clone()));
+        assert_eq!(dequeue(&state), Some(games[2].id.clone()));
+        assert_eq!(dequeue(&state), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_next_queued_game_launches_after_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        state.db.set_setting(AUTO_LAUNCH_QUEUE_SETTING, "true").unwrap();
+
+        let mut emulator = Emulator::new("True".to_string(), "true".to_string());
+        emulator.launch_arguments = String::new();
+        state.db.add_emulator(&emulator).unwrap();
+        let games: Vec<Game> = ["Metroid", "Zelda", "Kid Icarus"].iter()
+            .map(|title| {
+                let mut game = Game::new(title.to_string(), format!("/roms/{}.nes", title), "nes".to_string());
+                game.preferred_emulator_id = Some(emulator.id.clone());
+                state.db.add_game(&game).unwrap();
+                game
+            })
+            .collect();
+
+        // The first queued game is deleted before its turn comes and is skipped
+        enqueue(&state, games[1].id.clone()).unwrap();
+        enqueue(&state, games[2].id.clone()).unwrap();
+        state.db.delete_game(&games[1].id).unwrap();
+
+        assert!(launch_game_with_emulator_internal(&games[0], &emulator, &state).unwrap().success);
+        for _ in 0..50 {
+            if !state.db.get_play_sessions(&games[2].id).unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(state.db.get_play_sessions(&games[2].id).unwrap().len(), 1);
+        assert_eq!(dequeue(&state), None);
+    }
+
+    #[test]
+    fn test_resolution_args_appended_when_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let mut emulator = Emulator::new("Emu".to_string(), "emu".to_string());
+
+        state.db.set_setting(LAUNCH_WIDTH_SETTING, "1920").unwrap();
+        assert_eq!(launch_resolution(&state.db), None);
+        state.db.set_setting(LAUNCH_HEIGHT_SETTING, "1080").unwrap();
+        let resolution = launch_resolution(&state.db);
+        assert_eq!(resolution, Some((1920, 1080)));
+
+        // No template: the emulator's arguments are left alone
+        assert!(resolution_args(&emulator, resolution).unwrap().is_empty());
+
+        emulator.resolution_argument_template = Some("--width={w} --height={h}".to_string());
+        assert_eq!(resolution_args(&emulator, resolution).unwrap(), vec!["--width=1920", "--height=1080"]);
+        assert!(resolution_args(&emulator, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_display_overrides_for_preferred_display() {
+        let mut emulator = Emulator::new("Emu".to_string(), "emu".to_string());
+        assert_eq!(display_overrides(&emulator).unwrap(), (Vec::new(), Vec::new()));
+
+        emulator.preferred_display = Some(1);
+        let (args, env) = display_overrides(&emulator).unwrap();
+        assert!(args.is_empty());
+        assert_eq!(env, vec![("SDL_VIDEO_FULLSCREEN_DISPLAY", "1".to_string()), ("SDL_VIDEO_FULLSCREEN_HEAD", "1".to_string())]);
+
+        emulator.display_argument = Some("--monitor {display}".to_string());
+        let (args, _) = display_overrides(&emulator).unwrap();
+        assert_eq!(args, vec!["--monitor", "1"]);
+    }
+
+    #[test]
+    fn test_launch_fullscreen_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        assert!(!setting_enabled(&state.db, "launch_fullscreen"));
+
+        state.db.set_setting("launch_fullscreen", "true").unwrap();
+        assert!(setting_enabled(&state.db, "launch_fullscreen"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pre_launch_hook_runs_before_emulator() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let order_file = dir.path().join("order.txt");
+        let game = Game::new("Hooked".to_string(), "/roms/hooked.nes".to_string(), "nes".to_string());
+        state.db.add_game(&game).unwrap();
+
+        state.db.set_setting(
+            "pre_launch_command",
+            &format!("sh -c 'echo pre >> {}'", order_file.display()),
+        ).unwrap();
+
+        let mut emulator = Emulator::new("Shell".to_string(), "sh".to_string());
+        emulator.launch_arguments = format!("-c 'echo emulator >> {}'", order_file.display());
+
+        let result = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(result.success);
+        assert!(result.warnings.is_empty());
+        wait_for_game(&state, &game.id);
+
+        let order = std::fs::read_to_string(&order_file).unwrap();
+        assert_eq!(order.lines().collect::<Vec<_>>(), vec!["pre", "emulator"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_pre_launch_hook_does_not_block_launch() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Hooked".to_string(), "/roms/hooked.nes".to_string(), "nes".to_string());
+        state.db.add_game(&game).unwrap();
+        state.db.set_setting("pre_launch_command", "sh -c 'exit 3'").unwrap();
+
+        let mut emulator = Emulator::new("True".to_string(), "true".to_string());
+        emulator.launch_arguments = String::new();
+
+        let result = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(result.success);
+        assert_eq!(result.warnings.len(), 1);
+        wait_for_game(&state, &game.id);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_monitor_ends_session_and_runs_post_exit_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let marker = dir.path().join("post-exit");
+        let game = Game::new("Monitored".to_string(), "/roms/monitored.nes".to_string(), "nes".to_string());
+        state.db.add_game(&game).unwrap();
+        state.db.set_setting("post_exit_command", &format!("touch {}", marker.display())).unwrap();
+
+        let mut emulator = Emulator::new("True".to_string(), "true".to_string());
+        emulator.launch_arguments = String::new();
+
+        let result = launch_game_with_emulator_internal(&game, &emulator, &state).unwrap();
+        assert!(result.success);
+
+        // Give the monitor thread time to notice the exit
+        for _ in 0..50 {
+            if marker.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        assert!(marker.exists());
+        assert!(!state.active_sessions.lock().unwrap().contains_key(&game.id));
+        let sessions = state.db.get_play_sessions(&game.id).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].end_time.is_some());
+    }
+
+    #[test]
+    fn test_uri_launch_uses_opener() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Half-Life".to_string(), "70".to_string(), "windows".to_string());
+        state.db.add_game(&game).unwrap();
+
+        let mut emulator = Emulator::new("Steam".to_string(), String::new());
+        emulator.launch_type = LaunchType::Uri;
+        emulator.launch_arguments = "steam://rungameid/{rom}".to_string();
+
+        let mut opened = Vec::new();
+        let result = launch_uri(&game, &emulator, &game.rom_path, &state, |uri| {
+            opened.push(uri.to_string());
+            Ok(())
+        });
+
+        assert!(result.success);
+        assert_eq!(result.pid, None);
+        assert_eq!(opened, vec!["steam://rungameid/70"]);
+
+        // Nothing reports when a detached launch ends, so no session is left open
+        assert!(state.active_sessions.lock().unwrap().is_empty());
+        assert!(state.db.get_play_sessions(&game.id).unwrap().is_empty());
+        assert!(state.db.get_game(&game.id).unwrap().unwrap().last_played.is_some());
+    }
+
+    #[test]
+    fn test_uri_launch_encodes_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Tom & Jerry: Fists?".to_string(), "/roms/Tom & Jerry #1.gb".to_string(), "gb".to_string());
+        state.db.add_game(&game).unwrap();
+
+        let mut emulator = Emulator::new("Launcher".to_string(), String::new());
+        emulator.launch_type = LaunchType::Uri;
+        emulator.launch_arguments = " launcher://play?rom={rom}&title={title} ".to_string();
+
+        // A detached launch doesn't count as still running, so it can be launched again
+        for _ in 0..2 {
+            assert!(launch_game_with_emulator_internal(&game, &emulator, &state).unwrap().success);
+        }
+        let opened = OPENED_URIS.with(|opened| opened.take());
+        let expected = "launcher://play?rom=%2Froms%2FTom%20%26%20Jerry%20%231.gb&title=Tom%20%26%20Jerry%3A%20Fists%3F";
+        assert_eq!(opened, [expected, expected]);
+        assert!(state.active_sessions.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_now_playing_file_written_on_launch_and_removed_on_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        state.db.set_setting("now_playing_file", "true").unwrap();
+        let game = Game::new("Portal".to_string(), "/roms/portal.sfc".to_string(), "snes".to_string());
+        state.db.add_game(&game).unwrap();
+
+        let mut emulator = Emulator::new("Sleeper".to_string(), "sleep".to_string());
+        emulator.launch_arguments = "5".to_string();
+        assert!(launch_game_with_emulator_internal(&game, &emulator, &state).unwrap().success);
+
+        let path = dir.path().join(NOW_PLAYING_FILE);
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["title"], "Portal");
+        assert_eq!(written["platform"], "SNES");
+
+        let child = state.active_sessions.lock().unwrap().get_mut(&game.id).and_then(|s| s.child.take());
+        finish_game_session(&game.id, None, &state).unwrap();
+        assert!(!path.exists());
+        if let Some(mut child) = child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    #[test]
+    fn test_windows_rom_path_with_spaces_passed_exactly() {
+        let rom = r"C:\Games\My ROMs\Super Mario World (USA).sfc".to_string();
+
+        for template in ["{rom}", "\"{rom}\"", "--fullscreen {rom}"] {
+            let args = expand_launch_template(template, rom.clone(), "Super Mario World").unwrap();
+            assert_eq!(args.last().unwrap(), &rom, "template {}", template);
+        }
+
+        let args = expand_launch_template("--title {title} --rom={rom}", rom.clone(), "Mario's \"World\"").unwrap();
+        assert_eq!(args, vec![
+            "--title".to_string(),
+            "Mario's \"World\"".to_string(),
+            format!("--rom={}", rom),
+        ]);
+
+        // UNC paths keep their leading double backslash
+        let unc = r"\\nas\roms\Zelda.sfc".to_string();
+        assert_eq!(expand_launch_template("{rom}", unc.clone(), "Zelda").unwrap(), vec![unc]);
+    }
+
+    #[test]
+    fn test_placeholders_in_substituted_text_are_not_expanded() {
+        let args = expand_launch_template("{rom}", "/roms/{title}.nes".to_string(), "Game").unwrap();
+        assert_eq!(args, vec!["/roms/{title}.nes"]);
+    }
+
+    #[test]
+    fn test_wrapper_command_prepended_to_argv() {
+        let mut emulator = Emulator::new("Dolphin".to_string(), "/emus/Dolphin.exe".to_string());
+        emulator.launch_arguments = "-e {rom}".to_string();
+        let args = build_launch_args(&emulator, "/roms/game.iso".to_string(), "Game", false).unwrap();
+
+        let argv = build_command_line(&emulator, emulator.executable_path.clone(), args.clone()).unwrap();
+        assert_eq!(argv, vec!["/emus/Dolphin.exe", "-e", "/roms/game.iso"]);
+
+        emulator.wrapper_command = Some("'/opt/proton 9/proton' run".to_string());
+        let argv = build_command_line(&emulator, emulator.executable_path.clone(), args).unwrap();
+        assert_eq!(argv, vec!["/opt/proton 9/proton", "run", "/emus/Dolphin.exe", "-e", "/roms/game.iso"]);
+    }
+
+    #[test]
+    fn test_manual_play_session_adds_play_time() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Chrono Trigger".to_string(), "/roms/ct.sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        let session = record_manual_play_session(&db, &game.id, "2024-03-01T20:00:00Z", 5400, None).unwrap();
+        assert_eq!(session.end_time.as_deref(), Some("2024-03-01T21:30:00+00:00"));
+
+        let updated = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(updated.total_play_time_seconds, 5400);
+        assert_eq!(updated.last_played.as_deref(), Some("2024-03-01 21:30:00"));
+        assert_eq!(db.get_play_sessions(&game.id).unwrap().len(), 1);
+
+        // Non-positive durations and future sessions are rejected
+        assert!(record_manual_play_session(&db, &game.id, "2024-03-01T20:00:00Z", 0, None).is_err());
+        assert!(record_manual_play_session(&db, &game.id, "2999-01-01T00:00:00Z", 60, None).is_err());
+        assert!(record_manual_play_session(&db, &game.id, "yesterday", 60, None).is_err());
+    }
+
+    #[test]
+    fn test_import_play_sessions_updates_play_time() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let metroid = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        db.add_game(&metroid).unwrap();
+
+        let csv = "title,start_time,end_time,duration_seconds\r\n\
+                   metroid,2024-01-01T10:00:00+00:00,2024-01-01T11:00:00+00:00,3600\r\n\
+                   Metroid,2024-01-02T10:00:00+00:00,,600\r\n\
+                   Kid Icarus,2024-01-03T10:00:00+00:00,,60\r\n";
+        let result = import_play_sessions(&db, csv).unwrap();
+
+        assert_eq!(result.sessions_imported, 2);
+        assert_eq!(result.unmatched_rows, vec!["Line 4: no game matches Kid Icarus".to_string()]);
+
+        let game = db.get_game(&metroid.id).unwrap().unwrap();
+        assert_eq!(game.total_play_time_seconds, 4200);
+        assert_eq!(game.last_played.as_deref(), Some("2024-01-02 10:10:00"));
+        assert_eq!(db.get_play_sessions(&metroid.id).unwrap().len(), 2);
+
+        // Importing the same file again adds nothing, even with the offset written differently
+        let again = "title,start_time,end_time,duration_seconds\r\n\
+                     Metroid,2024-01-01T12:00:00+02:00,,3600\r\n\
+                     Metroid,2024-01-05T10:00:00+00:00,,60\r\n";
+        let result = import_play_sessions(&db, again).unwrap();
+        assert_eq!((result.sessions_imported, result.duplicates_skipped), (1, 1));
+        let game = db.get_game(&metroid.id).unwrap().unwrap();
+        assert_eq!(game.total_play_time_seconds, 4260);
+        assert_eq!(game.last_played.as_deref(), Some("2024-01-05 10:01:00"));
+    }
+
+    #[test]
+    fn test_clear_play_history_for_one_game() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let metroid = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        let zelda = Game::new("Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string());
+        db.add_game(&metroid).unwrap();
+        db.add_game(&zelda).unwrap();
+        record_manual_play_session(&db, &metroid.id, "2024-01-01T10:00:00Z", 3600, None).unwrap();
+        record_manual_play_session(&db, &metroid.id, "2024-01-02T10:00:00Z", 600, None).unwrap();
+        record_manual_play_session(&db, &zelda.id, "2024-01-03T10:00:00Z", 1200, None).unwrap();
+
+        assert_eq!(db.clear_play_history(Some(&metroid.id)).unwrap(), 2);
+
+        let cleared = db.get_game(&metroid.id).unwrap().unwrap();
+        assert_eq!(cleared.total_play_time_seconds, 0);
+        assert!(cleared.last_played.is_none());
+        assert!(db.get_play_sessions(&metroid.id).unwrap().is_empty());
+
+        let untouched = db.get_game(&zelda.id).unwrap().unwrap();
+        assert_eq!(untouched.total_play_time_seconds, 1200);
+        assert!(untouched.last_played.is_some());
+        assert_eq!(db.get_play_sessions(&zelda.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_clear_play_history_for_library() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let metroid = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        let zelda = Game::new("Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string());
+        db.add_game(&metroid).unwrap();
+        db.add_game(&zelda).unwrap();
+        record_manual_play_session(&db, &metroid.id, "2024-01-01T10:00:00Z", 3600, None).unwrap();
+        record_manual_play_session(&db, &zelda.id, "2024-01-03T10:00:00Z", 1200, None).unwrap();
+
+        assert_eq!(db.clear_play_history(None).unwrap(), 2);
+
+        for id in [&metroid.id, &zelda.id] {
+            let game = db.get_game(id).unwrap().unwrap();
+            assert_eq!(game.total_play_time_seconds, 0);
+            assert!(game.last_played.is_none());
+        }
+        assert!(db.get_played_game_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_play_session_recomputes_total() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        let kept = record_manual_play_session(&db, &game.id, "2024-01-01T10:00:00Z", 3600, None).unwrap();
+        let bogus = record_manual_play_session(&db, &game.id, "2024-01-02T10:00:00Z", 5, None).unwrap();
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().total_play_time_seconds, 3605);
+
+        remove_play_session(&db, &bogus.id).unwrap();
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().total_play_time_seconds, 3600);
+        assert!(db.get_play_session(&bogus.id).unwrap().is_none());
+        assert!(db.get_play_session(&kept.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_update_play_session_duration_recomputes_total() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        let session = record_manual_play_session(&db, &game.id, "2024-01-01T10:00:00Z", 7200, None).unwrap();
+        change_play_session_duration(&db, &session.id, 1800).unwrap();
+
+        assert_eq!(db.get_play_session(&session.id).unwrap().unwrap().duration_seconds, 1800);
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().total_play_time_seconds, 1800);
+        assert!(change_play_session_duration(&db, &session.id, -1).is_err());
+    }
+
+    #[test]
+    fn test_get_game_by_hash() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Tetris".to_string(), "/roms/tetris.gb".to_string(), "gb".to_string());
+        db.add_game(&game).unwrap();
+        db.set_game_hash(&game.id, HashAlgorithm::Md5, "982ED5D2B12A0377EB14BCDC4123744E").unwrap();
+
+        let found = db.get_game_by_hash(HashAlgorithm::Md5, "982ed5d2b12a0377eb14bcdc4123744e").unwrap();
+        assert_eq!(found.map(|g| g.id), Some(game.id));
+        assert!(db.get_game_by_hash(HashAlgorithm::Sha1, "982ed5d2b12a0377eb14bcdc4123744e").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_hash_game_rom_stores_md5() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let rom = dir.path().join("game.gb");
+        std::fs::write(&rom, b"hello").unwrap();
+
+        let game = Game::new("Game".to_string(), rom.to_string_lossy().to_string(), "gb".to_string());
+        db.add_game(&game).unwrap();
+
+        let hash = store_rom_md5(&db, &game.id, false).unwrap();
+        assert_eq!(hash, "5d41402abc4b2a76b9719d911017c592");
+        assert_eq!(db.get_game_by_hash(HashAlgorithm::Md5, &hash).unwrap().map(|g| g.id), Some(game.id));
+    }
+
+    #[test]
+    fn test_smc_header_skipped_for_hash_and_stripped() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let rom = dir.path().join("Zelda.smc");
+        let mut data = vec![0xFFu8; 512];
+        data.extend(vec![0x42u8; 8192]);
+        std::fs::write(&rom, &data).unwrap();
+
+        let game = Game::new("Zelda".to_string(), rom.to_string_lossy().to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        let headerless = format!("{:x}", md5::compute(&data[512..]));
+        assert_eq!(store_rom_md5(&db, &game.id, true).unwrap(), headerless);
+        assert_eq!(store_rom_md5(&db, &game.id, false).unwrap(), format!("{:x}", md5::compute(&data)));
+
+        let dest = write_headerless_smc(&rom).unwrap();
+        assert_eq!(dest, dir.path().join("Zelda.sfc"));
+        assert_eq!(std::fs::read(&dest).unwrap(), data[512..]);
+        assert!(write_headerless_smc(&dest).is_err());
+    }
+
+    #[test]
+    fn test_update_game_last_played_and_collections() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        db.update_game(&game.id, &UpdateGameInput {
+            last_played: Some("2023-05-01T18:30:00+00:00".to_string()),
+            collection_ids: Some(vec!["favorites-80s".to_string()]),
+            ..Default::default()
+        }).unwrap();
+
+        let stored = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(stored.last_played.as_deref(), Some("2023-05-01T18:30:00+00:00"));
+        assert_eq!(stored.collection_ids, vec!["favorites-80s"]);
+    }
+
+    /// Insert a finished session for a game that started at `start_time`
+    fn add_finished_session(db: &Database, game_id: &str, start_time: &str, duration_seconds: i64) {
+        let mut session = PlaySession::new(game_id.to_string());
+        session.start_time = start_time.to_string();
+        db.create_play_session(&session).unwrap();
+        db.update_play_session_duration(&session.id, duration_seconds).unwrap();
+    }
+
+    #[test]
+    fn test_reorder_m3u_rewrites_playlist() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Parasite Eve (Disc 1).cue"), b"disc 1").unwrap();
+        std::fs::write(dir.path().join("Parasite Eve (Disc 2).cue"), b"disc 2").unwrap();
+        let m3u = dir.path().join("Parasite Eve.m3u");
+        std::fs::write(&m3u, "Parasite Eve (Disc 2).cue\nParasite Eve (Disc 1).cue\n").unwrap();
+
+        let order = vec!["Parasite Eve (Disc 1).cue".to_string(), "Parasite Eve (Disc 2).cue".to_string()];
+        reorder_playlist(&m3u, &order).unwrap();
+        let entries: Vec<String> = std::fs::read_to_string(&m3u).unwrap().lines().map(String::from).collect();
+        assert_eq!(entries, order);
+
+        // Directives and comments keep their place around the moved entries
+        std::fs::write(&m3u, "#EXTM3U\r\n#EXTINF:0,Disc 2\r\nParasite Eve (Disc 2).cue\r\n\r\n# Disc 1\r\nParasite Eve (Disc 1).cue\r\n").unwrap();
+        reorder_playlist(&m3u, &order).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&m3u).unwrap(),
+            "#EXTM3U\r\n#EXTINF:0,Disc 2\r\nParasite Eve (Disc 1).cue\r\n\r\n# Disc 1\r\nParasite Eve (Disc 2).cue\r\n",
+        );
+
+        // Dropping or inventing a disc is refused
+        assert!(reorder_playlist(&m3u, &order[..1]).is_err());
+        assert!(reorder_playlist(&m3u, &[order[0].clone(), "Parasite Eve (Disc 3).cue".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_m3u_reports_missing_disc() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Xenogears (Disc 1).cue"), b"disc 1").unwrap();
+        let m3u = dir.path().join("Xenogears.m3u");
+        std::fs::write(&m3u, "#EXTM3U\nXenogears (Disc 1).cue\nXenogears (Disc 2).cue\n").unwrap();
+
+        let missing = find_missing_m3u_entries(&m3u).unwrap();
+        assert_eq!(missing, vec![dir.path().join("Xenogears (Disc 2).cue").to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn test_regenerate_m3u_picks_up_new_disc() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Metal Gear Solid (Disc 1).cue"), b"disc 1").unwrap();
+        std::fs::write(dir.path().join("Metal Gear Solid (Disc 2).cue"), b"disc 2").unwrap();
+        std::fs::write(dir.path().join("Metal Gear Solid (Disc 2).bin"), b"track").unwrap();
+        std::fs::write(dir.path().join("Silent Hill (Disc 1).cue"), b"other game").unwrap();
+        let m3u = dir.path().join("Metal Gear Solid.m3u");
+        std::fs::write(&m3u, "Metal Gear Solid (Disc 1).cue\n").unwrap();
+
+        let game = Game::new("Metal Gear Solid".to_string(), m3u.to_string_lossy().to_string(), "ps1".to_string());
+        db.add_game(&game).unwrap();
+
+        // A third disc added later, kept in its own folder
+        let disc3_dir = dir.path().join("Metal Gear Solid (Disc 3)");
+        std::fs::create_dir_all(&disc3_dir).unwrap();
+        std::fs::write(disc3_dir.join("mgs3.cue"), b"disc 3").unwrap();
+
+        let discs = regenerate_playlist(&db, &game.id).unwrap();
+        assert_eq!(discs.len(), 3);
+
+        let playlist = std::fs::read_to_string(&m3u).unwrap();
+        let expected_disc3 = Path::new("Metal Gear Solid (Disc 3)").join("mgs3.cue");
+        assert_eq!(
+            playlist.lines().collect::<Vec<_>>(),
+            vec!["Metal Gear Solid (Disc 1).cue", "Metal Gear Solid (Disc 2).cue", expected_disc3.to_str().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_split_m3u_game_into_discs() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Final Fantasy VII (Disc 1).cue"), b"disc 1").unwrap();
+        std::fs::write(dir.path().join("Final Fantasy VII (Disc 2).cue"), b"disc 2").unwrap();
+        let m3u = dir.path().join("Final Fantasy VII.m3u");
+        std::fs::write(&m3u, "Final Fantasy VII (Disc 1).cue\nFinal Fantasy VII (Disc 2).cue\n").unwrap();
+
+        let mut game = Game::new("Final Fantasy VII".to_string(), m3u.to_string_lossy().to_string(), "ps1".to_string());
+        game.developer = Some("Square".to_string());
+        db.add_game(&game).unwrap();
+        add_finished_session(&db, &game.id, "2024-01-01T10:00:00+00:00", 600);
+
+        let discs = split_playlist_game(&db, &game.id, true).unwrap();
+
+        let titles: Vec<&str> = discs.iter().map(|d| d.title.as_str()).collect();
+        assert_eq!(titles, vec!["Final Fantasy VII (Disc 1)", "Final Fantasy VII (Disc 2)"]);
+        assert!(discs[1].rom_path.ends_with("Final Fantasy VII (Disc 2).cue"));
+        assert_eq!(discs[1].developer.as_deref(), Some("Square"));
+        assert!(db.get_game(&game.id).unwrap().is_none());
+        assert!(!m3u.exists());
+
+        // Disc 1 keeps the playlist's play history
+        assert_eq!(db.get_play_sessions(&discs[0].id).unwrap().len(), 1);
+        assert!(db.get_play_sessions(&discs[1].id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_cover_art_from_url() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+        let game = Game::new("Kirby Super Star".to_string(), "/roms/kss.sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(8, 8, image::Rgb([255, 105, 180]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let base_url = serve_routes(vec![
+            ("/art?id=1", "image/png", png.clone()),
+            ("/page", "text/html", b"<html>not found</html>".to_vec()),
+        ]);
+
+        let cover = block_on(cover_art_from_url(&db, app_data.path(), &game.id, &format!("{}/art?id=1", base_url))).unwrap();
+        let expected = app_data.path().join("images").join("covers").join(format!("{}.png", game.id));
+        assert_eq!(cover, expected.to_string_lossy());
+        assert_eq!(std::fs::read(&expected).unwrap(), png);
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().cover_art_path, Some(cover));
+
+        // Only the stored cover is left behind
+        assert_eq!(std::fs::read_dir(expected.parent().unwrap()).unwrap().count(), 1);
+
+        assert!(block_on(cover_art_from_url(&db, app_data.path(), &game.id, &format!("{}/page", base_url))).is_err());
+    }
+
+    #[test]
+    fn test_repair_missing_covers_refetches_by_igdb_id() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+        let covers_dir = app_data.path().join("images").join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(8, 8, image::Rgb([40, 80, 160]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let base_url = serve_routes(vec![("/covers/1026.png", "image/png", png.clone())]);
+
+        let mut deleted = Game::new("Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string());
+        deleted.cover_art_path = Some(covers_dir.join("gone.jpg").to_string_lossy().to_string());
+        deleted.igdb_id = Some(1026);
+        db.add_game(&deleted).unwrap();
+
+        let intact_cover = covers_dir.join("metroid.png");
+        std::fs::write(&intact_cover, &png).unwrap();
+        let mut intact = Game::new("Metroid".to_string(), "/roms/metroid.nes".to_string(), "nes".to_string());
+        intact.cover_art_path = Some(intact_cover.to_string_lossy().to_string());
+        intact.igdb_id = Some(1027);
+        db.add_game(&intact).unwrap();
+
+        // Never scraped from IGDB, so there's nothing to re-fetch
+        db.add_game(&Game::new("Homebrew".to_string(), "/roms/homebrew.nes".to_string(), "nes".to_string())).unwrap();
+
+        let looked_up = Mutex::new(Vec::new());
+        let result = block_on(restore_missing_covers(&db, app_data.path(), |igdb_id| {
+            looked_up.lock().unwrap().push(igdb_id);
+            let url = format!("{}/covers/{}.png", base_url, igdb_id);
+            async move { Ok(Some(url)) }
+        })).unwrap();
+
+        assert_eq!(result.covers_restored, 1);
+        assert!(result.errors.is_empty());
+        assert_eq!(*looked_up.lock().unwrap(), vec![1026]);
+
+        let restored = db.get_game(&deleted.id).unwrap().unwrap().cover_art_path.unwrap();
+        assert_eq!(restored, covers_dir.join(format!("{}.png", deleted.id)).to_string_lossy());
+        assert_eq!(std::fs::read(&restored).unwrap(), png);
+        assert_eq!(db.get_game(&intact.id).unwrap().unwrap().cover_art_path, intact.cover_art_path);
+    }
+
+    #[test]
+    fn test_scrape_batch_resumes_from_checkpoint() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let games: Vec<Game> = (1..=14)
+            .map(|n| Game::new(format!("Game {}", n), format!("/roms/game{}.gba", n), "gba".to_string()))
+            .collect();
+        let scraped = |game_id: String| ScrapeResult {
+            success: true,
+            game_id,
+            fields_updated: Vec::new(),
+            error: None,
+            source: "igdb".to_string(),
+            offline_metadata: None,
+        };
+        let stored = |db: &Database| db.get_setting_json::<std::collections::HashSet<String>>(SCRAPE_CHECKPOINT_SETTING).unwrap();
+        let ids = |games: &[Game]| games.iter().map(|g| g.id.clone()).collect::<std::collections::HashSet<_>>();
+
+        // The app closes while the twelfth game is being scraped
+        let stall_on = games[11].id.clone();
+        let saved_before_stall = Mutex::new(None);
+        let interrupted = block_on(async {
+            tokio::time::timeout(Duration::from_millis(200), scrape_batch(&db, games.clone(), Some(Default::default()), |game_id| {
+                let stall = game_id == stall_on;
+                if stall {
+                    *saved_before_stall.lock().unwrap() = stored(&db);
+                }
+                async move {
+                    if stall {
+                        std::future::pending::<()>().await;
+                    }
+                    Ok(scraped(game_id))
+                }
+            })).await
+        });
+        assert!(interrupted.is_err());
+
+        // Written every few games rather than after each one, then flushed when cancelled
+        assert_eq!(saved_before_stall.into_inner().unwrap(), Some(ids(&games[..SCRAPE_CHECKPOINT_INTERVAL])));
+        let checkpoint = stored(&db).unwrap();
+        assert_eq!(checkpoint, ids(&games[..11]));
+
+        let calls = Mutex::new(Vec::new());
+        let result = block_on(scrape_batch(&db, games.clone(), Some(checkpoint), |game_id| {
+            calls.lock().unwrap().push(game_id.clone());
+            async move { Ok(scraped(game_id)) }
+        })).unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec![games[11].id.clone(), games[12].id.clone(), games[13].id.clone()]);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.successful, 3);
+        assert!(db.get_setting(SCRAPE_CHECKPOINT_SETTING).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clean_rom_title_title_case() {
+        let on = CleanTitleOptions { title_case: true, ..CleanTitleOptions::default() };
+
+        assert_eq!(clean_rom_title_options("SUPER MARIO WORLD (USA)", &on), "Super Mario World");
+        assert_eq!(clean_rom_title("SUPER MARIO WORLD (USA)"), "SUPER MARIO WORLD");
+        assert_eq!(
+            clean_rom_title_options("THE LEGEND OF ZELDA - A LINK TO THE PAST", &on),
+            "The Legend of Zelda - A Link to the Past"
+        );
+        assert_eq!(clean_rom_title_options("castlevania: the adventure", &on), "Castlevania: The Adventure");
+
+        // Acronyms, Roman numerals and numbered words stay upper-case
+        assert_eq!(clean_rom_title_options("NBA JAM", &on), "NBA Jam");
+        assert_eq!(clean_rom_title_options("FINAL FANTASY VII", &on), "Final Fantasy VII");
+        assert_eq!(clean_rom_title_options("super mario 3d land", &on), "Super Mario 3D Land");
+        assert_eq!(clean_rom_title_options("X-MEN VS STREET FIGHTER", &on), "X-Men vs Street Fighter");
+
+        // Deliberate mixed casing is kept
+        assert_eq!(clean_rom_title_options("McDonald's Treasure Land Adventure", &on), "McDonald's Treasure Land Adventure");
+    }
+
+    #[test]
+    fn test_clean_rom_title_tag_groups() {
+        let name = "Pokemon Red (USA) [!] {SGB}";
+        for parentheses in [true, false] {
+            for brackets in [true, false] {
+                for braces in [true, false] {
+                    let options = CleanTitleOptions {
+                        strip_parentheses: parentheses,
+                        strip_brackets: brackets,
+                        strip_braces: braces,
+                        ..CleanTitleOptions::default()
+                    };
+
+                    let mut expected = vec!["Pokemon Red"];
+                    if !parentheses { expected.push("(USA)"); }
+                    if !brackets { expected.push("[!]"); }
+                    if !braces { expected.push("{SGB}"); }
+                    assert_eq!(clean_rom_title_options(name, &options), expected.join(" "), "{:?}", options);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_title_cleaning_setting() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        assert_eq!(clean_rom_title_options("Tetris (World) [!]", &CleanTitleOptions::from_settings(&db)), "Tetris");
+
+        db.set_setting(TITLE_CLEANING_SETTING, r#"{"stripParentheses": false}"#).unwrap();
+        assert_eq!(clean_rom_title_options("Tetris (World) [!]", &CleanTitleOptions::from_settings(&db)), "Tetris (World)");
+    }
+
+    #[test]
+    fn test_custom_title_cleaning_rules() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let rules = vec![r"\s*<[^>]*>".to_string(), "(unclosed".to_string(), r"\s*\[[^\]]*\]".to_string()];
+        db.set_setting_json(TITLE_CLEANING_RULES_SETTING, &rules).unwrap();
+
+        // The custom tag and dump flag go, the invalid rule is skipped, and "(USA)" is no
+        // longer stripped because the user's list replaces the defaults
+        let options = CleanTitleOptions::from_settings(&db);
+        assert_eq!(clean_rom_title_options("Kirby <Beta> (USA) [!]", &options), "Kirby (USA)");
+        assert_eq!(compile_title_rules(&rules).1, vec!["(unclosed".to_string()]);
+
+        // A malformed rule list falls back to the defaults
+        db.set_setting(TITLE_CLEANING_RULES_SETTING, "not json").unwrap();
+        let options = CleanTitleOptions::from_settings(&db);
+        assert_eq!(clean_rom_title_options("Kirby <Beta> (USA) [!]", &options), "Kirby <Beta>");
+    }
+
+    #[test]
+    fn test_scraper_preference_per_platform() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        assert_eq!(preferred_provider(&db, "arcade"), MetadataProvider::Igdb);
+
+        db.set_setting(SCRAPER_PREFERENCE_SETTING, r#"{"arcade": "offline", "snes": "screenscraper"}"#).unwrap();
+        assert_eq!(preferred_provider(&db, "arcade"), MetadataProvider::Offline);
+        assert_eq!(preferred_provider(&db, "ps2"), MetadataProvider::Igdb);
+        // Not a provider this build has, so the default applies
+        assert_eq!(preferred_provider(&db, "snes"), MetadataProvider::Igdb);
+
+        db.set_setting(DEFAULT_SCRAPER_SETTING, "offline").unwrap();
+        db.set_setting(SCRAPER_PREFERENCE_SETTING, r#"{"ps2": "igdb"}"#).unwrap();
+        assert_eq!(preferred_provider(&db, "ps2"), MetadataProvider::Igdb);
+        assert_eq!(preferred_provider(&db, "arcade"), MetadataProvider::Offline);
+    }
+
+    #[test]
+    fn test_search_providers_merges_overlapping_titles() {
+        let result = |name: &str, year: &str, igdb_id: Option<u64>| ProviderSearchResult {
+            name: name.to_string(),
+            release_date: Some(format!("{}-01-01", year)),
+            cover_url: igdb_id.map(|id| format!("https://covers/{}.jpg", id)),
+            platforms: vec!["Arcade".to_string()],
+            summary: None,
+            igdb_id,
+            sources: Vec::new(),
+        };
+        // IGDB is the only searchable provider, so merge two of its searches
+        let first = vec![
+            result("The King of Fighters '98", "1998", Some(3)),
+            result("Street Fighter II", "1991", Some(4)),
+        ];
+        let mut second = vec![
+            result("King of Fighters '98, The", "1998", None),
+            result("Street Fighter III", "1997", Some(5)),
+            result("Street Fighter II", "1992", Some(6)),
+        ];
+        second[0].summary = Some("Dream match".to_string());
+        second[0].platforms = vec!["Neo Geo".to_string()];
+
+        let merged = block_on(search_providers(vec![
+            (MetadataProvider::Igdb, Box::pin(async move { Ok(first) })),
+            (MetadataProvider::Igdb, Box::pin(async move { Ok(second) })),
+            (MetadataProvider::Igdb, Box::pin(async { Err("rate limited".to_string()) })),
+        ])).unwrap();
+
+        // The sequel and the re-release a year later stay separate
+        let names: Vec<&str> = merged.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["The King of Fighters '98", "Street Fighter II", "Street Fighter III", "Street Fighter II"]);
+
+        let kof = &merged[0];
+        assert_eq!(kof.sources, [MetadataProvider::Igdb]);
+        assert_eq!(kof.igdb_id, Some(3));
+        assert_eq!(kof.summary.as_deref(), Some("Dream match"));
+        assert_eq!(kof.platforms, ["Arcade", "Neo Geo"]);
+        assert_eq!(merged[3].igdb_id, Some(6));
+
+        let failed = block_on(search_providers(vec![
+            (MetadataProvider::Igdb, Box::pin(async { Err("offline".to_string()) })),
+        ]));
+        assert_eq!(failed.unwrap_err(), "Igdb: offline");
+    }
+
+    #[test]
+    fn test_provider_chain_falls_back_on_no_match() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Kaiketsu Yancha Maru".to_string(), "/roms/Kaiketsu Yancha Maru (Japan) (1991).nes".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        assert_eq!(provider_chain(&db, "nes"), [MetadataProvider::Igdb]);
+        db.set_setting(PROVIDER_CHAIN_SETTING, r#"["igdb", "offline"]"#).unwrap();
+        let chain = provider_chain(&db, "nes");
+        assert_eq!(chain, [MetadataProvider::Igdb, MetadataProvider::Offline]);
+
+        let tried = Mutex::new(Vec::new());
+        let result = block_on(scrape_with_chain(&chain, |provider| {
+            tried.lock().unwrap().push(provider);
+            let (db, game) = (&db, &game);
+            async move {
+                match provider {
+                    MetadataProvider::Igdb => Ok(ScrapeResult {
+                        success: false,
+                        game_id: game.id.clone(),
+                        fields_updated: Vec::new(),
+                        error: Some("No matching games found on IGDB".to_string()),
+                        source: "igdb".to_string(),
+                        offline_metadata: None,
+                    }),
+                    MetadataProvider::Offline => scrape_offline(db, game, None),
+                }
+            }
+        })).unwrap();
+
+        assert_eq!(*tried.lock().unwrap(), chain);
+        assert!(result.success);
+        assert_eq!(result.source, "offline");
+        let scraped = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(scraped.release_date.as_deref(), Some("1991"));
+        assert_eq!(scraped.metadata_source.as_deref(), Some("offline"));
+
+        // An error from the first provider falls through to the next one too
+        let result = block_on(scrape_with_chain(&chain, |provider| {
+            let (db, game) = (&db, &game);
+            async move {
+                match provider {
+                    MetadataProvider::Igdb => Err("IGDB request failed: connection refused".to_string()),
+                    MetadataProvider::Offline => scrape_offline(db, game, None),
+                }
+            }
+        })).unwrap();
+        assert_eq!(result.source, "offline");
+
+        let failed = block_on(scrape_with_chain(&chain, |_| async { Err::<ScrapeResult, _>("down".to_string()) }));
+        assert_eq!(failed.unwrap_err(), "down");
+
+        // A preferred provider goes first, and isn't tried twice
+        db.set_setting(SCRAPER_PREFERENCE_SETTING, r#"{"nes": "offline"}"#).unwrap();
+        assert_eq!(provider_chain(&db, "nes"), [MetadataProvider::Offline, MetadataProvider::Igdb]);
+    }
+
+    #[test]
+    fn test_offline_scrape_without_credentials() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        assert!(db.get_setting("igdb_client_id").unwrap().is_none());
+
+        let game = Game::new("chrono".to_string(), "/roms/Chrono Trigger (USA) (1995).sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        let result = scrape_offline(&db, &game, None).unwrap();
+        assert_eq!(result.source, "offline");
+        let metadata = result.offline_metadata.unwrap();
+        assert_eq!(metadata.title, "Chrono Trigger");
+        assert_eq!(metadata.region.as_deref(), Some("USA"));
+        assert_eq!(metadata.year, Some(1995));
+        assert!(metadata.platform_name.is_some());
+
+        assert_eq!(result.fields_updated, ["release_date"]);
+        let scraped = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(scraped.release_date.as_deref(), Some("1995"));
+        assert_eq!(scraped.metadata_source.as_deref(), Some("offline"));
+        let scraped_at = chrono::DateTime::parse_from_rfc3339(scraped.last_scraped_at.as_deref().unwrap()).unwrap();
+        assert!(chrono::Utc::now().signed_duration_since(scraped_at) < chrono::Duration::minutes(1));
+    }
+
+    #[test]
+    fn test_edited_fields_survive_rescrape() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("zelda".to_string(), "/roms/Zelda (USA) (1986).nes".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        let edit = UpdateGameInput { title: Some("The Legend of Zelda".to_string()), release_date: Some("1986-02-21".to_string()), ..Default::default() };
+        edit_game(&db, &game.id, &edit).unwrap();
+        let game = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(game.manually_edited_fields, ["title", "release_date"]);
+
+        // A re-scrape drops the protected fields from its update
+        let scraped = UpdateGameInput {
+            title: Some("Zelda no Densetsu".to_string()),
+            developer: Some("Nintendo".to_string()),
+            ..Default::default()
+        };
+        db.update_game(&game.id, &scraped.without_fields(&protected_fields(&game, None))).unwrap();
+        let rescraped = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(rescraped.title, "The Legend of Zelda");
+        assert_eq!(rescraped.developer.as_deref(), Some("Nintendo"));
+
+        // Overwriting ignores the protection
+        assert!(protected_fields(&game, Some(true)).is_empty());
+
+        // Favourites aren't metadata, so toggling one protects nothing
+        edit_game(&db, &game.id, &UpdateGameInput { is_favorite: Some(true), ..Default::default() }).unwrap();
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().manually_edited_fields.len(), 2);
+    }
+
+    #[test]
+    fn test_stale_games_selected_by_last_scrape() {
+        let now = chrono::Utc::now();
+        let scraped = |title: &str, at: Option<chrono::DateTime<chrono::Utc>>| {
+            let mut game = Game::new(title.to_string(), format!("/roms/{}.nes", title), "nes".to_string());
+            game.last_scraped_at = at.map(|at| at.to_rfc3339());
+            game
+        };
+        let games = vec![
+            scraped("Old", Some(now - chrono::Duration::days(90))),
+            scraped("Recent", Some(now - chrono::Duration::days(2))),
+            scraped("Never", None),
         ];
 
-        for path_opt in possible_paths.iter() {
-            if let Some(path) = path_opt {
-                if path.exists() {
-                    return Ok(Some(path.to_string_lossy().to_string()));
-                }
-            }
+        let stale: Vec<String> = stale_games(games, now - chrono::Duration::days(30))
+            .into_iter()
+            .map(|game| game.title)
+            .collect();
+        assert_eq!(stale, ["Old", "Never"]);
+    }
+
+    #[test]
+    fn test_clear_metadata_removes_cleared_artwork() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+        let covers = app_data.path().join("images").join("covers");
+        std::fs::create_dir_all(&covers).unwrap();
+
+        let mut game = Game::new("Contra".to_string(), "/roms/contra.nes".to_string(), "nes".to_string());
+        let cover = covers.join(format!("{}.jpg", game.id));
+        let background = covers.join(format!("{}_bg.jpg", game.id));
+        std::fs::write(&cover, b"cover").unwrap();
+        std::fs::write(&background, b"background").unwrap();
+        game.cover_art_path = Some(cover.to_string_lossy().to_string());
+        game.background_path = Some(background.to_string_lossy().to_string());
+        game.description = Some("Scraped nonsense".to_string());
+        game.developer = Some("Konami".to_string());
+        db.add_game(&game).unwrap();
+
+        let fields = [ScrapeField::Description, ScrapeField::CoverArtPath];
+        assert_eq!(clear_game_metadata(&db, app_data.path(), &[game.id.clone()], &fields, true).unwrap(), 1);
+
+        let cleared = db.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(cleared.description, None);
+        assert_eq!(cleared.cover_art_path, None);
+        assert_eq!(cleared.developer.as_deref(), Some("Konami"));
+        assert_eq!(cleared.title, "Contra");
+        assert_eq!(cleared.rom_path, "/roms/contra.nes");
+        assert!(!cover.exists());
+        assert!(background.exists());
+    }
+
+    #[test]
+    fn test_write_library_html() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cover = dir.path().join("cover.png");
+        image::RgbImage::new(320, 426).save(&cover).unwrap();
+
+        let mut metroid = Game::new("Super Metroid".to_string(), "/roms/sm.sfc".to_string(), "snes".to_string());
+        metroid.cover_art_path = Some(cover.to_string_lossy().to_string());
+        db.add_game(&metroid).unwrap();
+        db.add_game(&Game::new("Kid Icarus".to_string(), "/roms/ki.nes".to_string(), "nes".to_string())).unwrap();
+
+        let report = dir.path().join("library.html");
+        write_library_html(&db, dir.path(), &report).unwrap();
+
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.contains("Super Metroid"));
+        assert!(html.contains("Kid Icarus"));
+        assert!(html.contains("data:image/jpeg;base64,"));
+    }
+
+    #[test]
+    fn test_pinned_platforms_sort_first() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let first = db.get_all_platforms().unwrap()[0].id.clone();
+        assert_ne!(first, "saturn");
+
+        assert!(db.toggle_platform_favorite("saturn").unwrap());
+        let platforms = db.get_all_platforms().unwrap();
+        assert_eq!(platforms[0].id, "saturn");
+        assert!(platforms[0].is_favorite);
+        assert_eq!(platforms[1].id, first);
+
+        assert!(!db.toggle_platform_favorite("saturn").unwrap());
+        assert_eq!(db.get_all_platforms().unwrap()[0].id, first);
+    }
+
+    #[test]
+    fn test_get_platforms_hides_empty() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.add_game(&Game::new("Chrono Trigger".to_string(), "/roms/ct.sfc".to_string(), "snes".to_string())).unwrap();
+
+        let all = list_platforms(&db, true).unwrap();
+        assert!(all.iter().any(|p| p.id == "n64"));
+
+        let populated = list_platforms(&db, false).unwrap();
+        assert_eq!(populated.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), vec!["snes"]);
+    }
+
+    #[test]
+    fn test_set_platform_color() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+
+        for bad in ["red", "#fff", "#12345g", "00f5ff0"] {
+            assert!(recolor_platform(&db, "snes", bad).is_err(), "{} should be rejected", bad);
+        }
+        assert!(recolor_platform(&db, "no-such-platform", "#112233").is_err());
+
+        recolor_platform(&db, "snes", "#A1B2C3").unwrap();
+        assert_eq!(db.get_platform("snes").unwrap().unwrap().color, "#a1b2c3");
+    }
+
+    #[test]
+    fn test_keybindings_reject_duplicates() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        assert_eq!(load_keybindings(&db).unwrap(), default_keybindings());
+
+        let mut bindings = default_keybindings();
+        bindings.insert(KeyAction::Search, "F".to_string());
+        let err = save_keybindings(&db, &bindings).unwrap_err();
+        assert!(err.contains("Search") && err.contains("ToggleFavorite"), "{}", err);
+        assert_eq!(load_keybindings(&db).unwrap(), default_keybindings());
+
+        bindings.insert(KeyAction::Search, "s".to_string());
+        save_keybindings(&db, &bindings).unwrap();
+        assert_eq!(load_keybindings(&db).unwrap()[&KeyAction::Search], "s");
+
+        // A partial map is checked against the defaults it will be merged with
+        let partial: Keybindings = [(KeyAction::Search, "f".to_string())].into_iter().collect();
+        let err = save_keybindings(&db, &partial).unwrap_err();
+        assert!(err.contains("Search") && err.contains("ToggleFavorite"), "{}", err);
+        assert_eq!(load_keybindings(&db).unwrap()[&KeyAction::Search], "s");
+
+        let partial: Keybindings = [(KeyAction::Search, "k".to_string())].into_iter().collect();
+        save_keybindings(&db, &partial).unwrap();
+        let loaded = load_keybindings(&db).unwrap();
+        assert_eq!(loaded[&KeyAction::Search], "k");
+        assert_eq!(loaded[&KeyAction::ToggleFavorite], "f");
+    }
+
+    #[test]
+    fn test_find_mislabeled_roms() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let snes_rom = |title: &[u8; 21]| {
+            let mut rom = vec![0u8; 0x8000];
+            rom[0x7FC0..0x7FD5].copy_from_slice(title);
+            rom[0x7FDC..0x7FDE].copy_from_slice(&0x5A5Au16.to_le_bytes());
+            rom[0x7FDE..0x7FE0].copy_from_slice(&0xA5A5u16.to_le_bytes());
+            rom
+        };
+
+        let wrong = dir.path().join("Super Mario World (USA).sfc");
+        let right = dir.path().join("Super Metroid (Japan, USA) (En,Ja).sfc");
+        std::fs::write(&wrong, snes_rom(b"SUPER METROID        ")).unwrap();
+        std::fs::write(&right, snes_rom(b"Super Metroid        ")).unwrap();
+
+        let mislabeled = Game::new("Super Mario World".to_string(), wrong.to_string_lossy().to_string(), "snes".to_string());
+        db.add_game(&mislabeled).unwrap();
+        db.add_game(&Game::new("Super Metroid".to_string(), right.to_string_lossy().to_string(), "snes".to_string())).unwrap();
+
+        let found = mislabeled_roms(&db).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].game_id, mislabeled.id);
+        assert_eq!(found[0].file_title, "Super Mario World");
+        assert_eq!(found[0].internal_title, "SUPER METROID");
+
+        // Truncated header titles still match
+        assert_eq!(title_similarity("The Legend of Zelda - A Link to the Past", "THE LEGEND OF ZELDA"), 1.0);
+    }
+
+    #[test]
+    fn test_write_n64_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let rom = dir.path().join("Mario.v64");
+        std::fs::write(&rom, [0x37, 0x80, 0x40, 0x12, 0xAA, 0xBB]).unwrap();
+
+        let dest = write_n64_copy(&rom, N64ByteOrder::BigEndian).unwrap();
+        assert_eq!(dest, dir.path().join("Mario.z64"));
+        assert_eq!(std::fs::read(&dest).unwrap(), vec![0x80, 0x37, 0x12, 0x40, 0xBB, 0xAA]);
+
+        // Never overwrites, and refuses a no-op conversion
+        assert!(write_n64_copy(&rom, N64ByteOrder::BigEndian).is_err());
+        assert!(write_n64_copy(&rom, N64ByteOrder::ByteSwapped).is_err());
+    }
+
+    #[test]
+    fn test_validate_rom_path() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let rom = dir.path().join("Super Mario World.sfc");
+        std::fs::write(&rom, b"rom").unwrap();
+
+        let valid = check_rom_path(&db, &rom).unwrap();
+        assert!(valid.exists && valid.readable);
+        assert_eq!(valid.detected_platform.as_deref(), Some("snes"));
+
+        let missing = check_rom_path(&db, &dir.path().join("Missing.nes")).unwrap();
+        assert!(!missing.exists && !missing.readable);
+        assert_eq!(missing.detected_platform.as_deref(), Some("nes"));
+
+        let unknown = check_rom_path(&db, &dir.path().join("notes.txt")).unwrap();
+        assert_eq!(unknown.detected_platform, None);
+    }
+
+    #[test]
+    fn test_add_games_batch_skips_existing_paths() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.add_game(&Game::new("Tetris".to_string(), "/roms/tetris.gb".to_string(), "gb".to_string())).unwrap();
+
+        let input = |title: &str, rom_path: &str| CreateGameInput {
+            title: title.to_string(),
+            rom_path: rom_path.to_string(),
+            platform_id: "gb".to_string(),
+            cover_art_path: None,
+            description: None,
+        };
+        let results = insert_games_batch(&db, vec![
+            input("Link's Awakening", "/roms/zelda.gb"),
+            input("Tetris", "/roms/tetris.gb"),
+            input("Kirby's Dream Land", "/roms/kirby.gb"),
+        ]).unwrap();
+
+        let skipped: Vec<bool> = results.iter().map(|r| r.skipped).collect();
+        assert_eq!(skipped, vec![false, true, false]);
+        assert!(results[1].game.is_none());
+        assert_eq!(results[2].game.as_ref().unwrap().title, "Kirby's Dream Land");
+        assert_eq!(db.get_all_games().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_merge_games_combines_history() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+
+        let keep = Game::new("Chrono Trigger".to_string(), "/roms/ct.sfc".to_string(), "snes".to_string());
+        let duplicate = Game::new("Chrono Trigger (USA)".to_string(), "/roms/usa/ct.sfc".to_string(), "snes".to_string());
+        db.add_game(&keep).unwrap();
+        db.add_game(&duplicate).unwrap();
+
+        let mut rpgs = Collection::new("RPGs".to_string());
+        rpgs.game_ids = vec![keep.id.clone(), duplicate.id.clone()];
+        db.add_collection(&rpgs).unwrap();
+        let mut favourites = Collection::new("Favourites".to_string());
+        favourites.game_ids = vec![duplicate.id.clone()];
+        favourites.cover_game_id = Some(duplicate.id.clone());
+        db.add_collection(&favourites).unwrap();
+
+        let updates = UpdateGameInput {
+            collection_ids: Some(vec![favourites.id.clone()]),
+            ..Default::default()
+        };
+        db.update_game(&duplicate.id, &updates).unwrap();
+
+        add_finished_session(&db, &keep.id, "2024-01-01T10:00:00+00:00", 600);
+        add_finished_session(&db, &duplicate.id, "2024-01-02T10:00:00+00:00", 900);
+        db.update_game_play_time(&keep.id, 600).unwrap();
+        db.update_game_play_time(&duplicate.id, 900).unwrap();
+
+        let kept = merge_duplicate_games(&db, app_data.path(), &keep.id, std::slice::from_ref(&duplicate.id)).unwrap();
+
+        assert_eq!(kept.total_play_time_seconds, 1500);
+        assert_eq!(kept.collection_ids, vec![favourites.id.clone()]);
+        assert!(db.get_game(&duplicate.id).unwrap().is_none());
+
+        let sessions = db.get_play_sessions(&keep.id).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.iter().map(|s| s.duration_seconds).sum::<i64>(), 1500);
+
+        assert_eq!(db.get_collection(&rpgs.id).unwrap().unwrap().game_ids, vec![keep.id.clone()]);
+        let favourites = db.get_collection(&favourites.id).unwrap().unwrap();
+        assert_eq!(favourites.game_ids, vec![keep.id.clone()]);
+        assert_eq!(favourites.cover_game_id, Some(keep.id.clone()));
+    }
+
+    #[test]
+    fn test_playtime_report_buckets() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Earthbound".to_string(), "/roms/eb.sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        // Mon 2024-01-01 and Sun 2024-01-07 share a week; Mon 2024-01-08 starts the next
+        add_finished_session(&db, &game.id, "2024-01-01T10:00:00+00:00", 600);
+        add_finished_session(&db, &game.id, "2024-01-07T22:00:00.123456789+00:00", 300);
+        add_finished_session(&db, &game.id, "2024-01-08T09:00:00+00:00", 120);
+        add_finished_session(&db, &game.id, "2024-02-14T09:00:00+00:00", 60);
+
+        let weekly = db.get_playtime_report(ReportPeriod::Week, None, None).unwrap();
+        assert_eq!(weekly, vec![
+            ("2024-01-01".to_string(), 900),
+            ("2024-01-08".to_string(), 120),
+            ("2024-02-12".to_string(), 60),
+        ]);
+
+        let monthly = db.get_playtime_report(ReportPeriod::Month, None, None).unwrap();
+        assert_eq!(monthly, vec![("2024-01".to_string(), 1020), ("2024-02".to_string(), 60)]);
+
+        let january = db.get_playtime_report(ReportPeriod::Month, Some("2024-01-02"), Some("2024-01-31")).unwrap();
+        assert_eq!(january, vec![("2024-01".to_string(), 420)]);
+    }
+
+    #[test]
+    fn test_recent_play_outranks_all_time_favorite() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let now = chrono::Utc::now();
+
+        let old_favorite = Game::new("Ocarina".to_string(), "/roms/oot.z64".to_string(), "n64".to_string());
+        db.add_game(&old_favorite).unwrap();
+        add_finished_session(&db, &old_favorite.id, &(now - chrono::Duration::days(30)).to_rfc3339(), 360_000);
+        db.recompute_game_play_time(&old_favorite.id).unwrap();
+
+        let current = Game::new("Tetris".to_string(), "/roms/tetris.gb".to_string(), "gb".to_string());
+        db.add_game(&current).unwrap();
+        add_finished_session(&db, &current.id, &(now - chrono::Duration::days(1)).to_rfc3339(), 1_800);
+        add_finished_session(&db, &current.id, &(now - chrono::Duration::hours(2)).to_rfc3339(), 600);
+
+        let ranked = find_most_played_since(&db, now - chrono::Duration::days(7), 10).unwrap();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].game.id, current.id);
+        assert_eq!(ranked[0].play_time_seconds, 2_400);
+
+        // Over a longer window the all-time favorite wins again
+        let ranked = find_most_played_since(&db, now - chrono::Duration::days(60), 10).unwrap();
+        assert_eq!(ranked[0].game.id, old_favorite.id);
+    }
+
+    #[test]
+    fn test_play_streak_consecutive_and_gapped_days() {
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let dates = ["2024-03-01", "2024-03-02", "2024-03-03", "2024-03-04", "2024-03-07", "2024-03-08"]
+            .into_iter()
+            .map(day)
+            .collect();
+
+        let streak = compute_play_streak(&dates, day("2024-03-09"));
+        assert_eq!(streak, PlayStreak { current_streak_days: 2, longest_streak_days: 4 });
+
+        let streak = compute_play_streak(&dates, day("2024-03-08"));
+        assert_eq!(streak.current_streak_days, 2);
+
+        // A missed day ends the current streak
+        let streak = compute_play_streak(&dates, day("2024-03-10"));
+        assert_eq!(streak, PlayStreak { current_streak_days: 0, longest_streak_days: 4 });
+
+        assert_eq!(
+            compute_play_streak(&Default::default(), day("2024-03-10")),
+            PlayStreak { current_streak_days: 0, longest_streak_days: 0 }
+        );
+    }
+
+    #[test]
+    fn test_add_and_toggle_achievement() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Pitfall!".to_string(), "/roms/pitfall.a26".to_string(), "atari2600".to_string());
+        db.add_game(&game).unwrap();
+
+        let achievement = create_achievement(&db, CreateAchievementInput {
+            game_id: game.id.clone(),
+            title: "  Collect every treasure ".to_string(),
+            description: Some("All 32 treasures in one run".to_string()),
+        }).unwrap();
+        assert_eq!(achievement.title, "Collect every treasure");
+        assert!(!achievement.unlocked);
+
+        assert_eq!(db.toggle_achievement(&achievement.id, "2024-05-01T20:00:00+00:00").unwrap(), Some(true));
+        let stored = &db.get_achievements(&game.id).unwrap()[0];
+        assert!(stored.unlocked);
+        assert_eq!(stored.unlocked_at.as_deref(), Some("2024-05-01T20:00:00+00:00"));
+
+        assert_eq!(db.toggle_achievement(&achievement.id, "2024-05-02T20:00:00+00:00").unwrap(), Some(false));
+        assert_eq!(db.get_achievements(&game.id).unwrap()[0].unlocked_at, None);
+        assert_eq!(db.toggle_achievement("missing", "2024-05-02T20:00:00+00:00").unwrap(), None);
+
+        // Achievements go with their game
+        db.delete_game(&game.id).unwrap();
+        assert!(db.get_achievements(&game.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_play_session_note_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let game = Game::new("Final Fantasy VI".to_string(), "/roms/ff6.sfc".to_string(), "snes".to_string());
+        state.db.add_game(&game).unwrap();
+
+        record_manual_play_session(&state.db, &game.id, "2024-01-01T10:00:00Z", 600, Some("World of Balance".to_string())).unwrap();
+
+        let session = PlaySession::new(game.id.clone());
+        state.db.create_play_session(&session).unwrap();
+        state.active_sessions.lock().unwrap().insert(game.id.clone(), ActiveSession {
+            session_id: session.id.clone(),
+            game_id: game.id.clone(),
+            start_time: chrono::Utc::now(),
+            pid: None,
+            child: None,
+            idle_seconds: 0,
+            focused_seconds: None,
+        });
+        finish_game_session(&game.id, Some("beat the final boss"), &state).unwrap();
+
+        let notes: Vec<Option<String>> = state.db.get_play_sessions(&game.id).unwrap()
+            .into_iter()
+            .map(|s| s.note)
+            .collect();
+        assert_eq!(notes, vec![Some("beat the final boss".to_string()), Some("World of Balance".to_string())]);
+    }
+
+    #[test]
+    fn test_smart_collection_filters_by_platform_and_favorite() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+
+        let mut favorite_snes = Game::new("Earthbound".to_string(), "/roms/eb.sfc".to_string(), "snes".to_string());
+        favorite_snes.is_favorite = true;
+        let plain_snes = Game::new("Pilotwings".to_string(), "/roms/pw.sfc".to_string(), "snes".to_string());
+        let mut favorite_nes = Game::new("Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string());
+        favorite_nes.is_favorite = true;
+        for game in [&favorite_snes, &plain_snes, &favorite_nes] {
+            db.add_game(game).unwrap();
         }
+
+        let collection = SmartCollection::new("Favorite SNES".to_string(), SmartCollectionRule {
+            platform_ids: Some(vec!["snes".to_string()]),
+            is_favorite: Some(true),
+            ..Default::default()
+        });
+        db.add_smart_collection(&collection).unwrap();
+
+        let games = find_smart_collection_games(&db, &collection.id).unwrap();
+        let ids: Vec<&str> = games.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec![favorite_snes.id.as_str()]);
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        if let Some(home) = dirs::home_dir() {
-            let cores_path = home.join(".config/retroarch/cores");
-            if cores_path.exists() {
-                return Ok(Some(cores_path.to_string_lossy().to_string()));
-            }
+    #[test]
+    fn test_collection_stats_aggregates_members() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+
+        let mut done = Game::new("Super Metroid".to_string(), "/roms/sm.sfc".to_string(), "snes".to_string());
+        done.total_play_time_seconds = 3600;
+        done.is_completed = true;
+        let mut started = Game::new("Castlevania".to_string(), "/roms/cv.nes".to_string(), "nes".to_string());
+        started.total_play_time_seconds = 600;
+        let outsider = Game::new("Tetris".to_string(), "/roms/tetris.gb".to_string(), "gb".to_string());
+        for game in [&done, &started, &outsider] {
+            db.add_game(game).unwrap();
         }
-    }
 
-    Ok(None)
-}
+        let mut collection = Collection::new("Backlog".to_string());
+        collection.game_ids = vec![done.id.clone(), started.id.clone()];
+        db.add_collection(&collection).unwrap();
 
-/// Scan a folder for RetroArch cores
-#[tauri::command]
-pub fn scan_retroarch_cores(cores_path: String) -> Result<Vec<RetroArchCore>, String> {
-    let path = Path::new(&cores_path);
+        let stats = compute_collection_stats(&db, &collection.id).unwrap();
+        assert_eq!(stats.game_count, 2);
+        assert_eq!(stats.total_play_time_seconds, 4200);
+        assert_eq!(stats.completed_count, 1);
+        assert_eq!(stats.platforms, vec!["nes", "snes"]);
+    }
 
-    if !path.exists() || !path.is_dir() {
-        return Err("Cores folder does not exist".to_string());
+    #[test]
+    fn test_never_played_virtual_collection() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let played = Game::new("Mega Man 2".to_string(), "/roms/mm2.nes".to_string(), "nes".to_string());
+        let unplayed = Game::new("Mega Man 3".to_string(), "/roms/mm3.nes".to_string(), "nes".to_string());
+        db.add_game(&played).unwrap();
+        db.add_game(&unplayed).unwrap();
+        record_manual_play_session(&db, &played.id, "2024-01-01T10:00:00Z", 900, None).unwrap();
+
+        let games = find_virtual_collection_games(&db, VirtualCollectionKind::NeverPlayed).unwrap();
+        let ids: Vec<&str> = games.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec![unplayed.id.as_str()]);
     }
 
-    // Core file extension based on platform
-    #[cfg(target_os = "macos")]
-    let core_ext = "dylib";
-    #[cfg(target_os = "windows")]
-    let core_ext = "dll";
-    #[cfg(target_os = "linux")]
-    let core_ext = "so";
+    #[test]
+    fn test_games_grouped_by_decade() {
+        let mut donkey_kong = Game::new("Donkey Kong Country".to_string(), "/roms/dkc.sfc".to_string(), "snes".to_string());
+        donkey_kong.release_date = Some("1994-11-21".to_string());
+        let mut pac_man = Game::new("Pac-Man".to_string(), "/roms/pacman.zip".to_string(), "arcade".to_string());
+        pac_man.release_date = Some("1980-05-22".to_string());
+        let homebrew = Game::new("Homebrew".to_string(), "/roms/homebrew.nes".to_string(), "nes".to_string());
+
+        let groups = group_games_by_decade(vec![donkey_kong, homebrew, pac_man]);
+        let summary: Vec<(&str, Vec<&str>)> = groups.iter()
+            .map(|(decade, games)| (decade.as_str(), games.iter().map(|g| g.title.as_str()).collect()))
+            .collect();
 
-    let mut cores = Vec::new();
+        assert_eq!(summary, vec![
+            ("1980s", vec!["Pac-Man"]),
+            ("1990s", vec!["Donkey Kong Country"]),
+            ("Unknown", vec!["Homebrew"]),
+        ]);
+    }
 
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.filter_map(|e| e.ok()) {
-            let file_path = entry.path();
+    #[test]
+    fn test_collection_cover_falls_back_to_member_art() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let no_art = Game::new("Kirby".to_string(), "/roms/kirby.gb".to_string(), "gb".to_string());
+        let mut with_art = Game::new("Wario Land".to_string(), "/roms/wario.gb".to_string(), "gb".to_string());
+        with_art.cover_art_path = Some("/covers/wario.png".to_string());
+        db.add_game(&no_art).unwrap();
+        db.add_game(&with_art).unwrap();
+
+        let mut collection = Collection::new("Handhelds".to_string());
+        collection.game_ids = vec![no_art.id.clone(), with_art.id.clone()];
+        db.add_collection(&collection).unwrap();
+
+        let collections = resolve_collection_covers(&db, db.get_all_collections().unwrap()).unwrap();
+        assert_eq!(collections[0].cover_game_id, None);
+        assert_eq!(collections[0].cover_art_path.as_deref(), Some("/covers/wario.png"));
+    }
 
-            // Check if it's a core file (ends with _libretro.dylib/dll/so)
-            if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
-                if file_name.ends_with(&format!("_libretro.{}", core_ext)) {
-                    // Extract display name from file name
-                    // e.g., "snes9x_libretro.dylib" -> "snes9x"
-                    let display_name = file_name
-                        .trim_end_matches(&format!("_libretro.{}", core_ext))
-                        .replace('_', " ")
-                        .split_whitespace()
-                        .map(|word| {
-                            let mut chars = word.chars();
-                            match chars.next() {
-                                Some(first) => first.to_uppercase().chain(chars).collect(),
-                                None => String::new(),
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join(" ");
+    #[test]
+    fn test_custom_platform_is_scanned() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let amiga = CreatePlatformInput {
+            id: "amiga".to_string(),
+            display_name: "Amiga".to_string(),
+            manufacturer: "Commodore".to_string(),
+            file_extensions: vec![".adf".to_string()],
+            color: None,
+        };
+        create_platform(&db, amiga.clone()).unwrap();
+        assert!(create_platform(&db, amiga).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Lemmings.adf"), b"DOS").unwrap();
+
+        let result = scan_paths(vec![ScanPath {
+            path: dir.path().to_string_lossy().to_string(),
+            platform_id: None,
+        }], &db).unwrap();
+        assert_eq!(result.games_added, 1);
+
+        let games = db.get_all_games().unwrap();
+        assert_eq!(games[0].platform_id, "amiga");
+    }
 
-                    cores.push(RetroArchCore {
-                        file_name: file_name.to_string(),
-                        display_name,
-                        full_path: file_path.to_string_lossy().to_string(),
-                    });
-                }
-            }
-        }
+    #[test]
+    fn test_scan_stores_n64_byte_order() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Mario.v64"), [0x37, 0x80, 0x40, 0x12]).unwrap();
+        std::fs::write(dir.path().join("Zelda.z64"), [0x80, 0x37, 0x12, 0x40]).unwrap();
+        std::fs::write(dir.path().join("Junk.n64"), b"junk").unwrap();
+
+        scan_paths(vec![ScanPath {
+            path: dir.path().to_string_lossy().to_string(),
+            platform_id: Some("n64".to_string()),
+        }], &db).unwrap();
+
+        let order = |title: &str| db.get_all_games().unwrap().into_iter().find(|g| g.title == title).unwrap().n64_byte_order;
+        assert_eq!(order("Mario"), Some(N64ByteOrder::ByteSwapped));
+        assert_eq!(order("Zelda"), Some(N64ByteOrder::BigEndian));
+        assert_eq!(order("Junk"), None);
     }
 
-    // Sort by display name
-    cores.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    #[test]
+    fn test_multi_disc_set_counted_once() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Final Fantasy VII (Disc 1).cue"), b"disc1").unwrap();
+        std::fs::write(dir.path().join("Final Fantasy VII (Disc 2).cue"), b"disc2").unwrap();
+
+        let scan_path = || ScanPath {
+            path: dir.path().to_string_lossy().to_string(),
+            platform_id: Some("ps1".to_string()),
+        };
+
+        let result = scan_paths(vec![scan_path()], &db).unwrap();
+        assert_eq!(result.games_found, 1);
+        assert_eq!(result.games_added, 1);
+        assert_eq!(result.games_updated, 0);
+        assert!(dir.path().join("Final Fantasy VII.m3u").exists());
+
+        // Rescanning finds the playlist on disk and counts it once more, as an update
+        let rescan = scan_paths(vec![scan_path()], &db).unwrap();
+        assert_eq!(rescan.games_found, 1);
+        assert_eq!(rescan.games_added, 0);
+        assert_eq!(rescan.games_updated, 1);
+        assert_eq!(db.get_all_games().unwrap().len(), 1);
+    }
 
-    Ok(cores)
-}
+    #[test]
+    fn test_updated_extensions_are_scanned() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Shenmue.gdi"), b"3").unwrap();
+        let scan = || scan_paths(vec![ScanPath {
+            path: dir.path().to_string_lossy().to_string(),
+            platform_id: None,
+        }], &db).unwrap();
+
+        assert_eq!(scan().games_added, 0);
+
+        assert!(normalize_extensions(&["gdi".to_string()]).is_err());
+        db.update_platform("dreamcast", &UpdatePlatformInput {
+            file_extensions: Some(normalize_extensions(&[".cue".to_string(), ".GDI".to_string()]).unwrap()),
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(db.get_platform("dreamcast").unwrap().unwrap().file_extensions, vec![".cue", ".gdi"]);
+        assert_eq!(scan().games_added, 1);
+    }
 
-// ==================== METADATA SCRAPING COMMANDS ====================
+    fn add_amiga_with_game(db: &Database) -> Game {
+        create_platform(db, CreatePlatformInput {
+            id: "amiga".to_string(),
+            display_name: "Amiga".to_string(),
+            manufacturer: "Commodore".to_string(),
+            file_extensions: vec![".adf".to_string()],
+            color: None,
+        }).unwrap();
+        let game = Game::new("Lemmings".to_string(), "/roms/lemmings.adf".to_string(), "amiga".to_string());
+        db.add_game(&game).unwrap();
+        game
+    }
 
-use crate::scraper::{IgdbClient, IgdbSearchResult, ScrapeResult, BatchScrapeResult};
+    #[test]
+    fn test_delete_platform_blocked_by_games() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        add_amiga_with_game(&db);
 
-/// Validate IGDB credentials
-#[tauri::command]
-pub async fn validate_igdb_credentials(client_id: String, client_secret: String) -> Result<bool, String> {
-    let client = IgdbClient::new(client_id, client_secret);
-    client.validate_credentials().await
-}
+        let result = remove_platform(&db, "amiga", None).unwrap();
+        assert!(!result.deleted);
+        assert_eq!(result.blocking_game_count, 1);
+        assert!(db.get_platform("amiga").unwrap().is_some());
 
-/// Search IGDB for games matching a query
-#[tauri::command]
-pub async fn search_igdb(
-    query: String,
-    platform_id: Option<String>,
-    state: State<'_, AppState>,
-) -> Result<Vec<IgdbSearchResult>, String> {
-    // Get IGDB credentials from settings
-    let client_id = state.db.get_setting("igdb_client_id")
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "IGDB Client ID not configured".to_string())?;
-    let client_secret = state.db.get_setting("igdb_client_secret")
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "IGDB Client Secret not configured".to_string())?;
+        assert!(remove_platform(&db, "snes", None).is_err());
+    }
 
-    let client = IgdbClient::new(client_id, client_secret);
-    client.search_games(&query, platform_id.as_deref()).await
-}
+    #[test]
+    fn test_delete_platform_reassigns_games() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = add_amiga_with_game(&db);
 
-/// Scrape metadata for a single game
-#[tauri::command]
-pub async fn scrape_game_metadata(
-    game_id: String,
-    igdb_id: Option<u64>,
-    app_handle: tauri::AppHandle,
-    state: State<'_, AppState>,
-) -> Result<ScrapeResult, String> {
-    // Get the game
-    let game = state.db.get_game(&game_id)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Game not found".to_string())?;
+        let result = remove_platform(&db, "amiga", Some("dos")).unwrap();
+        assert!(result.deleted);
+        assert_eq!(result.reassigned_count, 1);
+        assert!(db.get_platform("amiga").unwrap().is_none());
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().platform_id, "dos");
+    }
 
-    // Get IGDB credentials
-    let client_id = state.db.get_setting("igdb_client_id")
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "IGDB Client ID not configured".to_string())?;
-    let client_secret = state.db.get_setting("igdb_client_secret")
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "IGDB Client Secret not configured".to_string())?;
+    #[test]
+    fn test_reset_platform_defaults_restores_extensions() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.update_platform("snes", &UpdatePlatformInput {
+            file_extensions: Some(vec![".bin".to_string()]),
+            color: Some("#000000".to_string()),
+            ..Default::default()
+        }).unwrap();
+
+        let platform = restore_platform_defaults(&db, "snes").unwrap();
+        assert_eq!(platform.file_extensions, vec![".sfc", ".smc"]);
+        assert_eq!(platform.color, "#7b5aa6");
+
+        add_amiga_with_game(&db);
+        assert!(restore_platform_defaults(&db, "amiga").is_err());
+        assert!(restore_platform_defaults(&db, "unknown").is_err());
+    }
 
-    let client = IgdbClient::new(client_id, client_secret);
+    #[test]
+    fn test_custom_display_order() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
 
-    // If no IGDB ID provided, search for the game
-    let target_igdb_id = if let Some(id) = igdb_id {
-        id
-    } else {
-        // Search for the game by title and platform
-        let results = client.search_games(&game.title, Some(&game.platform_id)).await?;
+        db.reorder_platforms(&["snes".to_string(), "nes".to_string()]).unwrap();
+        let platforms = db.get_all_platforms().unwrap();
+        assert_eq!(platforms[0].id, "snes");
+        assert_eq!(platforms[1].id, "nes");
+        assert!(platforms.len() > 2);
 
-        if results.is_empty() {
-            // Try without platform filter
-            let results = client.search_games(&game.title, None).await?;
-            if results.is_empty() {
-                return Ok(ScrapeResult {
-                    success: false,
-                    game_id: game_id.clone(),
-                    fields_updated: vec![],
-                    error: Some("No matching games found on IGDB".to_string()),
-                });
-            }
-            results[0].igdb_id
-        } else {
-            results[0].igdb_id
+        for name in ["Alpha", "Bravo", "Charlie"] {
+            db.add_emulator(&Emulator::new(name.to_string(), format!("/emus/{}", name))).unwrap();
         }
-    };
+        let ids: Vec<String> = db.get_all_emulators().unwrap().into_iter().rev().map(|e| e.id).collect();
+        db.reorder_emulators(&ids).unwrap();
 
-    // Get full metadata
-    let metadata = client.get_game_metadata(target_igdb_id).await?;
+        let names: Vec<String> = db.get_all_emulators().unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["Charlie", "Bravo", "Alpha"]);
+    }
 
-    // Get app data directory for images
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let images_dir = app_data_dir.join("images");
+    #[test]
+    fn test_platform_game_counts() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        for (title, platform) in [("A", "snes"), ("B", "snes"), ("C", "nes")] {
+            db.add_game(&Game::new(title.to_string(), format!("/roms/{}", title), platform.to_string())).unwrap();
+        }
 
-    let mut fields_updated = Vec::new();
+        let counts = db.get_platform_game_counts(false).unwrap();
+        assert_eq!(counts, vec![("nes".to_string(), 1), ("snes".to_string(), 2)]);
 
-    println!("Metadata cover_url: {:?}", metadata.cover_url);
+        let with_empty = db.get_platform_game_counts(true).unwrap();
+        assert_eq!(with_empty.len(), crate::db::default_platforms().len());
+        assert!(with_empty.contains(&("gba".to_string(), 0)));
+        assert!(with_empty.contains(&("snes".to_string(), 2)));
+    }
 
-    // Download cover art with high-res fallback
-    let cover_path = if let Some(ref url) = metadata.cover_url {
-        let cover_dir = images_dir.join("covers");
-        let cover_path = cover_dir.join(format!("{}.jpg", game_id));
+    #[test]
+    fn test_set_platform_icon_copies_image() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("logo.PNG");
+        std::fs::write(&source, b"png").unwrap();
+
+        let dest = copy_platform_icon(&db, dir.path(), "snes", &source.to_string_lossy()).unwrap();
+        assert!(dest.ends_with("snes.png"));
+        assert_eq!(std::fs::read(&dest).unwrap(), b"png");
+        assert_eq!(db.get_platform("snes").unwrap().unwrap().icon_path, Some(dest));
+
+        let bad = dir.path().join("logo.bmp");
+        std::fs::write(&bad, b"bmp").unwrap();
+        assert!(copy_platform_icon(&db, dir.path(), "snes", &bad.to_string_lossy()).is_err());
+    }
 
-        println!("Downloading cover to: {:?}", cover_path);
+    #[test]
+    fn test_delete_game_removes_sessions_and_images() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let covers_dir = dir.path().join("images").join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let cover = covers_dir.join("game.jpg");
+        std::fs::write(&cover, b"jpg").unwrap();
+        let users_own = dir.path().join("my-art.png");
+        std::fs::write(&users_own, b"png").unwrap();
+
+        let mut game = Game::new("Doomed".to_string(), "/roms/doomed.nes".to_string(), "nes".to_string());
+        game.cover_art_path = Some(cover.to_string_lossy().to_string());
+        game.background_path = Some(users_own.to_string_lossy().to_string());
+        db.add_game(&game).unwrap();
+        let session = PlaySession::new(game.id.clone());
+        db.create_play_session(&session).unwrap();
+
+        remove_game(&db, dir.path(), &game.id).unwrap();
+
+        assert!(db.get_game(&game.id).unwrap().is_none());
+        assert!(db.get_play_session(&session.id).unwrap().is_none());
+        assert!(!cover.exists());
+        assert!(users_own.exists());
+    }
 
-        // Extract image_id from URL (format: .../t_cover_big/{image_id}.jpg)
-        let image_id = url
-            .rsplit('/')
-            .next()
-            .and_then(|s| s.strip_suffix(".jpg"))
-            .unwrap_or("");
+    #[test]
+    fn test_find_corrupt_images() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let covers_dir = dir.path().join("images").join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let good = covers_dir.join("good.png");
+        image::RgbImage::from_pixel(8, 8, image::Rgb([0, 0, 0])).save(&good).unwrap();
+        let empty = covers_dir.join("empty.jpg");
+        std::fs::write(&empty, b"").unwrap();
+        let truncated = covers_dir.join("truncated.png");
+        std::fs::write(&truncated, &std::fs::read(&good).unwrap()[..40]).unwrap();
+
+        let mut broken = Game::new("Broken".to_string(), "/roms/broken.nes".to_string(), "nes".to_string());
+        broken.cover_art_path = Some(empty.to_string_lossy().to_string());
+        broken.screenshots = vec![good.to_string_lossy().to_string(), truncated.to_string_lossy().to_string()];
+        db.add_game(&broken).unwrap();
+        let mut fine = Game::new("Fine".to_string(), "/roms/fine.nes".to_string(), "nes".to_string());
+        fine.cover_art_path = Some(good.to_string_lossy().to_string());
+        fine.background_path = Some(dir.path().join("missing.png").to_string_lossy().to_string());
+        db.add_game(&fine).unwrap();
+
+        let found = corrupt_images(&db, dir.path(), false).unwrap();
+        let paths: Vec<&str> = found.iter().map(|image| image.path.as_str()).collect();
+        assert_eq!(paths, vec![empty.to_str().unwrap(), truncated.to_str().unwrap()]);
+        assert_eq!(found[0].field, ScrapeField::CoverArtPath);
+        assert!(empty.exists());
+
+        corrupt_images(&db, dir.path(), true).unwrap();
+        let cleared = db.get_game(&broken.id).unwrap().unwrap();
+        assert!(cleared.cover_art_path.is_none());
+        assert_eq!(cleared.screenshots, vec![good.to_string_lossy().to_string()]);
+        assert!(!empty.exists());
+        assert!(good.exists());
+        assert!(corrupt_images(&db, dir.path(), false).unwrap().is_empty());
+    }
 
-        if !image_id.is_empty() {
-            // Use fallback method: tries t_cover_big_2x (528x748) first, then t_cover_big (264x374)
-            if let Err(e) = client.download_cover_with_fallback(image_id, &cover_path).await {
-                eprintln!("Failed to download cover: {}", e);
-                None
-            } else {
-                println!("Cover downloaded successfully (with high-res fallback)");
-                fields_updated.push("cover_art_path".to_string());
-                Some(cover_path.to_string_lossy().to_string())
-            }
-        } else {
-            eprintln!("Could not extract image_id from cover URL");
-            None
+    #[test]
+    fn test_dedup_cover_images_shares_one_file() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let images_dir = dir.path().join("images");
+        let covers_dir = images_dir.join("covers");
+        std::fs::create_dir_all(&covers_dir).unwrap();
+
+        let placeholder = b"placeholder cover bytes";
+        let mut games = Vec::new();
+        for (name, contents) in [("a", &placeholder[..]), ("b", &placeholder[..]), ("c", b"real cover")] {
+            let cover = covers_dir.join(format!("{}.jpg", name));
+            std::fs::write(&cover, contents).unwrap();
+            let mut game = Game::new(name.to_string(), format!("/roms/{}.nes", name), "nes".to_string());
+            game.cover_art_path = Some(cover.to_string_lossy().to_string());
+            db.add_game(&game).unwrap();
+            games.push(game);
         }
-    } else {
-        println!("No cover URL in metadata");
-        None
-    };
 
-    // Download screenshots
-    let mut screenshot_paths = Vec::new();
-    let screenshots_dir = images_dir.join("screenshots");
+        let result = dedup_library_covers(&db, &images_dir).unwrap();
+        assert_eq!(result.files_removed, 2);
+        assert_eq!(result.games_updated, 2);
+        assert_eq!(result.bytes_saved, placeholder.len() as u64);
+
+        let cover = |game: &Game| db.get_game(&game.id).unwrap().unwrap().cover_art_path.unwrap();
+        let shared = covers_dir.join("shared").join(format!("{:x}.jpg", md5::compute(placeholder)));
+        assert_eq!(cover(&games[0]), shared.to_string_lossy());
+        assert_eq!(cover(&games[1]), shared.to_string_lossy());
+        assert_eq!(cover(&games[2]), covers_dir.join("c.jpg").to_string_lossy());
+        assert!(!covers_dir.join("a.jpg").exists());
+        assert!(!covers_dir.join("b.jpg").exists());
+
+        // Running again finds nothing to do
+        let rerun = dedup_library_covers(&db, &images_dir).unwrap();
+        assert_eq!((rerun.files_removed, rerun.games_updated, rerun.bytes_saved), (0, 0, 0));
+
+        // A new cover for one of the sharing games doesn't replace the other's
+        let new_cover = dir.path().join("new.jpg");
+        std::fs::write(&new_cover, b"rescraped cover").unwrap();
+        store_cover_art(&db, dir.path(), &games[0].id, &new_cover).unwrap();
+        assert_eq!(std::fs::read(cover(&games[1])).unwrap(), placeholder);
+
+        // Deleting one of the sharing games leaves the other's cover alone
+        db.update_game(&games[0].id, &UpdateGameInput { cover_art_path: Some(shared.to_string_lossy().to_string()), ..Default::default() }).unwrap();
+        remove_game(&db, dir.path(), &games[0].id).unwrap();
+        assert!(Path::new(&cover(&games[1])).exists());
+    }
 
-    for (i, url) in metadata.screenshot_urls.iter().enumerate() {
-        let screenshot_path = screenshots_dir.join(format!("{}_{}.jpg", game_id, i));
+    #[test]
+    fn test_rotate_database_backups_keeps_retention() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.set_setting(BACKUP_RETENTION_SETTING, "1").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let backups_dir = dir.path().join("backups");
 
-        if let Err(e) = client.download_image(url, &screenshot_path).await {
-            eprintln!("Failed to download screenshot {}: {}", i, e);
-        } else {
-            screenshot_paths.push(screenshot_path.to_string_lossy().to_string());
-        }
-    }
+        let now = chrono::Local::now();
+        let first = rotate_database_backups(&db, &backups_dir, now - chrono::Duration::hours(1)).unwrap();
+        let second = rotate_database_backups(&db, &backups_dir, now).unwrap();
 
-    if !screenshot_paths.is_empty() {
-        fields_updated.push("screenshots".to_string());
+        assert!(!first.exists());
+        assert!(second.exists());
+        assert_eq!(std::fs::read_dir(&backups_dir).unwrap().count(), 1);
+
+        // A backup is a complete database
+        let restored = Database::new(second).unwrap();
+        assert_eq!(restored.get_setting(BACKUP_RETENTION_SETTING).unwrap().as_deref(), Some("1"));
     }
 
-    // Build update input
-    let mut updates = crate::models::UpdateGameInput::default();
+    #[test]
+    fn test_configured_igdb_client_only_none_without_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        assert!(configured_igdb_client(&state).unwrap().is_none());
 
-    if let Some(cover) = cover_path {
-        updates.cover_art_path = Some(cover);
+        state.db.set_setting("igdb_client_id", "client").unwrap();
+        assert!(configured_igdb_client(&state).unwrap().is_none());
+
+        // A secret that no longer decrypts is an error, not "unconfigured"
+        state.db.set_setting("secret.igdb_client_secret", "zz").unwrap();
+        assert!(configured_igdb_client(&state).is_err());
+
+        state.secrets.set_secret(&state.db, "igdb_client_secret", "secret").unwrap();
+        assert!(configured_igdb_client(&state).unwrap().is_some());
+
+        state.db.set_setting(IGDB_PLATFORM_OVERRIDES_SETTING, "not json").unwrap();
+        assert!(configured_igdb_client(&state).is_err());
+        assert!(igdb_client(&state).is_err());
     }
 
-    if !screenshot_paths.is_empty() {
-        updates.screenshots = Some(screenshot_paths);
+    #[test]
+    fn test_restore_synced_database_keeps_local_secrets() {
+        let remote = Database::new(PathBuf::from(":memory:")).unwrap();
+        let mut game = Game::new("Super Metroid".to_string(), "/roms/Super Metroid.sfc".to_string(), "snes".to_string());
+        game.cover_art_path = Some("/home/other/retrovoid/images/covers/sm.jpg".to_string());
+        remote.add_game(&game).unwrap();
+        remote.set_setting("secret.igdb_client_secret", "sealed-elsewhere").unwrap();
+        remote.set_setting(SYNC_URL_SETTING, "https://old.example/dav").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let backup = dir.path().join("pulled.db");
+        remote.backup_to(&backup).unwrap();
+
+        let local = Database::new(PathBuf::from(":memory:")).unwrap();
+        local.set_setting("secret.sync_webdav_password", "sealed-here").unwrap();
+        local.set_setting(SYNC_URL_SETTING, "https://dav.example/retrovoid").unwrap();
+
+        let manifest = SyncManifest {
+            images_dir: "/home/other/retrovoid/images".to_string(),
+            images: Default::default(),
+        };
+        let images_dir = dir.path().join("images");
+        restore_synced_database(&local, &backup, &manifest, &images_dir).unwrap();
+
+        let restored = local.get_game(&game.id).unwrap().unwrap();
+        assert_eq!(restored.cover_art_path, Some(images_dir.join("covers/sm.jpg").to_string_lossy().to_string()));
+        assert_eq!(local.get_setting(SYNC_URL_SETTING).unwrap().as_deref(), Some("https://dav.example/retrovoid"));
+        assert_eq!(local.get_setting("secret.sync_webdav_password").unwrap().as_deref(), Some("sealed-here"));
+        assert_eq!(local.get_setting("secret.igdb_client_secret").unwrap(), None);
     }
 
-    if metadata.summary.is_some() {
-        updates.description = metadata.summary;
-        fields_updated.push("description".to_string());
+    #[test]
+    fn test_list_save_states_uses_emulator_folder() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let states_dir = tempfile::tempdir().unwrap();
+        std::fs::write(states_dir.path().join("Super Metroid.state1"), b"state").unwrap();
+
+        let emulator = Emulator::new("Snes9x".to_string(), "/emus/snes9x".to_string());
+        db.add_emulator(&emulator).unwrap();
+        let game = Game::new("Super Metroid".to_string(), "/roms/Super Metroid.sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        // No emulator configured for the game yet
+        assert!(list_game_save_states(&db, &game.id).unwrap().is_empty());
+
+        let updates = UpdateGameInput {
+            preferred_emulator_id: Some(emulator.id.clone()),
+            ..Default::default()
+        };
+        db.update_game(&game.id, &updates).unwrap();
+        let overrides = HashMap::from([(emulator.id.clone(), states_dir.path().to_string_lossy().to_string())]);
+        db.set_setting_json(SAVE_STATE_DIRS_SETTING, &overrides).unwrap();
+
+        let states = list_game_save_states(&db, &game.id).unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].slot, Some(1));
     }
 
-    if metadata.release_date.is_some() {
-        updates.release_date = metadata.release_date;
-        fields_updated.push("release_date".to_string());
+    #[test]
+    fn test_import_emulator_screenshots_by_rom_name() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+        let shots = tempfile::tempdir().unwrap();
+
+        std::fs::write(shots.path().join("Super Metroid-240101-120000.png"), b"shot").unwrap();
+        std::fs::write(shots.path().join("Other Game-240101-120000.png"), b"other").unwrap();
+        std::fs::write(shots.path().join("Super Metroid.txt"), b"notes").unwrap();
+
+        let game = Game::new("Super Metroid".to_string(), "/roms/Super Metroid.sfc".to_string(), "snes".to_string());
+        db.add_game(&game).unwrap();
+
+        let imported = import_screenshots_from(&db, app_data.path(), &game.id, shots.path()).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert!(imported[0].ends_with(&format!("{}_Super Metroid-240101-120000.png", game.id)));
+        assert_eq!(std::fs::read(&imported[0]).unwrap(), b"shot");
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().screenshots, imported);
+
+        // Importing again doesn't duplicate
+        assert!(import_screenshots_from(&db, app_data.path(), &game.id, shots.path()).unwrap().is_empty());
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().screenshots.len(), 1);
     }
 
-    if !metadata.genres.is_empty() {
-        updates.genre = Some(metadata.genres);
-        fields_updated.push("genre".to_string());
+    #[test]
+    fn test_match_local_artwork_uses_sibling_image() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let roms = tempfile::tempdir().unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+
+        let rom = roms.path().join("Game.rom");
+        std::fs::write(&rom, b"rom").unwrap();
+        std::fs::write(roms.path().join("Game.png"), b"png").unwrap();
+        std::fs::write(roms.path().join("Other.png"), b"other").unwrap();
+
+        let game = Game::new("Game".to_string(), rom.to_string_lossy().to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        assert_eq!(match_local_artwork_in(&db, app_data.path(), roms.path()).unwrap(), 1);
+
+        let cover = db.get_game(&game.id).unwrap().unwrap().cover_art_path.unwrap();
+        assert!(cover.starts_with(&*app_data.path().to_string_lossy()));
+        assert!(cover.ends_with(&format!("{}.png", game.id)));
+        assert_eq!(std::fs::read(&cover).unwrap(), b"png");
+
+        // Games that already have a cover are left alone
+        assert_eq!(match_local_artwork_in(&db, app_data.path(), roms.path()).unwrap(), 0);
     }
 
-    if metadata.developer.is_some() {
-        updates.developer = metadata.developer;
-        fields_updated.push("developer".to_string());
+    #[test]
+    fn test_match_local_artwork_folders_ignore_case() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let roms = tempfile::tempdir().unwrap();
+        let app_data = tempfile::tempdir().unwrap();
+
+        let box_front = roms.path().join("MEDIA").join("Box FRONT");
+        std::fs::create_dir_all(&box_front).unwrap();
+        std::fs::write(box_front.join("Game.jpg"), b"jpg").unwrap();
+        let rom = roms.path().join("Game.rom");
+        std::fs::write(&rom, b"rom").unwrap();
+
+        let game = Game::new("Game".to_string(), rom.to_string_lossy().to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+
+        assert_eq!(match_local_artwork_in(&db, app_data.path(), roms.path()).unwrap(), 1);
+        let cover = db.get_game(&game.id).unwrap().unwrap().cover_art_path.unwrap();
+        assert_eq!(std::fs::read(&cover).unwrap(), b"jpg");
+        assert_eq!(find_dir_ignoring_case(roms.path(), "media/box front"), Some(box_front));
+        assert_eq!(find_dir_ignoring_case(roms.path(), "covers"), None);
     }
 
-    if metadata.publisher.is_some() {
-        updates.publisher = metadata.publisher;
-        fields_updated.push("publisher".to_string());
+    #[test]
+    fn test_convert_library_images_updates_paths() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let images_dir = dir.path().join("images");
+        std::fs::create_dir_all(images_dir.join("covers")).unwrap();
+
+        let cover = images_dir.join("covers").join("game.jpg");
+        image::RgbImage::from_pixel(32, 32, image::Rgb([250, 200, 0]))
+            .save_with_format(&cover, image::ImageFormat::Jpeg)
+            .unwrap();
+
+        // Covers outside the images directory are the user's own files
+        let outside = dir.path().join("mine.jpg");
+        std::fs::copy(&cover, &outside).unwrap();
+
+        let mut game = Game::new("Game".to_string(), "/roms/game.nes".to_string(), "nes".to_string());
+        game.cover_art_path = Some(cover.to_string_lossy().to_string());
+        game.screenshots = vec![outside.to_string_lossy().to_string()];
+        db.add_game(&game).unwrap();
+
+        let result = convert_library_images(&db, &images_dir).unwrap();
+        assert_eq!(result.images_converted, 1);
+        assert!(result.bytes_saved > 0);
+        assert!(result.errors.is_empty());
+
+        let stored = db.get_game(&game.id).unwrap().unwrap();
+        let converted = images_dir.join("covers").join("game.webp");
+        assert_eq!(stored.cover_art_path, Some(converted.to_string_lossy().to_string()));
+        assert!(converted.exists());
+        assert!(!cover.exists());
+        assert_eq!(stored.screenshots, game.screenshots);
+        assert!(outside.exists());
     }
 
-    // Update the game in the database
-    state.db.update_game(&game_id, &updates)
-        .map_err(|e| e.to_string())?;
+    #[test]
+    fn test_setting_json_round_trip() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct WindowState {
+            width: u32,
+            height: u32,
+            maximized: bool,
+        }
 
-    Ok(ScrapeResult {
-        success: true,
-        game_id,
-        fields_updated,
-        error: None,
-    })
-}
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        assert_eq!(db.get_setting_json::<WindowState>("window_state").unwrap(), None);
 
-/// Batch scrape metadata for all games (or only those missing metadata)
-#[tauri::command]
-pub async fn scrape_library_metadata(
-    only_missing: bool,
-    app_handle: tauri::AppHandle,
-    state: State<'_, AppState>,
-) -> Result<BatchScrapeResult, String> {
-    // Get all games
-    let games = state.db.get_all_games().map_err(|e| e.to_string())?;
+        let window = WindowState { width: 1280, height: 720, maximized: false };
+        db.set_setting_json("window_state", &window).unwrap();
+        assert_eq!(db.get_setting_json::<WindowState>("window_state").unwrap(), Some(window));
 
-    let mut total = 0u32;
-    let mut successful = 0u32;
-    let mut failed = 0u32;
-    let mut errors = Vec::new();
+        db.set_setting("window_state", "not json").unwrap();
+        assert!(db.get_setting_json::<WindowState>("window_state").is_err());
+    }
 
-    for game in games {
-        // Skip games that already have metadata if only_missing is true
-        // Consider a game as "having metadata" if it has cover art OR any text metadata
-        if only_missing {
-            let has_metadata = game.cover_art_path.is_some()
-                || game.description.is_some()
-                || game.developer.is_some()
-                || game.publisher.is_some()
-                || game.release_date.is_some()
-                || !game.genre.is_empty();
-
-            if has_metadata {
-                continue;
-            }
-        }
+    #[test]
+    fn test_settings_export_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("settings.json");
 
-        total += 1;
+        let source = Database::new(PathBuf::from(":memory:")).unwrap();
+        source.set_setting("launch_fullscreen", "true").unwrap();
+        source.set_setting("theme", "synthwave").unwrap();
+        write_settings_file(&source, &file).unwrap();
 
-        // Rate limiting - IGDB allows 4 requests/second, be conservative
-        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        let exported = std::fs::read_to_string(&file).unwrap();
+        assert!(!exported.contains("schema_version"));
 
-        match scrape_game_metadata(
-            game.id.clone(),
-            None,
-            app_handle.clone(),
-            state.clone(),
-        ).await {
-            Ok(result) => {
-                if result.success {
-                    successful += 1;
-                } else {
-                    failed += 1;
-                    if let Some(err) = result.error {
-                        errors.push(format!("{}: {}", game.title, err));
-                    }
-                }
-            }
-            Err(e) => {
-                failed += 1;
-                errors.push(format!("{}: {}", game.title, e));
-            }
-        }
-    }
+        // A hand-edited file must not be able to rewind the schema version
+        let mut settings: HashMap<String, String> = serde_json::from_str(&exported).unwrap();
+        settings.insert("schema_version".to_string(), "1".to_string());
+        std::fs::write(&file, serde_json::to_string(&settings).unwrap()).unwrap();
 
-    Ok(BatchScrapeResult {
-        total,
-        successful,
-        failed,
-        errors,
-    })
+        let target = Database::new(PathBuf::from(":memory:")).unwrap();
+        let version = target.get_setting("schema_version").unwrap();
+        assert_eq!(read_settings_file(&target, &file).unwrap(), 2);
+
+        assert_eq!(target.get_setting("launch_fullscreen").unwrap().as_deref(), Some("true"));
+        assert_eq!(target.get_setting("theme").unwrap().as_deref(), Some("synthwave"));
+        assert_eq!(target.get_setting("schema_version").unwrap(), version);
+    }
 }
@@ -1,9 +1,182 @@
-use rusqlite::{Connection, Result, params};
-use std::path::PathBuf;
+use rusqlite::{Connection, OptionalExtension, Result, params};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::models::*;
 
+/// A built-in platform: (id, display name, manufacturer, file extensions JSON, color)
+pub type DefaultPlatform = (&'static str, &'static str, &'static str, &'static str, &'static str);
+
+/// The platforms every library starts with
+pub fn default_platforms() -> Vec<DefaultPlatform> {
+    vec![
+        ("nes", "NES", "Nintendo", r#"[".nes", ".unf"]"#, "#e60012"),
+        ("snes", "SNES", "Nintendo", r#"[".sfc", ".smc"]"#, "#7b5aa6"),
+        ("n64", "Nintendo 64", "Nintendo", r#"[".n64", ".z64", ".v64"]"#, "#009e60"),
+        ("gamecube", "GameCube", "Nintendo", r#"[".iso", ".gcz", ".rvz"]"#, "#6a5acd"),
+        ("wii", "Wii", "Nintendo", r#"[".iso", ".wbfs", ".rvz", ".wad"]"#, "#00a0dc"),
+        ("wiiu", "Wii U", "Nintendo", r#"[".wux", ".wud", ".wua", ".rpx"]"#, "#009ac7"),
+        ("switch", "Nintendo Switch", "Nintendo", r#"[]"#, "#e60012"),
+        ("gb", "Game Boy", "Nintendo", r#"[".gb"]"#, "#8b956d"),
+        ("gbc", "Game Boy Color", "Nintendo", r#"[".gbc"]"#, "#6b5b95"),
+        ("gba", "Game Boy Advance", "Nintendo", r#"[".gba"]"#, "#5b5ea6"),
+        ("nds", "Nintendo DS", "Nintendo", r#"[".nds"]"#, "#c0c0c0"),
+        ("3ds", "Nintendo 3DS", "Nintendo", r#"[".3ds", ".cci", ".cia"]"#, "#ce1141"),
+        ("virtualboy", "Virtual Boy", "Nintendo", r#"[".vb", ".vboy"]"#, "#e60012"),
+        ("ps1", "PlayStation", "Sony", r#"[".cue", ".chd", ".iso", ".m3u"]"#, "#003087"),
+        ("ps2", "PlayStation 2", "Sony", r#"[".iso", ".chd", ".m3u"]"#, "#003087"),
+        ("ps3", "PlayStation 3", "Sony", r#"[]"#, "#003087"),
+        ("psp", "PlayStation Portable", "Sony", r#"[".iso", ".cso"]"#, "#003087"),
+        ("vita", "PlayStation Vita", "Sony", r#"[".vpk", ".zip"]"#, "#003087"),
+        ("genesis", "Sega Genesis", "Sega", r#"[".md", ".gen", ".bin"]"#, "#0060a8"),
+        ("sega32x", "Sega 32X", "Sega", r#"[".32x", ".bin"]"#, "#0060a8"),
+        ("segacd", "Sega CD", "Sega", r#"[".cue", ".chd", ".iso", ".m3u"]"#, "#0060a8"),
+        ("saturn", "Sega Saturn", "Sega", r#"[".iso", ".cue", ".chd", ".m3u"]"#, "#0060a8"),
+        ("dreamcast", "Dreamcast", "Sega", r#"[".cue", ".cdi", ".chd"]"#, "#ff6600"),
+        ("mastersystem", "Master System", "Sega", r#"[".sms"]"#, "#0060a8"),
+        ("gamegear", "Game Gear", "Sega", r#"[".gg"]"#, "#0060a8"),
+        ("xbox", "Xbox", "Microsoft", r#"[".xiso"]"#, "#107c10"),
+        ("xbox360", "Xbox 360", "Microsoft", r#"[".iso", ".stfs"]"#, "#107c10"),
+        ("arcade", "Arcade", "Various", r#"[".zip"]"#, "#ff00ff"),
+        ("dos", "DOS", "PC", r#"[".exe", ".com"]"#, "#00ff00"),
+        ("scummvm", "ScummVM", "PC", r#"[]"#, "#8b4513"),
+        ("windows", "Windows", "PC", r#"[]"#, "#0078d4"),
+        ("atari2600", "Atari 2600", "Atari", r#"[".a26", ".bin"]"#, "#ff0000"),
+        ("atari5200", "Atari 5200", "Atari", r#"[".a52", ".bin"]"#, "#ff0000"),
+        ("atari7800", "Atari 7800", "Atari", r#"[".a78", ".bin"]"#, "#ff0000"),
+        ("atarijaguar", "Atari Jaguar", "Atari", r#"[".j64", ".jag", ".rom"]"#, "#ff0000"),
+        ("atarijaguarcd", "Atari Jaguar CD", "Atari", r#"[".cue", ".chd", ".m3u"]"#, "#ff0000"),
+        ("3do", "3DO", "Panasonic", r#"[".iso", ".chd", ".cue", ".m3u"]"#, "#d4af37"),
+        ("cdi", "CD-i", "Philips", r#"[".chd", ".cue", ".iso"]"#, "#00a651"),
+        ("neogeo", "Neo Geo", "SNK", r#"[".zip", ".neo"]"#, "#ffd700"),
+        ("pcengine", "TurboGrafx-16", "NEC", r#"[".pce"]"#, "#ff4500"),
+        ("pcenginecd", "TurboGrafx-CD", "NEC", r#"[".cue", ".chd", ".m3u"]"#, "#ff4500"),
+    ]
+}
+
+/// Schema migrations, applied in order to databases below each version. Each runs in its own
+/// transaction together with the schema_version bump, so a failed migration leaves no trace.
+/// Append new migrations at the end; never edit or renumber a released one.
+const MIGRATIONS: &[(i32, &str)] = &[
+    // Migration 1: Remove .bin from PS1 extensions (causes duplicates with .cue files)
+    (1, r#"UPDATE platforms SET file_extensions = '[".cue", ".chd", ".iso"]' WHERE id = 'ps1'"#),
+    // Migration 2: Ensure PS1 .bin is removed (re-run in case migration 1 had issues)
+    (2, r#"UPDATE platforms SET file_extensions = '[".cue", ".chd", ".iso"]' WHERE id = 'ps1'"#),
+    // Migration 3: Update Dreamcast extensions to prefer .cue over .gdi (avoid duplicates)
+    (3, r#"UPDATE platforms SET file_extensions = '[".cue", ".cdi", ".chd"]' WHERE id = 'dreamcast'"#),
+    // Migration 4: Add .stfs support for Xbox 360
+    (4, r#"UPDATE platforms SET file_extensions = '[".iso", ".stfs"]' WHERE id = 'xbox360'"#),
+    // Migration 5: Add .wad support for Wii (WiiWare/Virtual Console)
+    (5, r#"UPDATE platforms SET file_extensions = '[".iso", ".wbfs", ".rvz", ".wad"]' WHERE id = 'wii'"#),
+    // Migration 6: Remove .pkg from PS3 (too ambiguous - could be games, DLC, or updates)
+    // PS3 disc games are detected via PS3_DISC.SFB directory structure instead
+    (6, r#"UPDATE platforms SET file_extensions = '[]' WHERE id = 'ps3'"#),
+    // Migration 7: Remove .nsp/.xci from Switch (ambiguous - could be games, DLC, or updates)
+    (7, r#"UPDATE platforms SET file_extensions = '[]' WHERE id = 'switch'"#),
+    // Migration 8: Per-emulator stdout/stderr log capture
+    (8, "ALTER TABLE emulators ADD COLUMN capture_logs INTEGER DEFAULT 0"),
+    // Migration 9: Per-emulator fullscreen flag
+    (9, "ALTER TABLE emulators ADD COLUMN fullscreen_argument TEXT"),
+    // Migration 10: URI-based launching (Steam shortcuts, external launchers)
+    (10, "ALTER TABLE emulators ADD COLUMN launch_type TEXT DEFAULT 'executable'"),
+    // Migration 11: Wrapper command (Wine/Proton) for emulators
+    (11, "ALTER TABLE emulators ADD COLUMN wrapper_command TEXT"),
+    // Migration 12: Notes on play sessions
+    (12, "ALTER TABLE play_sessions ADD COLUMN note TEXT"),
+    // Migration 13: Smart (rule-based) collections
+    (
+        13,
+        "CREATE TABLE IF NOT EXISTS smart_collections (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            rule TEXT DEFAULT '{}',
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+    ),
+    // Migration 14: Track whether a game has been completed
+    (14, "ALTER TABLE games ADD COLUMN is_completed INTEGER DEFAULT 0"),
+    // Migration 15: ROM checksums for duplicate detection and lookups
+    (
+        15,
+        "ALTER TABLE games ADD COLUMN crc32 TEXT;
+        ALTER TABLE games ADD COLUMN md5 TEXT;
+        ALTER TABLE games ADD COLUMN sha1 TEXT;
+        CREATE INDEX IF NOT EXISTS idx_games_crc32 ON games(crc32);
+        CREATE INDEX IF NOT EXISTS idx_games_md5 ON games(md5);
+        CREATE INDEX IF NOT EXISTS idx_games_sha1 ON games(sha1);",
+    ),
+    // Migration 16: User-defined display order for emulators and platforms
+    (
+        16,
+        "ALTER TABLE emulators ADD COLUMN sort_order INTEGER DEFAULT 0;
+        ALTER TABLE platforms ADD COLUMN sort_order INTEGER DEFAULT 0;",
+    ),
+    // Migration 17: Personal achievement checklists
+    (
+        17,
+        "CREATE TABLE IF NOT EXISTS achievements (
+            id TEXT PRIMARY KEY,
+            game_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            unlocked INTEGER DEFAULT 0,
+            unlocked_at TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        )",
+    ),
+    // Migration 18: Title read from the ROM header during scans
+    (18, "ALTER TABLE games ADD COLUMN internal_title TEXT"),
+    // Migration 19: Titles without a leading article, for library ordering
+    (
+        19,
+        "ALTER TABLE games ADD COLUMN sort_title TEXT;
+        UPDATE games SET sort_title = COALESCE(NULLIF(CASE
+            WHEN title LIKE 'The %' THEN ltrim(substr(title, 5), char(32, 9, 10, 11, 12, 13))
+            WHEN title LIKE 'An %' THEN ltrim(substr(title, 4), char(32, 9, 10, 11, 12, 13))
+            WHEN title LIKE 'A %' THEN ltrim(substr(title, 3), char(32, 9, 10, 11, 12, 13))
+        END, ''), title);
+        CREATE INDEX IF NOT EXISTS idx_games_sort_title ON games(sort_title COLLATE NOCASE);",
+    ),
+    // Migration 20: Fields protected from re-scrapes because the user edited them
+    (20, "ALTER TABLE games ADD COLUMN manually_edited_fields TEXT DEFAULT '[]'"),
+    // Migration 21: Where a game's metadata came from, and when it was last scraped
+    (
+        21,
+        "ALTER TABLE games ADD COLUMN metadata_source TEXT;
+        ALTER TABLE games ADD COLUMN last_scraped_at TEXT;",
+    ),
+    // Migration 22: Pinned platforms, shown at the top of the sidebar
+    (22, "ALTER TABLE platforms ADD COLUMN is_favorite INTEGER DEFAULT 0"),
+    // Migration 23: Session length before idle time is subtracted
+    (23, "ALTER TABLE play_sessions ADD COLUMN gross_duration_seconds INTEGER"),
+    // Migration 24: Which monitor an emulator opens on, and how to tell it
+    (
+        24,
+        "ALTER TABLE emulators ADD COLUMN preferred_display INTEGER;
+        ALTER TABLE emulators ADD COLUMN display_argument TEXT;",
+    ),
+    // Migration 25: Flags for launching emulators at a chosen resolution
+    (25, "ALTER TABLE emulators ADD COLUMN resolution_argument_template TEXT"),
+    // Migration 26: Ordered playlists
+    (
+        26,
+        "CREATE TABLE IF NOT EXISTS playlists (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            game_ids TEXT DEFAULT '[]',
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )",
+    ),
+    // Migration 27: IGDB ID of the last scrape, for re-downloading artwork
+    (27, "ALTER TABLE games ADD COLUMN igdb_id INTEGER"),
+    // Migration 28: Detected byte order of N64 ROM dumps
+    (28, "ALTER TABLE games ADD COLUMN n64_byte_order TEXT"),
+];
+
 /// Database wrapper with thread-safe connection
 pub struct Database {
     conn: Mutex<Connection>,
@@ -13,6 +186,10 @@ impl Database {
     /// Create a new database connection
     pub fn new(db_path: PathBuf) -> Result<Self> {
         let conn = Connection::open(&db_path)?;
+        // Refuse before touching anything: an older build could corrupt a newer schema
+        check_schema_supported(stored_schema_version(&conn))?;
+        // SQLite leaves foreign keys off by default; play_sessions relies on ON DELETE CASCADE
+        conn.pragma_update(None, "foreign_keys", true)?;
         let db = Self {
             conn: Mutex::new(conn),
         };
@@ -22,110 +199,26 @@ impl Database {
         Ok(db)
     }
 
-    /// Run database migrations
-    fn run_migrations(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // Get current schema version
-        let version: i32 = conn
-            .query_row(
-                "SELECT COALESCE((SELECT CAST(value AS INTEGER) FROM settings WHERE key = 'schema_version'), 0)",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
-
-        // Migration 1: Remove .bin from PS1 extensions (causes duplicates with .cue files)
-        if version < 1 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[".cue", ".chd", ".iso"]' WHERE id = 'ps1'"#,
-                [],
-            )?;
-
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '1')",
-                [],
-            )?;
-        }
-
-        // Migration 2: Ensure PS1 .bin is removed (re-run in case migration 1 had issues)
-        if version < 2 {
-            // Unconditionally set PS1 extensions to exclude .bin
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[".cue", ".chd", ".iso"]' WHERE id = 'ps1'"#,
-                [],
-            )?;
-
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '2')",
-                [],
-            )?;
-        }
-
-        // Migration 3: Update Dreamcast extensions to prefer .cue over .gdi (avoid duplicates)
-        if version < 3 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[".cue", ".cdi", ".chd"]' WHERE id = 'dreamcast'"#,
-                [],
-            )?;
-
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '3')",
-                [],
-            )?;
-        }
-
-        // Migration 4: Add .stfs support for Xbox 360
-        if version < 4 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[".iso", ".stfs"]' WHERE id = 'xbox360'"#,
-                [],
-            )?;
-
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '4')",
-                [],
-            )?;
-        }
-
-        // Migration 5: Add .wad support for Wii (WiiWare/Virtual Console)
-        if version < 5 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[".iso", ".wbfs", ".rvz", ".wad"]' WHERE id = 'wii'"#,
-                [],
-            )?;
-
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '5')",
-                [],
-            )?;
-        }
-
-        // Migration 6: Remove .pkg from PS3 (too ambiguous - could be games, DLC, or updates)
-        // PS3 disc games are detected via PS3_DISC.SFB directory structure instead
-        if version < 6 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[]' WHERE id = 'ps3'"#,
-                [],
-            )?;
+    /// Version of the last migration applied to this database
+    pub fn schema_version(&self) -> i32 {
+        stored_schema_version(&self.conn.lock().unwrap())
+    }
 
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '6')",
-                [],
-            )?;
-        }
+    /// Run any migrations newer than the stored schema_version
+    fn run_migrations(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
 
-        // Migration 7: Remove .nsp/.xci from Switch (ambiguous - could be games, DLC, or updates)
-        if version < 7 {
-            conn.execute(
-                r#"UPDATE platforms SET file_extensions = '[]' WHERE id = 'switch'"#,
-                [],
-            )?;
+        let version = stored_schema_version(&conn);
+        check_schema_supported(version)?;
 
-            conn.execute(
-                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', '7')",
-                [],
+        for (target, sql) in MIGRATIONS.iter().filter(|(target, _)| *target > version) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(sql)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES ('schema_version', ?1)",
+                params![target.to_string()],
             )?;
+            tx.commit()?;
         }
 
         Ok(())
@@ -213,6 +306,8 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_games_favorite ON games(is_favorite);
             CREATE INDEX IF NOT EXISTS idx_games_last_played ON games(last_played);
             CREATE INDEX IF NOT EXISTS idx_play_sessions_game ON play_sessions(game_id);
+            CREATE INDEX IF NOT EXISTS idx_games_title ON games(title);
+            CREATE INDEX IF NOT EXISTS idx_games_title_nocase ON games(title COLLATE NOCASE);
             "#,
         )?;
 
@@ -223,49 +318,7 @@ impl Database {
     fn init_default_platforms(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
-        let platforms = vec![
-            ("nes", "NES", "Nintendo", r#"[".nes", ".unf"]"#, "#e60012"),
-            ("snes", "SNES", "Nintendo", r#"[".sfc", ".smc"]"#, "#7b5aa6"),
-            ("n64", "Nintendo 64", "Nintendo", r#"[".n64", ".z64", ".v64"]"#, "#009e60"),
-            ("gamecube", "GameCube", "Nintendo", r#"[".iso", ".gcz", ".rvz"]"#, "#6a5acd"),
-            ("wii", "Wii", "Nintendo", r#"[".iso", ".wbfs", ".rvz", ".wad"]"#, "#00a0dc"),
-            ("wiiu", "Wii U", "Nintendo", r#"[".wux", ".wud", ".wua", ".rpx"]"#, "#009ac7"),
-            ("switch", "Nintendo Switch", "Nintendo", r#"[]"#, "#e60012"),
-            ("gb", "Game Boy", "Nintendo", r#"[".gb"]"#, "#8b956d"),
-            ("gbc", "Game Boy Color", "Nintendo", r#"[".gbc"]"#, "#6b5b95"),
-            ("gba", "Game Boy Advance", "Nintendo", r#"[".gba"]"#, "#5b5ea6"),
-            ("nds", "Nintendo DS", "Nintendo", r#"[".nds"]"#, "#c0c0c0"),
-            ("3ds", "Nintendo 3DS", "Nintendo", r#"[".3ds", ".cci", ".cia"]"#, "#ce1141"),
-            ("virtualboy", "Virtual Boy", "Nintendo", r#"[".vb", ".vboy"]"#, "#e60012"),
-            ("ps1", "PlayStation", "Sony", r#"[".cue", ".chd", ".iso", ".m3u"]"#, "#003087"),
-            ("ps2", "PlayStation 2", "Sony", r#"[".iso", ".chd", ".m3u"]"#, "#003087"),
-            ("ps3", "PlayStation 3", "Sony", r#"[]"#, "#003087"),
-            ("psp", "PlayStation Portable", "Sony", r#"[".iso", ".cso"]"#, "#003087"),
-            ("vita", "PlayStation Vita", "Sony", r#"[".vpk", ".zip"]"#, "#003087"),
-            ("genesis", "Sega Genesis", "Sega", r#"[".md", ".gen", ".bin"]"#, "#0060a8"),
-            ("sega32x", "Sega 32X", "Sega", r#"[".32x", ".bin"]"#, "#0060a8"),
-            ("segacd", "Sega CD", "Sega", r#"[".cue", ".chd", ".iso", ".m3u"]"#, "#0060a8"),
-            ("saturn", "Sega Saturn", "Sega", r#"[".iso", ".cue", ".chd", ".m3u"]"#, "#0060a8"),
-            ("dreamcast", "Dreamcast", "Sega", r#"[".cue", ".cdi", ".chd"]"#, "#ff6600"),
-            ("mastersystem", "Master System", "Sega", r#"[".sms"]"#, "#0060a8"),
-            ("gamegear", "Game Gear", "Sega", r#"[".gg"]"#, "#0060a8"),
-            ("xbox", "Xbox", "Microsoft", r#"[".xiso"]"#, "#107c10"),
-            ("xbox360", "Xbox 360", "Microsoft", r#"[".iso", ".stfs"]"#, "#107c10"),
-            ("arcade", "Arcade", "Various", r#"[".zip"]"#, "#ff00ff"),
-            ("dos", "DOS", "PC", r#"[".exe", ".com"]"#, "#00ff00"),
-            ("scummvm", "ScummVM", "PC", r#"[]"#, "#8b4513"),
-            ("windows", "Windows", "PC", r#"[]"#, "#0078d4"),
-            ("atari2600", "Atari 2600", "Atari", r#"[".a26", ".bin"]"#, "#ff0000"),
-            ("atari5200", "Atari 5200", "Atari", r#"[".a52", ".bin"]"#, "#ff0000"),
-            ("atari7800", "Atari 7800", "Atari", r#"[".a78", ".bin"]"#, "#ff0000"),
-            ("atarijaguar", "Atari Jaguar", "Atari", r#"[".j64", ".jag", ".rom"]"#, "#ff0000"),
-            ("atarijaguarcd", "Atari Jaguar CD", "Atari", r#"[".cue", ".chd", ".m3u"]"#, "#ff0000"),
-            ("3do", "3DO", "Panasonic", r#"[".iso", ".chd", ".cue", ".m3u"]"#, "#d4af37"),
-            ("cdi", "CD-i", "Philips", r#"[".chd", ".cue", ".iso"]"#, "#00a651"),
-            ("neogeo", "Neo Geo", "SNK", r#"[".zip", ".neo"]"#, "#ffd700"),
-            ("pcengine", "TurboGrafx-16", "NEC", r#"[".pce"]"#, "#ff4500"),
-            ("pcenginecd", "TurboGrafx-CD", "NEC", r#"[".cue", ".chd", ".m3u"]"#, "#ff4500"),
-        ];
+        let platforms = default_platforms();
 
         for (id, name, manufacturer, extensions, color) in platforms {
             conn.execute(
@@ -312,35 +365,27 @@ impl Database {
     /// Get all games
     pub fn get_all_games(&self) -> Result<Vec<Game>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, title, rom_path, platform_id, cover_art_path, background_path,
-                    screenshots, description, release_date, genre, developer, publisher,
-                    total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
-                    collection_ids, created_at FROM games ORDER BY title"
-        )?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM games ORDER BY title", GAME_COLUMNS))?;
+        let games = stmt.query_map([], game_from_row)?.collect::<Result<Vec<_>>>()?;
 
-        let games = stmt.query_map([], |row| {
-            Ok(Game {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                rom_path: row.get(2)?,
-                platform_id: row.get(3)?,
-                cover_art_path: row.get(4)?,
-                background_path: row.get(5)?,
-                screenshots: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
-                description: row.get(7)?,
-                release_date: row.get(8)?,
-                genre: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-                developer: row.get(10)?,
-                publisher: row.get(11)?,
-                total_play_time_seconds: row.get(12)?,
-                last_played: row.get(13)?,
-                is_favorite: row.get::<_, i32>(14)? == 1,
-                preferred_emulator_id: row.get(15)?,
-                collection_ids: serde_json::from_str(&row.get::<_, String>(16)?).unwrap_or_default(),
-                created_at: row.get(17)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
+        Ok(games)
+    }
+
+    /// Get one page of games in the given order
+    pub fn get_games_paged(&self, sort: GameSort, offset: u32, limit: u32) -> Result<Vec<Game>> {
+        let order_by = match sort {
+            GameSort::Title => "title COLLATE NOCASE",
+            GameSort::SortTitle => "sort_title COLLATE NOCASE, title COLLATE NOCASE",
+            GameSort::LastPlayed => "last_played DESC, title COLLATE NOCASE",
+            GameSort::DateAdded => "created_at DESC, title COLLATE NOCASE",
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM games ORDER BY {} LIMIT ?1 OFFSET ?2",
+            GAME_COLUMNS, order_by
+        ))?;
+        let games = stmt.query_map(params![limit, offset], game_from_row)?.collect::<Result<Vec<_>>>()?;
 
         Ok(games)
     }
@@ -348,36 +393,12 @@ impl Database {
     /// Get a single game by ID
     pub fn get_game(&self, id: &str) -> Result<Option<Game>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, title, rom_path, platform_id, cover_art_path, background_path,
-                    screenshots, description, release_date, genre, developer, publisher,
-                    total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
-                    collection_ids, created_at FROM games WHERE id = ?1"
-        )?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM games WHERE id = ?1", GAME_COLUMNS))?;
 
         let mut rows = stmt.query(params![id])?;
 
         if let Some(row) = rows.next()? {
-            Ok(Some(Game {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                rom_path: row.get(2)?,
-                platform_id: row.get(3)?,
-                cover_art_path: row.get(4)?,
-                background_path: row.get(5)?,
-                screenshots: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
-                description: row.get(7)?,
-                release_date: row.get(8)?,
-                genre: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-                developer: row.get(10)?,
-                publisher: row.get(11)?,
-                total_play_time_seconds: row.get(12)?,
-                last_played: row.get(13)?,
-                is_favorite: row.get::<_, i32>(14)? == 1,
-                preferred_emulator_id: row.get(15)?,
-                collection_ids: serde_json::from_str(&row.get::<_, String>(16)?).unwrap_or_default(),
-                created_at: row.get(17)?,
-            }))
+            Ok(Some(game_from_row(row)?))
         } else {
             Ok(None)
         }
@@ -386,79 +407,83 @@ impl Database {
     /// Get a game by ROM path
     pub fn get_game_by_path(&self, rom_path: &str) -> Result<Option<Game>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, title, rom_path, platform_id, cover_art_path, background_path,
-                    screenshots, description, release_date, genre, developer, publisher,
-                    total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
-                    collection_ids, created_at FROM games WHERE rom_path = ?1"
-        )?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM games WHERE rom_path = ?1", GAME_COLUMNS))?;
 
         let mut rows = stmt.query(params![rom_path])?;
 
         if let Some(row) = rows.next()? {
-            Ok(Some(Game {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                rom_path: row.get(2)?,
-                platform_id: row.get(3)?,
-                cover_art_path: row.get(4)?,
-                background_path: row.get(5)?,
-                screenshots: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
-                description: row.get(7)?,
-                release_date: row.get(8)?,
-                genre: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-                developer: row.get(10)?,
-                publisher: row.get(11)?,
-                total_play_time_seconds: row.get(12)?,
-                last_played: row.get(13)?,
-                is_favorite: row.get::<_, i32>(14)? == 1,
-                preferred_emulator_id: row.get(15)?,
-                collection_ids: serde_json::from_str(&row.get::<_, String>(16)?).unwrap_or_default(),
-                created_at: row.get(17)?,
-            }))
+            Ok(Some(game_from_row(row)?))
         } else {
             Ok(None)
         }
     }
 
-    /// Add a new game
-    pub fn add_game(&self, game: &Game) -> Result<()> {
+    /// Get a game by a ROM checksum
+    pub fn get_game_by_hash(&self, algo: HashAlgorithm, hash: &str) -> Result<Option<Game>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM games WHERE {} = ?1",
+            GAME_COLUMNS,
+            algo.column()
+        ))?;
+
+        let mut rows = stmt.query(params![hash.to_lowercase()])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(game_from_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Store a ROM checksum for a game. Hashes are kept as lowercase hex.
+    pub fn set_game_hash(&self, id: &str, algo: HashAlgorithm, hash: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO games (id, title, rom_path, platform_id, cover_art_path, background_path,
-                               screenshots, description, release_date, genre, developer, publisher,
-                               total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
-                               collection_ids)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
-            params![
-                game.id,
-                game.title,
-                game.rom_path,
-                game.platform_id,
-                game.cover_art_path,
-                game.background_path,
-                serde_json::to_string(&game.screenshots).unwrap(),
-                game.description,
-                game.release_date,
-                serde_json::to_string(&game.genre).unwrap(),
-                game.developer,
-                game.publisher,
-                game.total_play_time_seconds,
-                game.last_played,
-                if game.is_favorite { 1 } else { 0 },
-                game.preferred_emulator_id,
-                serde_json::to_string(&game.collection_ids).unwrap(),
-            ],
+            &format!("UPDATE games SET {} = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", algo.column()),
+            params![hash.to_lowercase(), id],
         )?;
         Ok(())
     }
 
+    /// Add a new game
+    pub fn add_game(&self, game: &Game) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        insert_game(&conn, game, "INSERT")?;
+        Ok(())
+    }
+
+    /// Add several games in one transaction. Games whose ROM path is already in the library
+    /// (or earlier in the batch) are skipped; the result says which were inserted.
+    pub fn add_games_batch(&self, games: &[Game]) -> Result<Vec<bool>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let inserted = games.iter()
+            .map(|game| insert_game(&tx, game, "INSERT OR IGNORE").map(|rows| rows > 0))
+            .collect::<Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// Whether any game still uses `path` as its cover or background
+    pub fn is_artwork_in_use(&self, path: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE cover_art_path = ?1 OR background_path = ?1)",
+            params![path],
+            |row| row.get(0),
+        )
+    }
+
     /// Update a game
     pub fn update_game(&self, id: &str, updates: &UpdateGameInput) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
         if let Some(title) = &updates.title {
-            conn.execute("UPDATE games SET title = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![title, id])?;
+            conn.execute(
+                "UPDATE games SET title = ?1, sort_title = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+                params![title, sort_title(title), id],
+            )?;
         }
         if let Some(platform_id) = &updates.platform_id {
             conn.execute("UPDATE games SET platform_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![platform_id, id])?;
@@ -495,27 +520,134 @@ impl Database {
         if let Some(preferred_emulator_id) = &updates.preferred_emulator_id {
             conn.execute("UPDATE games SET preferred_emulator_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![preferred_emulator_id, id])?;
         }
+        if let Some(is_completed) = updates.is_completed {
+            conn.execute("UPDATE games SET is_completed = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![if is_completed { 1 } else { 0 }, id])?;
+        }
+        if let Some(collection_ids) = &updates.collection_ids {
+            let collection_ids_json = serde_json::to_string(collection_ids).unwrap();
+            conn.execute("UPDATE games SET collection_ids = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![collection_ids_json, id])?;
+        }
+        if let Some(last_played) = &updates.last_played {
+            conn.execute("UPDATE games SET last_played = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![last_played, id])?;
+        }
 
         Ok(())
     }
 
-    /// Delete a game
+    /// Delete a game, dropping it from any playlists
     pub fn delete_game(&self, id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM games WHERE id = ?1", params![id])?;
-        Ok(())
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+        replace_playlist_games(&tx, &[id], None)?;
+        tx.commit()
     }
 
-    /// Delete multiple games in a single transaction
+    /// Delete multiple games in a single transaction, dropping them from any playlists
     pub fn delete_games_batch(&self, ids: &[String]) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
         let mut deleted = 0;
         for id in ids {
-            deleted += conn.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+            deleted += tx.execute("DELETE FROM games WHERE id = ?1", params![id])?;
         }
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        replace_playlist_games(&tx, &ids, None)?;
+        tx.commit()?;
         Ok(deleted)
     }
 
+    /// Reset the given metadata fields on each game in one transaction, returning games updated
+    pub fn clear_game_fields(&self, ids: &[String], fields: &[ScrapeField]) -> Result<usize> {
+        if fields.is_empty() {
+            return Ok(0);
+        }
+
+        let assignments: Vec<String> = fields.iter()
+            .map(|field| format!("{} = {}", field.column(), field.cleared_value()))
+            .collect();
+        let sql = format!(
+            "UPDATE games SET {}, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            assignments.join(", ")
+        );
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut updated = 0;
+        for id in ids {
+            updated += tx.execute(&sql, params![id])?;
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Fold the games in `merge_ids` into `keep_id` in one transaction: play sessions and
+    /// achievements move to the kept game, play time is summed, collection memberships are
+    /// unioned, playlists point at the kept game, artwork the kept game lacks is taken over,
+    /// and the merged rows are deleted
+    pub fn merge_games(&self, keep_id: &str, merge_ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        merge_games_into(&tx, keep_id, merge_ids)?;
+        tx.commit()
+    }
+
+    /// Add the `new_discs` of a multi-disc game and fold the game into `keep_id`, one of
+    /// its discs, in one transaction so a failure leaves the library as it was
+    pub fn split_into_discs(&self, game_id: &str, new_discs: &[Game], keep_id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for disc in new_discs {
+            insert_game(&tx, disc, "INSERT")?;
+        }
+        merge_games_into(&tx, keep_id, &[game_id.to_string()])?;
+        tx.commit()
+    }
+
+    /// Record a successful scrape's provider, stamped with the current time
+    pub fn set_scrape_info(&self, id: &str, source: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE games SET metadata_source = ?1, last_scraped_at = ?2 WHERE id = ?3",
+            params![source, chrono::Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Remember which IGDB game a game was scraped from
+    pub fn set_igdb_id(&self, id: &str, igdb_id: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE games SET igdb_id = ?1 WHERE id = ?2", params![igdb_id, id])?;
+        Ok(())
+    }
+
+    /// Add fields to the set a game's user has edited by hand
+    pub fn mark_fields_edited(&self, id: &str, fields: &[&str]) -> Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let json: Option<String> = conn.query_row(
+            "SELECT manually_edited_fields FROM games WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ).optional()?.flatten();
+
+        let mut edited: Vec<String> = json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default();
+        for field in fields {
+            if !edited.iter().any(|f| f == field) {
+                edited.push(field.to_string());
+            }
+        }
+
+        conn.execute(
+            "UPDATE games SET manually_edited_fields = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&edited).unwrap(), id],
+        )?;
+        Ok(())
+    }
+
     /// Update game play time
     pub fn update_game_play_time(&self, id: &str, additional_seconds: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -529,6 +661,19 @@ impl Database {
         Ok(())
     }
 
+    /// Add play time that happened at a known time, moving last_played forward only if it is later
+    pub fn add_past_play_time(&self, id: &str, additional_seconds: i64, played_at: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE games SET total_play_time_seconds = total_play_time_seconds + ?1,
+                             last_played = CASE WHEN last_played IS NULL OR last_played < ?2 THEN ?2 ELSE last_played END,
+                             updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?3",
+            params![additional_seconds, played_at, id],
+        )?;
+        Ok(())
+    }
+
     /// Toggle game favorite status
     pub fn toggle_favorite(&self, id: &str) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
@@ -552,7 +697,7 @@ impl Database {
     pub fn get_all_emulators(&self) -> Result<Vec<Emulator>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, name, executable_path, launch_arguments, supported_platform_ids FROM emulators ORDER BY name"
+            "SELECT id, name, executable_path, launch_arguments, supported_platform_ids, capture_logs, fullscreen_argument, launch_type, wrapper_command, preferred_display, display_argument, resolution_argument_template FROM emulators ORDER BY sort_order, name"
         )?;
 
         let emulators = stmt.query_map([], |row| {
@@ -562,6 +707,13 @@ impl Database {
                 executable_path: row.get(2)?,
                 launch_arguments: row.get(3)?,
                 supported_platform_ids: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                capture_logs: row.get::<_, i32>(5)? == 1,
+                fullscreen_argument: row.get(6)?,
+                launch_type: LaunchType::parse(&row.get::<_, String>(7)?),
+                wrapper_command: row.get(8)?,
+                preferred_display: row.get(9)?,
+                display_argument: row.get(10)?,
+                resolution_argument_template: row.get(11)?,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
@@ -572,7 +724,7 @@ impl Database {
     pub fn get_emulator(&self, id: &str) -> Result<Option<Emulator>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, name, executable_path, launch_arguments, supported_platform_ids FROM emulators WHERE id = ?1"
+            "SELECT id, name, executable_path, launch_arguments, supported_platform_ids, capture_logs, fullscreen_argument, launch_type, wrapper_command, preferred_display, display_argument, resolution_argument_template FROM emulators WHERE id = ?1"
         )?;
 
         let mut rows = stmt.query(params![id])?;
@@ -584,29 +736,49 @@ impl Database {
                 executable_path: row.get(2)?,
                 launch_arguments: row.get(3)?,
                 supported_platform_ids: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                capture_logs: row.get::<_, i32>(5)? == 1,
+                fullscreen_argument: row.get(6)?,
+                launch_type: LaunchType::parse(&row.get::<_, String>(7)?),
+                wrapper_command: row.get(8)?,
+                preferred_display: row.get(9)?,
+                display_argument: row.get(10)?,
+                resolution_argument_template: row.get(11)?,
             }))
         } else {
             Ok(None)
         }
     }
 
-    /// Add a new emulator
+    /// Add a new emulator, listed after the existing ones
     pub fn add_emulator(&self, emulator: &Emulator) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO emulators (id, name, executable_path, launch_arguments, supported_platform_ids)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO emulators (id, name, executable_path, launch_arguments, supported_platform_ids, capture_logs, fullscreen_argument, launch_type, wrapper_command, preferred_display, display_argument, resolution_argument_template, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM emulators))",
             params![
                 emulator.id,
                 emulator.name,
                 emulator.executable_path,
                 emulator.launch_arguments,
                 serde_json::to_string(&emulator.supported_platform_ids).unwrap(),
+                if emulator.capture_logs { 1 } else { 0 },
+                emulator.fullscreen_argument,
+                emulator.launch_type.as_str(),
+                emulator.wrapper_command,
+                emulator.preferred_display,
+                emulator.display_argument,
+                emulator.resolution_argument_template,
             ],
         )?;
         Ok(())
     }
 
+    /// Set the display order of emulators to the order of `ids`.
+    /// Emulators not listed are placed after the listed ones.
+    pub fn reorder_emulators(&self, ids: &[String]) -> Result<()> {
+        reorder_rows(&mut self.conn.lock().unwrap(), "emulators", ids)
+    }
+
     /// Update an emulator
     pub fn update_emulator(&self, id: &str, updates: &UpdateEmulatorInput) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -624,6 +796,27 @@ impl Database {
             let json = serde_json::to_string(supported_platform_ids).unwrap();
             conn.execute("UPDATE emulators SET supported_platform_ids = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![json, id])?;
         }
+        if let Some(capture_logs) = updates.capture_logs {
+            conn.execute("UPDATE emulators SET capture_logs = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![if capture_logs { 1 } else { 0 }, id])?;
+        }
+        if let Some(fullscreen_argument) = &updates.fullscreen_argument {
+            conn.execute("UPDATE emulators SET fullscreen_argument = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![fullscreen_argument, id])?;
+        }
+        if let Some(launch_type) = updates.launch_type {
+            conn.execute("UPDATE emulators SET launch_type = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![launch_type.as_str(), id])?;
+        }
+        if let Some(wrapper_command) = &updates.wrapper_command {
+            conn.execute("UPDATE emulators SET wrapper_command = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![wrapper_command, id])?;
+        }
+        if let Some(preferred_display) = updates.preferred_display {
+            conn.execute("UPDATE emulators SET preferred_display = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![preferred_display, id])?;
+        }
+        if let Some(display_argument) = &updates.display_argument {
+            conn.execute("UPDATE emulators SET display_argument = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![display_argument, id])?;
+        }
+        if let Some(template) = &updates.resolution_argument_template {
+            conn.execute("UPDATE emulators SET resolution_argument_template = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![template, id])?;
+        }
 
         Ok(())
     }
@@ -641,8 +834,8 @@ impl Database {
     pub fn get_all_platforms(&self) -> Result<Vec<Platform>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, display_name, manufacturer, file_extensions, icon_path, default_emulator_id, color
-             FROM platforms ORDER BY manufacturer, display_name"
+            "SELECT id, display_name, manufacturer, file_extensions, icon_path, default_emulator_id, color, is_favorite
+             FROM platforms ORDER BY is_favorite DESC, sort_order, manufacturer, display_name"
         )?;
 
         let platforms = stmt.query_map([], |row| {
@@ -654,6 +847,7 @@ impl Database {
                 icon_path: row.get(4)?,
                 default_emulator_id: row.get(5)?,
                 color: row.get(6)?,
+                is_favorite: row.get::<_, i32>(7)? == 1,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
@@ -664,7 +858,7 @@ impl Database {
     pub fn get_platform(&self, id: &str) -> Result<Option<Platform>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, display_name, manufacturer, file_extensions, icon_path, default_emulator_id, color
+            "SELECT id, display_name, manufacturer, file_extensions, icon_path, default_emulator_id, color, is_favorite
              FROM platforms WHERE id = ?1"
         )?;
 
@@ -679,12 +873,145 @@ impl Database {
                 icon_path: row.get(4)?,
                 default_emulator_id: row.get(5)?,
                 color: row.get(6)?,
+                is_favorite: row.get::<_, i32>(7)? == 1,
             }))
         } else {
             Ok(None)
         }
     }
 
+    /// Add a new platform, listed after the existing ones
+    pub fn add_platform(&self, platform: &Platform) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO platforms (id, display_name, manufacturer, file_extensions, icon_path, default_emulator_id, color, is_favorite, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM platforms))",
+            params![
+                platform.id,
+                platform.display_name,
+                platform.manufacturer,
+                serde_json::to_string(&platform.file_extensions).unwrap(),
+                platform.icon_path,
+                platform.default_emulator_id,
+                platform.color,
+                if platform.is_favorite { 1 } else { 0 },
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Set the display order of platforms to the order of `ids`.
+    /// Platforms not listed are placed after the listed ones.
+    pub fn reorder_platforms(&self, ids: &[String]) -> Result<()> {
+        reorder_rows(&mut self.conn.lock().unwrap(), "platforms", ids)
+    }
+
+    /// Update a platform
+    pub fn update_platform(&self, id: &str, updates: &UpdatePlatformInput) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        if let Some(display_name) = &updates.display_name {
+            conn.execute("UPDATE platforms SET display_name = ?1 WHERE id = ?2", params![display_name, id])?;
+        }
+        if let Some(manufacturer) = &updates.manufacturer {
+            conn.execute("UPDATE platforms SET manufacturer = ?1 WHERE id = ?2", params![manufacturer, id])?;
+        }
+        if let Some(file_extensions) = &updates.file_extensions {
+            let json = serde_json::to_string(file_extensions).unwrap();
+            conn.execute("UPDATE platforms SET file_extensions = ?1 WHERE id = ?2", params![json, id])?;
+        }
+        if let Some(color) = &updates.color {
+            conn.execute("UPDATE platforms SET color = ?1 WHERE id = ?2", params![color, id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Restore a built-in platform's default extensions and color. Returns false for
+    /// platforms that aren't built in.
+    pub fn reset_platform_defaults(&self, id: &str) -> Result<bool> {
+        let Some((_, _, _, extensions, color)) = default_platforms().into_iter().find(|p| p.0 == id) else {
+            return Ok(false);
+        };
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE platforms SET file_extensions = ?1, color = ?2 WHERE id = ?3",
+            params![extensions, color, id],
+        )?;
+        Ok(true)
+    }
+
+    /// Set a platform's icon path
+    pub fn set_platform_icon(&self, id: &str, icon_path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE platforms SET icon_path = ?1 WHERE id = ?2",
+            params![icon_path, id],
+        )?;
+        Ok(())
+    }
+
+    /// Toggle whether a platform is pinned, returning the new state
+    pub fn toggle_platform_favorite(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE platforms SET is_favorite = NOT is_favorite WHERE id = ?1", params![id])?;
+        let is_favorite: i32 = conn.query_row("SELECT is_favorite FROM platforms WHERE id = ?1", params![id], |row| row.get(0))?;
+        Ok(is_favorite == 1)
+    }
+
+    /// Set a platform's theme color
+    pub fn set_platform_color(&self, id: &str, color: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE platforms SET color = ?1 WHERE id = ?2", params![color, id])?;
+        Ok(())
+    }
+
+    /// Count the games assigned to a platform
+    pub fn count_games_for_platform(&self, platform_id: &str) -> Result<u32> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*) FROM games WHERE platform_id = ?1",
+            params![platform_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Count games per platform in a single query, optionally including empty platforms
+    pub fn get_platform_game_counts(&self, include_empty: bool) -> Result<Vec<(String, u32)>> {
+        let conn = self.conn.lock().unwrap();
+        let sql = if include_empty {
+            "SELECT p.id, COUNT(g.id) FROM platforms p
+             LEFT JOIN games g ON g.platform_id = p.id
+             GROUP BY p.id ORDER BY p.id"
+        } else {
+            "SELECT platform_id, COUNT(*) FROM games GROUP BY platform_id ORDER BY platform_id"
+        };
+
+        let mut stmt = conn.prepare(sql)?;
+        let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(counts)
+    }
+
+    /// Delete a platform, first moving its games to `reassign_to` if given, in one
+    /// transaction. Returns how many games were moved.
+    pub fn delete_platform(&self, id: &str, reassign_to: Option<&str>) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let moved = match reassign_to {
+            Some(target) => tx.execute(
+                "UPDATE games SET platform_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE platform_id = ?2",
+                params![target, id],
+            )?,
+            None => 0,
+        };
+        tx.execute("DELETE FROM platforms WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(moved)
+    }
+
     /// Set default emulator for a platform
     pub fn set_platform_default_emulator(&self, platform_id: &str, emulator_id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -710,12 +1037,37 @@ impl Database {
                 name: row.get(1)?,
                 game_ids: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
                 cover_game_id: row.get(3)?,
+                cover_art_path: None,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
         Ok(collections)
     }
 
+    /// Get a single collection by ID
+    pub fn get_collection(&self, id: &str) -> Result<Option<Collection>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, game_ids, cover_game_id FROM collections WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query_map(params![id], |row| {
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                game_ids: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                cover_game_id: row.get(3)?,
+                cover_art_path: None,
+            })
+        })?;
+
+        match rows.next() {
+            Some(Ok(collection)) => Ok(Some(collection)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+
     /// Add a new collection
     pub fn add_collection(&self, collection: &Collection) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -756,54 +1108,579 @@ impl Database {
         Ok(())
     }
 
-    // ==================== PLAY SESSIONS ====================
+    // ==================== SMART COLLECTIONS ====================
 
-    /// Create a new play session
-    pub fn create_play_session(&self, session: &PlaySession) -> Result<()> {
+    /// Get all smart collections
+    pub fn get_all_smart_collections(&self) -> Result<Vec<SmartCollection>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO play_sessions (id, game_id, start_time, end_time, duration_seconds) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
-                session.id,
-                session.game_id,
-                session.start_time,
-                session.end_time,
-                session.duration_seconds,
-            ],
+        let mut stmt = conn.prepare(
+            "SELECT id, name, rule FROM smart_collections ORDER BY name"
         )?;
-        Ok(())
-    }
 
-    /// End a play session
-    pub fn end_play_session(&self, session_id: &str, end_time: &str, duration_seconds: i64) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE play_sessions SET end_time = ?1, duration_seconds = ?2 WHERE id = ?3",
-            params![end_time, duration_seconds, session_id],
-        )?;
-        Ok(())
+        let collections = stmt.query_map([], |row| {
+            Ok(SmartCollection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                rule: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(collections)
     }
 
-    /// Get play sessions for a game
-    pub fn get_play_sessions(&self, game_id: &str) -> Result<Vec<PlaySession>> {
+    /// Get a single smart collection by ID
+    pub fn get_smart_collection(&self, id: &str) -> Result<Option<SmartCollection>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, game_id, start_time, end_time, duration_seconds FROM play_sessions WHERE game_id = ?1 ORDER BY start_time DESC"
+            "SELECT id, name, rule FROM smart_collections WHERE id = ?1"
         )?;
 
-        let sessions = stmt.query_map(params![game_id], |row| {
+        let mut rows = stmt.query_map(params![id], |row| {
+            Ok(SmartCollection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                rule: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+            })
+        })?;
+
+        match rows.next() {
+            Some(Ok(collection)) => Ok(Some(collection)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    /// Add a new smart collection
+    pub fn add_smart_collection(&self, collection: &SmartCollection) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO smart_collections (id, name, rule) VALUES (?1, ?2, ?3)",
+            params![
+                collection.id,
+                collection.name,
+                serde_json::to_string(&collection.rule).unwrap(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Update a smart collection
+    pub fn update_smart_collection(&self, id: &str, updates: &UpdateSmartCollectionInput) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        if let Some(name) = &updates.name {
+            conn.execute("UPDATE smart_collections SET name = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![name, id])?;
+        }
+        if let Some(rule) = &updates.rule {
+            let json = serde_json::to_string(rule).unwrap();
+            conn.execute("UPDATE smart_collections SET rule = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![json, id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete a smart collection
+    pub fn delete_smart_collection(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM smart_collections WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // ==================== PLAYLISTS ====================
+
+    /// Get all playlists
+    pub fn get_all_playlists(&self) -> Result<Vec<Playlist>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, game_ids FROM playlists ORDER BY name")?;
+
+        let playlists = stmt.query_map([], |row| {
+            Ok(Playlist {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                game_ids: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(playlists)
+    }
+
+    /// Get a single playlist by ID
+    pub fn get_playlist(&self, id: &str) -> Result<Option<Playlist>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT id, name, game_ids FROM playlists WHERE id = ?1", params![id], |row| {
+            Ok(Playlist {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                game_ids: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+            })
+        }).optional()
+    }
+
+    /// Add a new playlist
+    pub fn add_playlist(&self, playlist: &Playlist) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO playlists (id, name, game_ids) VALUES (?1, ?2, ?3)",
+            params![playlist.id, playlist.name, serde_json::to_string(&playlist.game_ids).unwrap()],
+        )?;
+        Ok(())
+    }
+
+    /// Update a playlist's name or replace its games
+    pub fn update_playlist(&self, id: &str, updates: &UpdatePlaylistInput) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        if let Some(name) = &updates.name {
+            conn.execute("UPDATE playlists SET name = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![name, id])?;
+        }
+        if let Some(game_ids) = &updates.game_ids {
+            let json = serde_json::to_string(game_ids).unwrap();
+            conn.execute("UPDATE playlists SET game_ids = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", params![json, id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete a playlist
+    pub fn delete_playlist(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM playlists WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // ==================== ACHIEVEMENTS ====================
+
+    /// Get a game's personal achievements in the order they were added
+    pub fn get_achievements(&self, game_id: &str) -> Result<Vec<CustomAchievement>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, game_id, title, description, unlocked, unlocked_at FROM achievements
+             WHERE game_id = ?1 ORDER BY created_at, rowid"
+        )?;
+
+        let achievements = stmt.query_map(params![game_id], |row| {
+            Ok(CustomAchievement {
+                id: row.get(0)?,
+                game_id: row.get(1)?,
+                title: row.get(2)?,
+                description: row.get(3)?,
+                unlocked: row.get::<_, i32>(4)? == 1,
+                unlocked_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(achievements)
+    }
+
+    /// Add a personal achievement
+    pub fn add_achievement(&self, achievement: &CustomAchievement) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO achievements (id, game_id, title, description, unlocked, unlocked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                achievement.id,
+                achievement.game_id,
+                achievement.title,
+                achievement.description,
+                if achievement.unlocked { 1 } else { 0 },
+                achievement.unlocked_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Flip an achievement between locked and unlocked, recording when it was unlocked.
+    /// Returns the new state, or `None` if the achievement doesn't exist.
+    pub fn toggle_achievement(&self, id: &str, now: &str) -> Result<Option<bool>> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE achievements SET
+                unlocked = CASE WHEN unlocked = 1 THEN 0 ELSE 1 END,
+                unlocked_at = CASE WHEN unlocked = 1 THEN NULL ELSE ?1 END
+             WHERE id = ?2",
+            params![now, id],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+
+        let unlocked: i32 = conn.query_row("SELECT unlocked FROM achievements WHERE id = ?1", params![id], |row| row.get(0))?;
+        Ok(Some(unlocked == 1))
+    }
+
+    // ==================== PLAY SESSIONS ====================
+
+    /// Create a new play session
+    pub fn create_play_session(&self, session: &PlaySession) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO play_sessions (id, game_id, start_time, end_time, duration_seconds, note, gross_duration_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                session.id,
+                session.game_id,
+                session.start_time,
+                session.end_time,
+                session.duration_seconds,
+                session.note,
+                session.gross_duration_seconds,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Add imported play sessions in one transaction, skipping any that start when one of
+    /// the game's sessions already does, then refresh the affected games' play time and
+    /// last played. Returns how many sessions were added.
+    pub fn import_play_sessions(&self, sessions: &[PlaySession]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut added = 0;
+        let mut affected: Vec<&str> = Vec::new();
+        for session in sessions {
+            let inserted = tx.execute(
+                "INSERT INTO play_sessions (id, game_id, start_time, end_time, duration_seconds, note, gross_duration_seconds)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7
+                 WHERE NOT EXISTS (
+                     SELECT 1 FROM play_sessions WHERE game_id = ?2 AND datetime(start_time) = datetime(?3)
+                 )",
+                params![
+                    session.id,
+                    session.game_id,
+                    session.start_time,
+                    session.end_time,
+                    session.duration_seconds,
+                    session.note,
+                    session.gross_duration_seconds,
+                ],
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+            added += 1;
+
+            // datetime() gives CURRENT_TIMESTAMP's format, so last_played values compare correctly
+            tx.execute(
+                "UPDATE games SET last_played = CASE WHEN last_played IS NULL OR last_played < datetime(?1)
+                                                     THEN datetime(?1) ELSE last_played END
+                 WHERE id = ?2",
+                params![session.end_time.as_deref().unwrap_or(&session.start_time), session.game_id],
+            )?;
+            if !affected.contains(&session.game_id.as_str()) {
+                affected.push(&session.game_id);
+            }
+        }
+
+        for game_id in &affected {
+            tx.execute(
+                "UPDATE games SET total_play_time_seconds =
+                    (SELECT COALESCE(SUM(duration_seconds), 0) FROM play_sessions WHERE game_id = ?1),
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1",
+                params![game_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// End a play session, optionally attaching a note. `duration_seconds` is the time played and
+    /// `gross_duration_seconds` the wall-clock length, which differ when idle time was subtracted.
+    pub fn end_play_session(
+        &self,
+        session_id: &str,
+        end_time: &str,
+        duration_seconds: i64,
+        gross_duration_seconds: i64,
+        note: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE play_sessions SET end_time = ?1, duration_seconds = ?2, gross_duration_seconds = ?3, note = COALESCE(?4, note)
+             WHERE id = ?5",
+            params![end_time, duration_seconds, gross_duration_seconds, note, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get play sessions for a game
+    pub fn get_play_sessions(&self, game_id: &str) -> Result<Vec<PlaySession>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, game_id, start_time, end_time, duration_seconds, note, gross_duration_seconds FROM play_sessions WHERE game_id = ?1 ORDER BY start_time DESC"
+        )?;
+
+        let sessions = stmt.query_map(params![game_id], |row| {
             Ok(PlaySession {
                 id: row.get(0)?,
                 game_id: row.get(1)?,
                 start_time: row.get(2)?,
                 end_time: row.get(3)?,
                 duration_seconds: row.get(4)?,
+                note: row.get(5)?,
+                gross_duration_seconds: row.get(6)?,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
         Ok(sessions)
     }
 
+    /// Get the IDs of every game with at least one recorded play session
+    pub fn get_played_game_ids(&self) -> Result<std::collections::HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT game_id FROM play_sessions")?;
+        let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// Play sessions paired with their game's title, oldest first: one game's, or every game's when `game_id` is None
+    pub fn get_play_sessions_with_titles(&self, game_id: Option<&str>) -> Result<Vec<(String, PlaySession)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT g.title, s.id, s.game_id, s.start_time, s.end_time, s.duration_seconds, s.note, s.gross_duration_seconds
+             FROM play_sessions s JOIN games g ON g.id = s.game_id
+             WHERE ?1 IS NULL OR s.game_id = ?1
+             ORDER BY s.start_time"
+        )?;
+
+        let sessions = stmt.query_map(params![game_id], |row| {
+            Ok((row.get(0)?, PlaySession {
+                id: row.get(1)?,
+                game_id: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                note: row.get(6)?,
+                gross_duration_seconds: row.get(7)?,
+            }))
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(sessions)
+    }
+
+    /// Get a single play session by ID
+    pub fn get_play_session(&self, session_id: &str) -> Result<Option<PlaySession>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, game_id, start_time, end_time, duration_seconds, note, gross_duration_seconds FROM play_sessions WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query_map(params![session_id], |row| {
+            Ok(PlaySession {
+                id: row.get(0)?,
+                game_id: row.get(1)?,
+                start_time: row.get(2)?,
+                end_time: row.get(3)?,
+                duration_seconds: row.get(4)?,
+                note: row.get(5)?,
+                gross_duration_seconds: row.get(6)?,
+            })
+        })?;
+
+        match rows.next() {
+            Some(Ok(session)) => Ok(Some(session)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    /// Delete a play session
+    pub fn delete_play_session(&self, session_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM play_sessions WHERE id = ?1", params![session_id])?;
+        Ok(())
+    }
+
+    /// Delete the play sessions of one game, or of every game when `game_id` is None, and reset
+    /// the affected games' play time and last played date. Returns the number of sessions deleted.
+    pub fn clear_play_history(&self, game_id: Option<&str>) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let deleted = match game_id {
+            Some(id) => {
+                let deleted = tx.execute("DELETE FROM play_sessions WHERE game_id = ?1", params![id])?;
+                tx.execute(
+                    "UPDATE games SET total_play_time_seconds = 0, last_played = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+                    params![id],
+                )?;
+                deleted
+            }
+            None => {
+                let deleted = tx.execute("DELETE FROM play_sessions", [])?;
+                tx.execute(
+                    "UPDATE games SET total_play_time_seconds = 0, last_played = NULL, updated_at = CURRENT_TIMESTAMP",
+                    [],
+                )?;
+                deleted
+            }
+        };
+
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Change the recorded duration of a play session
+    pub fn update_play_session_duration(&self, session_id: &str, duration_seconds: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE play_sessions SET duration_seconds = ?1 WHERE id = ?2",
+            params![duration_seconds, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Recompute a game's total play time from the sum of its sessions
+    pub fn recompute_game_play_time(&self, game_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE games SET total_play_time_seconds =
+                (SELECT COALESCE(SUM(duration_seconds), 0) FROM play_sessions WHERE game_id = ?1),
+                updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![game_id],
+        )?;
+        Ok(())
+    }
+
+    /// Session time per game for sessions started at or after `since` (RFC 3339),
+    /// most played first
+    pub fn get_play_time_since(&self, since: &str, limit: u32) -> Result<Vec<(String, i64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT game_id, SUM(duration_seconds) AS seconds FROM play_sessions
+             WHERE datetime(start_time) >= datetime(?1) AND duration_seconds > 0
+             GROUP BY game_id ORDER BY seconds DESC LIMIT ?2"
+        )?;
+
+        let totals = stmt.query_map(params![since, limit], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(totals)
+    }
+
+    /// Start times of every play session
+    pub fn get_play_session_start_times(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT start_time FROM play_sessions")?;
+        let times = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+        Ok(times)
+    }
+
+    /// Total session time per week or month (UTC), oldest first. `from`/`to` are inclusive
+    /// dates (YYYY-MM-DD) limiting which sessions are counted.
+    pub fn get_playtime_report(&self, period: ReportPeriod, from: Option<&str>, to: Option<&str>) -> Result<Vec<(String, i64)>> {
+        let bucket = match period {
+            // 'weekday 0' moves forward to Sunday; six days back is that week's Monday
+            ReportPeriod::Week => "date(start_time, 'weekday 0', '-6 days')",
+            ReportPeriod::Month => "strftime('%Y-%m', start_time)",
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {bucket} AS bucket, SUM(duration_seconds) FROM play_sessions
+             WHERE duration_seconds > 0
+               AND (?1 IS NULL OR date(start_time) >= date(?1))
+               AND (?2 IS NULL OR date(start_time) <= date(?2))
+             GROUP BY bucket ORDER BY bucket"
+        ))?;
+
+        let report = stmt.query_map(params![from, to], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(report)
+    }
+
+    // ==================== BACKUP ====================
+
+    /// Write a consistent copy of the whole database to `dest`
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.backup(rusqlite::DatabaseName::Main, dest, None)
+    }
+
+    /// Rebuild the database file to reclaim space left by deletes and refresh query planner
+    /// statistics. Returns the file size before and after, in bytes.
+    pub fn optimize(&self) -> Result<OptimizeResult> {
+        let conn = self.conn.lock().unwrap();
+        let file_size = |conn: &Connection| {
+            conn.path()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|m| m.len())
+                .unwrap_or(0)
+        };
+
+        let size_before = file_size(&conn);
+        conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+
+        Ok(OptimizeResult {
+            size_before,
+            size_after: file_size(&conn),
+        })
+    }
+
+    /// Replace the whole database with the copy at `src`, then bring it up to date the same way
+    /// opening it would
+    pub fn restore_from(&self, src: &Path) -> Result<()> {
+        {
+            let mut conn = self.conn.lock().unwrap();
+            conn.restore(rusqlite::DatabaseName::Main, src, None::<fn(rusqlite::backup::Progress)>)?;
+        }
+        self.init_schema()?;
+        self.init_default_platforms()?;
+        self.run_migrations()
+    }
+
+    /// Rewrite stored image paths under `old_dir` to point under `new_dir`, for a database
+    /// restored from a machine with a different app data directory. Returns rows changed.
+    pub fn rebase_image_paths(&self, old_dir: &str, new_dir: &str) -> Result<usize> {
+        let rebase = |path: String| match path.strip_prefix(old_dir) {
+            Some(rest) => format!("{}{}", new_dir, rest),
+            None => path,
+        };
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+
+        let games: Vec<(String, Option<String>, Option<String>, String)> = {
+            let mut stmt = tx.prepare("SELECT id, cover_art_path, background_path, screenshots FROM games")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+        for (id, cover, background, screenshots_json) in games {
+            let screenshots: Vec<String> = serde_json::from_str(&screenshots_json).unwrap_or_default();
+            let new_cover = cover.clone().map(rebase);
+            let new_background = background.clone().map(rebase);
+            let new_screenshots: Vec<String> = screenshots.iter().cloned().map(rebase).collect();
+
+            if new_cover != cover || new_background != background || new_screenshots != screenshots {
+                tx.execute(
+                    "UPDATE games SET cover_art_path = ?1, background_path = ?2, screenshots = ?3 WHERE id = ?4",
+                    params![new_cover, new_background, serde_json::to_string(&new_screenshots).unwrap(), id],
+                )?;
+                changed += 1;
+            }
+        }
+
+        let icons: Vec<(String, String)> = {
+            let mut stmt = tx.prepare("SELECT id, icon_path FROM platforms WHERE icon_path IS NOT NULL")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+        for (id, icon) in icons {
+            let new_icon = rebase(icon.clone());
+            if new_icon != icon {
+                tx.execute("UPDATE platforms SET icon_path = ?1 WHERE id = ?2", params![new_icon, id])?;
+                changed += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(changed)
+    }
+
     // ==================== SETTINGS ====================
 
     /// Get a setting value
@@ -831,4 +1708,504 @@ impl Database {
         )?;
         Ok(())
     }
+
+    /// Delete a setting
+    pub fn delete_setting(&self, key: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
+    /// Get every setting as key/value pairs
+    pub fn get_all_settings(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let settings = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(settings)
+    }
+
+    /// Get a setting stored as JSON, deserialized into `T`
+    pub fn get_setting_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.get_setting(key)? {
+            Some(value) => serde_json::from_str(&value)
+                .map(Some)
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a setting as JSON
+    pub fn set_setting_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string(value)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.set_setting(key, &json)
+    }
+}
+
+/// A title without its leading article, so "The Legend of Zelda" sorts under L
+fn sort_title(title: &str) -> String {
+    for article in ["the ", "an ", "a "] {
+        if let Some(prefix) = title.get(..article.len()) {
+            let rest = title[article.len()..].trim_start();
+            if prefix.eq_ignore_ascii_case(article) && !rest.is_empty() {
+                return rest.to_string();
+            }
+        }
+    }
+    title.to_string()
+}
+
+/// Columns read by `game_from_row`, in order
+const GAME_COLUMNS: &str = "id, title, rom_path, platform_id, cover_art_path, background_path,
+    screenshots, description, release_date, genre, developer, publisher,
+    total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
+    collection_ids, created_at, is_completed, internal_title, manually_edited_fields,
+    metadata_source, last_scraped_at, igdb_id, n64_byte_order";
+
+/// Build a game from a row selected with `GAME_COLUMNS`
+fn game_from_row(row: &rusqlite::Row) -> Result<Game> {
+    Ok(Game {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        rom_path: row.get(2)?,
+        platform_id: row.get(3)?,
+        cover_art_path: row.get(4)?,
+        background_path: row.get(5)?,
+        screenshots: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+        description: row.get(7)?,
+        release_date: row.get(8)?,
+        genre: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
+        developer: row.get(10)?,
+        publisher: row.get(11)?,
+        total_play_time_seconds: row.get(12)?,
+        last_played: row.get(13)?,
+        is_favorite: row.get::<_, i32>(14)? == 1,
+        preferred_emulator_id: row.get(15)?,
+        collection_ids: serde_json::from_str(&row.get::<_, String>(16)?).unwrap_or_default(),
+        created_at: row.get(17)?,
+        is_completed: row.get::<_, i32>(18)? == 1,
+        internal_title: row.get(19)?,
+        manually_edited_fields: row.get::<_, Option<String>>(20)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        metadata_source: row.get(21)?,
+        last_scraped_at: row.get(22)?,
+        igdb_id: row.get(23)?,
+        n64_byte_order: row.get::<_, Option<String>>(24)?.as_deref().and_then(N64ByteOrder::parse),
+    })
+}
+
+/// Insert a game row with `verb` ("INSERT" or "INSERT OR IGNORE"), returning rows inserted
+fn insert_game(conn: &Connection, game: &Game, verb: &str) -> Result<usize> {
+    conn.execute(
+        &format!(
+            "{} INTO games (id, title, rom_path, platform_id, cover_art_path, background_path,
+                               screenshots, description, release_date, genre, developer, publisher,
+                               total_play_time_seconds, last_played, is_favorite, preferred_emulator_id,
+                               collection_ids, is_completed, internal_title, sort_title, manually_edited_fields,
+                               metadata_source, last_scraped_at, igdb_id, n64_byte_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+            verb
+        ),
+        params![
+            game.id,
+            game.title,
+            game.rom_path,
+            game.platform_id,
+            game.cover_art_path,
+            game.background_path,
+            serde_json::to_string(&game.screenshots).unwrap(),
+            game.description,
+            game.release_date,
+            serde_json::to_string(&game.genre).unwrap(),
+            game.developer,
+            game.publisher,
+            game.total_play_time_seconds,
+            game.last_played,
+            if game.is_favorite { 1 } else { 0 },
+            game.preferred_emulator_id,
+            serde_json::to_string(&game.collection_ids).unwrap(),
+            if game.is_completed { 1 } else { 0 },
+            game.internal_title,
+            sort_title(&game.title),
+            serde_json::to_string(&game.manually_edited_fields).unwrap(),
+            game.metadata_source,
+            game.last_scraped_at,
+            game.igdb_id,
+            game.n64_byte_order.map(|order| order.as_str()),
+        ],
+    )
+}
+
+/// Newest schema version this build knows how to use
+pub fn latest_schema_version() -> i32 {
+    MIGRATIONS.last().map(|(version, _)| *version).unwrap_or(0)
+}
+
+/// Schema version recorded in the settings table, 0 for a new database
+fn stored_schema_version(conn: &Connection) -> i32 {
+    conn.query_row(
+        "SELECT COALESCE((SELECT CAST(value AS INTEGER) FROM settings WHERE key = 'schema_version'), 0)",
+        [],
+        |row| row.get(0),
+    )
+    .unwrap_or(0)
+}
+
+/// Fail for a database written by a newer build, e.g. after downgrading the app
+fn check_schema_supported(version: i32) -> Result<()> {
+    if version > latest_schema_version() {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(format!(
+                "Database schema version {} is newer than this version of RetroVoid supports ({}); please update the app",
+                version,
+                latest_schema_version()
+            )),
+        ));
+    }
+    Ok(())
+}
+
+/// Body of `Database::merge_games`, inside the caller's transaction
+fn merge_games_into(tx: &Connection, keep_id: &str, merge_ids: &[String]) -> Result<()> {
+    let keep_collections: String = tx.query_row(
+        "SELECT collection_ids FROM games WHERE id = ?1",
+        params![keep_id],
+        |row| row.get(0),
+    )?;
+    let mut collection_ids: Vec<String> = serde_json::from_str(&keep_collections).unwrap_or_default();
+
+    // Play time, last played, collection ids, cover, background
+    type MergedRow = (i64, Option<String>, String, Option<String>, Option<String>);
+
+    let merge_ids: Vec<&String> = merge_ids.iter().filter(|id| id.as_str() != keep_id).collect();
+    for merge_id in &merge_ids {
+        let merged: Option<MergedRow> = tx.query_row(
+            "SELECT total_play_time_seconds, last_played, collection_ids, cover_art_path, background_path
+             FROM games WHERE id = ?1",
+            params![merge_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        ).optional()?;
+        let Some((play_time, last_played, merged_collections, cover, background)) = merged else {
+            continue;
+        };
+
+        tx.execute("UPDATE play_sessions SET game_id = ?1 WHERE game_id = ?2", params![keep_id, merge_id])?;
+        tx.execute("UPDATE achievements SET game_id = ?1 WHERE game_id = ?2", params![keep_id, merge_id])?;
+        tx.execute(
+            "UPDATE games SET total_play_time_seconds = total_play_time_seconds + ?1,
+                             last_played = NULLIF(MAX(COALESCE(last_played, ''), COALESCE(?2, '')), ''),
+                             cover_art_path = COALESCE(cover_art_path, ?3),
+                             background_path = COALESCE(background_path, ?4),
+                             updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?5",
+            params![play_time, last_played, cover, background, keep_id],
+        )?;
+
+        for collection_id in serde_json::from_str::<Vec<String>>(&merged_collections).unwrap_or_default() {
+            if !collection_ids.contains(&collection_id) {
+                collection_ids.push(collection_id);
+            }
+        }
+
+        tx.execute("DELETE FROM games WHERE id = ?1", params![merge_id])?;
+    }
+
+    tx.execute(
+        "UPDATE games SET collection_ids = ?1 WHERE id = ?2",
+        params![serde_json::to_string(&collection_ids).unwrap(), keep_id],
+    )?;
+
+    // Collections list their members too; point them at the kept game without duplicating it
+    let collections: Vec<(String, String, Option<String>)> = {
+        let mut stmt = tx.prepare("SELECT id, game_ids, cover_game_id FROM collections")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    for (id, game_ids_json, cover_game_id) in collections {
+        let game_ids: Vec<String> = serde_json::from_str(&game_ids_json).unwrap_or_default();
+        let is_merged = |game_id: &String| merge_ids.contains(&game_id);
+        if !game_ids.iter().any(is_merged) && !cover_game_id.as_ref().is_some_and(is_merged) {
+            continue;
+        }
+
+        let mut members: Vec<String> = Vec::new();
+        for game_id in game_ids {
+            let game_id = if is_merged(&game_id) { keep_id.to_string() } else { game_id };
+            if !members.contains(&game_id) {
+                members.push(game_id);
+            }
+        }
+        let cover_game_id = cover_game_id.map(|c| if is_merged(&c) { keep_id.to_string() } else { c });
+
+        tx.execute(
+            "UPDATE collections SET game_ids = ?1, cover_game_id = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+            params![serde_json::to_string(&members).unwrap(), cover_game_id, id],
+        )?;
+    }
+
+    let merge_ids: Vec<&str> = merge_ids.iter().map(|id| id.as_str()).collect();
+    replace_playlist_games(tx, &merge_ids, Some(keep_id))?;
+
+    Ok(())
+}
+
+/// Point playlist entries for the games in `ids` at `replacement`, or drop them without one.
+/// Playlists may repeat a game, so repointed entries are kept where they are.
+fn replace_playlist_games(conn: &Connection, ids: &[&str], replacement: Option<&str>) -> Result<()> {
+    let playlists: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, game_ids FROM playlists")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    for (id, game_ids_json) in playlists {
+        let game_ids: Vec<String> = serde_json::from_str(&game_ids_json).unwrap_or_default();
+        if !game_ids.iter().any(|game_id| ids.contains(&game_id.as_str())) {
+            continue;
+        }
+
+        let entries: Vec<String> = game_ids.into_iter()
+            .filter_map(|game_id| {
+                if ids.contains(&game_id.as_str()) { replacement.map(String::from) } else { Some(game_id) }
+            })
+            .collect();
+
+        conn.execute(
+            "UPDATE playlists SET game_ids = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![serde_json::to_string(&entries).unwrap(), id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Write sequential sort_order values (starting at 1) for `ids` in `table`, pushing unlisted
+/// rows after them. Rows added later take the next value up, so they are listed last.
+fn reorder_rows(conn: &mut Connection, table: &str, ids: &[String]) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute(&format!("UPDATE {} SET sort_order = ?1", table), params![ids.len() as i64 + 1])?;
+    for (position, id) in ids.iter().enumerate() {
+        tx.execute(
+            &format!("UPDATE {} SET sort_order = ?1 WHERE id = ?2", table),
+            params![position as i64 + 1, id],
+        )?;
+    }
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_plan(db: &Database, sql: &str) -> String {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql)).unwrap();
+        let details: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        details.join("\n")
+    }
+
+    #[test]
+    fn test_migrations_reach_latest_version_once() {
+        let versions: Vec<i32> = MIGRATIONS.iter().map(|(version, _)| *version).collect();
+        assert!(versions.windows(2).all(|pair| pair[1] == pair[0] + 1), "migrations must be numbered consecutively");
+
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let latest = latest_schema_version();
+        assert_eq!(db.schema_version(), latest);
+
+        // Running again applies nothing; re-running an ALTER TABLE would fail
+        db.run_migrations().unwrap();
+        assert_eq!(db.schema_version(), latest);
+    }
+
+    #[test]
+    fn test_newer_schema_version_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("retrovoid.db");
+
+        let db = Database::new(path.clone()).unwrap();
+        assert_eq!(db.schema_version(), latest_schema_version());
+        db.set_setting("schema_version", &(latest_schema_version() + 1).to_string()).unwrap();
+        drop(db);
+
+        let err = Database::new(path).err().expect("newer schema should be refused");
+        assert!(err.to_string().contains("newer than this version"), "{}", err);
+    }
+
+    #[test]
+    fn test_optimize_shrinks_file_after_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("retrovoid.db")).unwrap();
+
+        for i in 0..500 {
+            let mut game = Game::new(format!("Game {}", i), format!("/roms/game{}.sfc", i), "snes".to_string());
+            game.description = Some("x".repeat(1000));
+            db.add_game(&game).unwrap();
+        }
+        db.conn.lock().unwrap().execute("DELETE FROM games", []).unwrap();
+
+        let result = db.optimize().unwrap();
+        assert!(result.size_before > 0);
+        assert!(result.size_after < result.size_before, "{:?}", result);
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+
+        // Make migration 16 half-applicable: its first ALTER succeeds, its second fails
+        db.conn.lock().unwrap().execute_batch("ALTER TABLE emulators DROP COLUMN sort_order").unwrap();
+        db.set_setting("schema_version", "15").unwrap();
+
+        assert!(db.run_migrations().is_err());
+        assert_eq!(db.schema_version(), 15);
+
+        let conn = db.conn.lock().unwrap();
+        let has_sort_order: bool = conn
+            .query_row("SELECT COUNT(*) > 0 FROM pragma_table_info('emulators') WHERE name = 'sort_order'", [], |row| row.get(0))
+            .unwrap();
+        assert!(!has_sort_order);
+    }
+
+    #[test]
+    fn test_rows_added_after_reorder_are_listed_last() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let emulators: Vec<Emulator> = ["RetroArch", "Dolphin"].iter()
+            .map(|name| Emulator::new(name.to_string(), format!("/usr/bin/{}", name)))
+            .collect();
+        for emulator in &emulators {
+            db.add_emulator(emulator).unwrap();
+        }
+        db.reorder_emulators(&[emulators[1].id.clone(), emulators[0].id.clone()]).unwrap();
+
+        db.add_emulator(&Emulator::new("Cemu".to_string(), "/usr/bin/cemu".to_string())).unwrap();
+        let names: Vec<String> = db.get_all_emulators().unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["Dolphin", "RetroArch", "Cemu"]);
+
+        let ids: Vec<String> = db.get_all_platforms().unwrap().into_iter().map(|p| p.id).rev().collect();
+        db.reorder_platforms(&ids).unwrap();
+        db.add_platform(&Platform {
+            id: "homebrew".to_string(),
+            display_name: "Homebrew".to_string(),
+            manufacturer: "Various".to_string(),
+            file_extensions: vec![".bin".to_string()],
+            icon_path: None,
+            default_emulator_id: None,
+            color: "#888888".to_string(),
+            is_favorite: false,
+        }).unwrap();
+        let platforms = db.get_all_platforms().unwrap();
+        assert_eq!(platforms.last().unwrap().id, "homebrew");
+        assert_eq!(platforms[0].id, ids[0]);
+    }
+
+    #[test]
+    fn test_split_into_discs_is_all_or_nothing() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Metal Gear Solid".to_string(), "/roms/mgs.m3u".to_string(), "ps1".to_string());
+        db.add_game(&game).unwrap();
+
+        let first = Game::new("Metal Gear Solid (Disc 1)".to_string(), "/roms/mgs1.cue".to_string(), "ps1".to_string());
+        let mut second = Game::new("Metal Gear Solid (Disc 2)".to_string(), "/roms/mgs2.cue".to_string(), "ps1".to_string());
+        second.id = first.id.clone();
+        assert!(db.split_into_discs(&game.id, &[first.clone(), second.clone()], &first.id).is_err());
+        assert!(db.get_game(&game.id).unwrap().is_some());
+        assert!(db.get_game_by_path("/roms/mgs1.cue").unwrap().is_none());
+
+        second.id = uuid::Uuid::new_v4().to_string();
+        db.split_into_discs(&game.id, &[first.clone(), second], &first.id).unwrap();
+        assert!(db.get_game(&game.id).unwrap().is_none());
+        assert_eq!(db.get_all_games().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_failed_platform_delete_keeps_games_in_place() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let game = Game::new("Lemmings".to_string(), "/roms/lemmings.adf".to_string(), "nes".to_string());
+        db.add_game(&game).unwrap();
+        db.conn.lock().unwrap().execute_batch(
+            "CREATE TRIGGER keep_nes BEFORE DELETE ON platforms WHEN OLD.id = 'nes'
+             BEGIN SELECT RAISE(ABORT, 'platform is locked'); END",
+        ).unwrap();
+
+        assert!(db.delete_platform("nes", Some("dos")).is_err());
+        assert_eq!(db.get_game(&game.id).unwrap().unwrap().platform_id, "nes");
+    }
+
+    #[test]
+    fn test_update_emulator_clears_preferred_display() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let emulator = Emulator::new("Dolphin".to_string(), "/usr/bin/dolphin".to_string());
+        db.add_emulator(&emulator).unwrap();
+        let update = |json: &str| {
+            let updates: UpdateEmulatorInput = serde_json::from_str(json).unwrap();
+            db.update_emulator(&emulator.id, &updates).unwrap();
+            db.get_emulator(&emulator.id).unwrap().unwrap().preferred_display
+        };
+
+        assert_eq!(update(r#"{"preferredDisplay": 1}"#), Some(1));
+        assert_eq!(update(r#"{"name": "Dolphin Beta"}"#), Some(1));
+        assert_eq!(update(r#"{"preferredDisplay": null}"#), None);
+    }
+
+    #[test]
+    fn test_sort_title_backfill_matches_inserted_rows() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let titles = ["The  Legend of Zelda", "A ", "An Untitled Goose Game", "the\tWitness", "Anticipation", "Zelda"];
+        for (i, title) in titles.iter().enumerate() {
+            db.add_game(&Game::new(title.to_string(), format!("/roms/{}.nes", i), "nes".to_string())).unwrap();
+        }
+
+        // Re-run migration 19's backfill over the rows inserted through Rust
+        let backfill = MIGRATIONS.iter().find(|(version, _)| *version == 19).unwrap().1
+            .split(';')
+            .find(|statement| statement.trim_start().starts_with("UPDATE"))
+            .unwrap();
+        let conn = db.conn.lock().unwrap();
+        let stored = |conn: &Connection| -> Vec<(String, String)> {
+            let mut stmt = conn.prepare("SELECT title, sort_title FROM games ORDER BY rom_path").unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|r| r.unwrap()).collect()
+        };
+        let inserted = stored(&conn);
+        conn.execute_batch(&format!("UPDATE games SET sort_title = NULL; {}", backfill)).unwrap();
+
+        assert_eq!(stored(&conn), inserted);
+        assert!(inserted.iter().all(|(title, key)| *key == sort_title(title)));
+    }
+
+    #[test]
+    fn test_games_paged_by_sort_title() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        for title in ["The Legend of Zelda", "Metroid", "Kirby's Adventure"] {
+            db.add_game(&Game::new(title.to_string(), format!("/roms/{}.nes", title), "nes".to_string())).unwrap();
+        }
+        let titles = |sort| -> Vec<String> {
+            db.get_games_paged(sort, 0, 10).unwrap().into_iter().map(|g| g.title).collect()
+        };
+
+        assert_eq!(titles(GameSort::SortTitle), ["Kirby's Adventure", "The Legend of Zelda", "Metroid"]);
+        assert_eq!(titles(GameSort::Title), ["Kirby's Adventure", "Metroid", "The Legend of Zelda"]);
+        assert_eq!(db.get_games_paged(GameSort::SortTitle, 1, 1).unwrap()[0].title, "The Legend of Zelda");
+
+        // Renaming recomputes the sort title
+        let metroid = db.get_all_games().unwrap().into_iter().find(|g| g.title == "Metroid").unwrap();
+        db.update_game(&metroid.id, &UpdateGameInput { title: Some("A Boy and His Blob".to_string()), ..Default::default() }).unwrap();
+        assert_eq!(titles(GameSort::SortTitle), ["A Boy and His Blob", "Kirby's Adventure", "The Legend of Zelda"]);
+    }
+
+    #[test]
+    fn test_title_indexes_used() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+
+        let sort_plan = query_plan(&db, "SELECT id FROM games ORDER BY title");
+        assert!(sort_plan.contains("idx_games_title"), "{}", sort_plan);
+
+        let search_plan = query_plan(&db, "SELECT id FROM games WHERE title = 'zelda' COLLATE NOCASE");
+        assert!(search_plan.contains("idx_games_title_nocase"), "{}", search_plan);
+    }
 }
@@ -0,0 +1,114 @@
+use crate::models::{DeviceKind, DeviceProfile, UiMode};
+
+/// Facts about the machine the handheld heuristic looks at, gathered by `DeviceInfo::current`
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    /// `tauri_plugin_os::platform()`, e.g. "linux" or "windows"
+    pub platform: String,
+    pub os_version: String,
+    /// DMI vendor and product strings, empty when unreadable (always, outside Linux)
+    pub board_vendor: String,
+    pub product_name: String,
+    /// Set by SteamOS, which exports `SteamDeck=1` in game mode
+    pub steam_deck_env: bool,
+}
+
+/// Product name fragments of handheld PCs, matched case-insensitively against DMI product names.
+/// Lenovo reports a model number rather than a name, so both are listed.
+const HANDHELD_PRODUCTS: &[(&str, &str)] = &[
+    ("rog ally", "ROG Ally"),
+    ("rc71l", "ROG Ally"),
+    ("rc72la", "ROG Ally X"),
+    ("legion go", "Legion Go"),
+    ("83e1", "Legion Go"),
+    ("ayaneo", "AYANEO"),
+    ("onexplayer", "OneXPlayer"),
+    ("gpd win", "GPD Win"),
+    ("claw a1m", "MSI Claw"),
+];
+
+impl DeviceInfo {
+    pub fn current() -> Self {
+        let dmi = |field: &str| {
+            std::fs::read_to_string(format!("/sys/devices/virtual/dmi/id/{}", field))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        Self {
+            platform: tauri_plugin_os::platform().to_string(),
+            os_version: tauri_plugin_os::version().to_string(),
+            board_vendor: dmi("board_vendor"),
+            product_name: dmi("product_name"),
+            steam_deck_env: std::env::var("SteamDeck").is_ok_and(|value| value == "1"),
+        }
+    }
+}
+
+/// Work out what kind of device this is, then apply the user's `ui_mode` override
+pub fn detect_profile(info: &DeviceInfo, ui_mode: UiMode) -> DeviceProfile {
+    let product = info.product_name.to_lowercase();
+
+    // Valve's boards are codenamed Jupiter (LCD) and Galileo (OLED)
+    let (device, device_name) = if info.steam_deck_env
+        || (info.board_vendor.eq_ignore_ascii_case("valve") && (product == "jupiter" || product == "galileo"))
+    {
+        (DeviceKind::SteamDeck, Some("Steam Deck".to_string()))
+    } else if let Some((_, name)) = HANDHELD_PRODUCTS.iter().find(|(marker, _)| product.contains(marker)) {
+        (DeviceKind::Handheld, Some(name.to_string()))
+    } else {
+        (DeviceKind::Desktop, None)
+    };
+
+    let controller_first = match ui_mode {
+        UiMode::Auto => device != DeviceKind::Desktop,
+        UiMode::Desktop => false,
+        UiMode::Controller => true,
+    };
+
+    DeviceProfile {
+        device,
+        device_name,
+        platform: info.platform.clone(),
+        os_version: info.os_version.clone(),
+        ui_mode,
+        controller_first,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linux(board_vendor: &str, product_name: &str) -> DeviceInfo {
+        DeviceInfo {
+            platform: "linux".to_string(),
+            os_version: "3.5.19".to_string(),
+            board_vendor: board_vendor.to_string(),
+            product_name: product_name.to_string(),
+            steam_deck_env: false,
+        }
+    }
+
+    #[test]
+    fn test_detect_handhelds() {
+        let deck = detect_profile(&linux("Valve", "Galileo"), UiMode::Auto);
+        assert_eq!(deck.device, DeviceKind::SteamDeck);
+        assert!(deck.controller_first);
+
+        let game_mode = DeviceInfo { steam_deck_env: true, ..linux("", "") };
+        assert_eq!(detect_profile(&game_mode, UiMode::Auto).device, DeviceKind::SteamDeck);
+
+        let ally = detect_profile(&linux("ASUSTeK COMPUTER INC.", "ROG Ally RC71L_RC71L"), UiMode::Auto);
+        assert_eq!(ally.device, DeviceKind::Handheld);
+        assert_eq!(ally.device_name.as_deref(), Some("ROG Ally"));
+
+        let desktop = detect_profile(&linux("Micro-Star International Co., Ltd.", "MS-7C56"), UiMode::Auto);
+        assert_eq!(desktop.device, DeviceKind::Desktop);
+        assert!(!desktop.controller_first);
+
+        // The override wins over detection either way
+        assert!(!detect_profile(&linux("Valve", "Jupiter"), UiMode::Desktop).controller_first);
+        assert!(detect_profile(&linux("", "MS-7C56"), UiMode::Controller).controller_first);
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, System};
+
+/// Time between foreground-window checks. Each check may start a helper process, so this is
+/// kept well above the process monitor's interval.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Adds up the time a window spends focused, one segment per stretch of focus.
+/// Times are offsets from the start of the session.
+#[derive(Debug, Clone)]
+pub struct FocusTracker {
+    focused_since: Option<Duration>,
+    focused_total: Duration,
+}
+
+impl FocusTracker {
+    pub fn new(focused: bool) -> Self {
+        Self {
+            focused_since: focused.then_some(Duration::ZERO),
+            focused_total: Duration::ZERO,
+        }
+    }
+
+    /// Record the focus state observed at `at`. Returns true when this closes a focused segment.
+    pub fn update(&mut self, focused: bool, at: Duration) -> bool {
+        match (self.focused_since, focused) {
+            (None, true) => {
+                self.focused_since = Some(at);
+                false
+            }
+            (Some(since), false) => {
+                self.focused_total += at.saturating_sub(since);
+                self.focused_since = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused_since.is_some()
+    }
+
+    /// Focused time up to `now`, including the open segment
+    pub fn focused_seconds(&self, now: Duration) -> i64 {
+        let open = self.focused_since.map(|since| now.saturating_sub(since)).unwrap_or_default();
+        (self.focused_total + open).as_secs() as i64
+    }
+}
+
+/// Tracks whether a launched emulator is the foreground window
+pub struct FocusWatcher {
+    pid: u32,
+    system: System,
+    started: Instant,
+    last_check: Instant,
+    tracker: FocusTracker,
+}
+
+impl FocusWatcher {
+    /// Start watching `pid`, which is assumed focused since it was just launched
+    pub fn new(pid: u32) -> Self {
+        let now = Instant::now();
+        Self {
+            pid,
+            system: System::new(),
+            started: now,
+            last_check: now,
+            tracker: FocusTracker::new(true),
+        }
+    }
+
+    /// Check the foreground window if it's time to. Returns true when a focused segment
+    /// just ended, so the caller can save the running total.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        // When the foreground window can't be determined, keep counting rather than lose time
+        let focused = foreground_pid()
+            .map(|foreground| self.is_same_or_child(foreground))
+            .unwrap_or(true);
+        self.tracker.update(focused, self.started.elapsed())
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.tracker.is_focused()
+    }
+
+    pub fn focused_seconds(&self) -> i64 {
+        self.tracker.focused_seconds(self.started.elapsed())
+    }
+
+    /// Whether `pid` is the watched process or one it started, such as an emulator
+    /// launched through a wrapper command
+    fn is_same_or_child(&mut self, pid: u32) -> bool {
+        let mut current = Pid::from_u32(pid);
+        // Bounded in case of a cycle from a recycled PID
+        for _ in 0..16 {
+            if current == Pid::from_u32(self.pid) {
+                return true;
+            }
+            self.system.refresh_process(current);
+            match self.system.process(current).and_then(|process| process.parent()) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+/// PID of the process owning the foreground window
+#[cfg(target_os = "windows")]
+fn foreground_pid() -> Option<u32> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let mut pid = 0u32;
+    // SAFETY: both calls only read window state; pid outlives the call writing to it
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        GetWindowThreadProcessId(window, &mut pid);
+    }
+    (pid != 0).then_some(pid)
+}
+
+/// PID of the frontmost application
+#[cfg(target_os = "macos")]
+fn foreground_pid() -> Option<u32> {
+    use std::process::Command;
+
+    let front = Command::new("lsappinfo").arg("front").output().ok()?;
+    let asn = String::from_utf8_lossy(&front.stdout).trim().to_string();
+    if asn.is_empty() {
+        return None;
+    }
+
+    // Prints "pid"=1234
+    let info = Command::new("lsappinfo").args(["info", "-only", "pid", &asn]).output().ok()?;
+    String::from_utf8_lossy(&info.stdout).rsplit('=').next()?.trim().parse().ok()
+}
+
+/// PID of the active window, via xdotool. Not available on Wayland, where this returns None.
+#[cfg(target_os = "linux")]
+fn foreground_pid() -> Option<u32> {
+    let output = std::process::Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_segments_accumulate() {
+        let secs = Duration::from_secs;
+        let mut tracker = FocusTracker::new(true);
+
+        // Focused 0-60s, alt-tabbed away 60-90s, back 90-100s, away 100-130s, back from 130s
+        assert!(!tracker.update(true, secs(30)));
+        assert!(tracker.update(false, secs(60)));
+        assert!(!tracker.update(false, secs(75)));
+        assert_eq!(tracker.focused_seconds(secs(80)), 60);
+        assert!(!tracker.update(true, secs(90)));
+        assert!(tracker.update(false, secs(100)));
+        assert!(!tracker.update(true, secs(130)));
+
+        assert!(tracker.is_focused());
+        assert_eq!(tracker.focused_seconds(secs(150)), 90);
+
+        let mut started_unfocused = FocusTracker::new(false);
+        started_unfocused.update(true, secs(10));
+        assert_eq!(started_unfocused.focused_seconds(secs(25)), 15);
+    }
+}
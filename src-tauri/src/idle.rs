@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use sysinfo::{Pid, System};
+
+/// CPU usage (percent of one core) at or below which an emulator is treated as idle.
+/// A running game keeps at least one core busy; a paused or backgrounded one barely registers.
+pub const IDLE_CPU_PERCENT: f32 = 2.0;
+
+/// How long an emulator must sit idle before that time stops counting as play
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// Time between CPU samples. sysinfo measures usage since the previous refresh, so this is
+/// also the window each sample averages over.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Accumulates idle time from a series of CPU samples. A stretch of low-CPU samples only counts
+/// once it lasts `threshold`, and then counts in full, since the player left when it began.
+#[derive(Debug, Clone)]
+pub struct IdleTracker {
+    threshold: Duration,
+    idle_total: Duration,
+    current_run: Duration,
+}
+
+impl IdleTracker {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            idle_total: Duration::ZERO,
+            current_run: Duration::ZERO,
+        }
+    }
+
+    /// Record a sample of `cpu_percent` covering the `elapsed` time since the previous one
+    pub fn sample(&mut self, cpu_percent: f32, elapsed: Duration) {
+        if cpu_percent <= IDLE_CPU_PERCENT {
+            self.current_run += elapsed;
+        } else {
+            self.end_run();
+        }
+    }
+
+    /// End the current stretch without adding time, for periods that aren't being counted
+    pub fn interrupt(&mut self) {
+        self.end_run();
+    }
+
+    /// Idle time so far, including the current stretch if it has passed the threshold
+    pub fn idle_seconds(&self) -> i64 {
+        let current = if self.current_run >= self.threshold { self.current_run } else { Duration::ZERO };
+        (self.idle_total + current).as_secs() as i64
+    }
+
+    fn end_run(&mut self) {
+        if self.current_run >= self.threshold {
+            self.idle_total += self.current_run;
+        }
+        self.current_run = Duration::ZERO;
+    }
+}
+
+/// Reads a process's CPU usage between calls
+pub struct CpuSampler {
+    system: System,
+    pid: Pid,
+}
+
+impl CpuSampler {
+    pub fn new(pid: u32) -> Self {
+        let pid = Pid::from_u32(pid);
+        let mut system = System::new();
+        // The first refresh only establishes a baseline for the next one to measure against
+        system.refresh_process(pid);
+        Self { system, pid }
+    }
+
+    /// CPU usage since the last call, or None once the process is gone
+    pub fn sample(&mut self) -> Option<f32> {
+        if !self.system.refresh_process(self.pid) {
+            return None;
+        }
+        self.system.process(self.pid).map(|process| process.cpu_usage())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_runs_past_threshold_are_subtracted() {
+        let mut tracker = IdleTracker::new(Duration::from_secs(60));
+        let step = Duration::from_secs(10);
+
+        // 30s of play, a 40s pause (under the threshold), more play, then 90s away
+        let samples = [80.0, 95.0, 70.0, 0.5, 1.0, 0.0, 0.2, 60.0, 75.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 85.0];
+        for cpu in samples {
+            tracker.sample(cpu, step);
+        }
+        assert_eq!(tracker.idle_seconds(), 90);
+
+        // A stretch still in progress counts as soon as it reaches the threshold
+        for _ in 0..5 {
+            tracker.sample(0.0, step);
+        }
+        assert_eq!(tracker.idle_seconds(), 90);
+        tracker.sample(0.0, step);
+        assert_eq!(tracker.idle_seconds(), 150);
+    }
+}
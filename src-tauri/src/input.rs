@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use serde::Serialize;
+use tauri::Emitter;
+
+/// Event the frontend listens to for controller navigation
+pub const GAMEPAD_EVENT: &str = "gamepad-input";
+
+/// How far a stick must move (0.0 to 1.0) before it counts as a direction
+pub const DEFAULT_DEADZONE: f32 = 0.5;
+
+/// Repeats of the same action closer together than this are dropped, so a bouncing
+/// button or a stick jittering around the deadzone edge doesn't skip several tiles
+const DEBOUNCE: Duration = Duration::from_millis(120);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Set while the polling thread runs, so starting twice doesn't double every event
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Navigation actions sent to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GamepadAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A (south face button)
+    Confirm,
+    /// B (east face button)
+    Back,
+    /// Left shoulder
+    PreviousPage,
+    /// Right shoulder
+    NextPage,
+    Menu,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GamepadInput {
+    pub action: GamepadAction,
+    pub gamepad_id: usize,
+}
+
+/// The parts of a gamepad event that drive navigation
+#[derive(Debug, Clone, Copy)]
+pub enum PadEvent {
+    Pressed(Button),
+    Axis(Axis, f32),
+}
+
+/// Turns raw button and stick events into debounced navigation actions
+pub struct InputMapper {
+    deadzone: f32,
+    /// Direction each stick axis currently points, so only leaving the deadzone fires
+    stick: HashMap<Axis, Option<GamepadAction>>,
+    last_fired: HashMap<GamepadAction, Instant>,
+}
+
+impl InputMapper {
+    pub fn new(deadzone: f32) -> Self {
+        Self {
+            deadzone: deadzone.clamp(0.05, 0.95),
+            stick: HashMap::new(),
+            last_fired: HashMap::new(),
+        }
+    }
+
+    pub fn handle(&mut self, event: PadEvent, now: Instant) -> Option<GamepadAction> {
+        let action = match event {
+            PadEvent::Pressed(button) => button_action(button),
+            PadEvent::Axis(axis, value) => self.stick_action(axis, value),
+        }?;
+
+        if let Some(last) = self.last_fired.get(&action) {
+            if now.duration_since(*last) < DEBOUNCE {
+                return None;
+            }
+        }
+        self.last_fired.insert(action, now);
+        Some(action)
+    }
+
+    fn stick_action(&mut self, axis: Axis, value: f32) -> Option<GamepadAction> {
+        let (negative, positive) = match axis {
+            Axis::LeftStickX | Axis::DPadX => (GamepadAction::Left, GamepadAction::Right),
+            // Stick Y is positive when pushed up
+            Axis::LeftStickY | Axis::DPadY => (GamepadAction::Down, GamepadAction::Up),
+            _ => return None,
+        };
+
+        let direction = if value.abs() < self.deadzone {
+            None
+        } else if value > 0.0 {
+            Some(positive)
+        } else {
+            Some(negative)
+        };
+
+        // Holding the stick over doesn't repeat; it has to come back first
+        let previous = self.stick.insert(axis, direction).flatten();
+        direction.filter(|direction| previous != Some(*direction))
+    }
+}
+
+fn button_action(button: Button) -> Option<GamepadAction> {
+    match button {
+        Button::DPadUp => Some(GamepadAction::Up),
+        Button::DPadDown => Some(GamepadAction::Down),
+        Button::DPadLeft => Some(GamepadAction::Left),
+        Button::DPadRight => Some(GamepadAction::Right),
+        Button::South => Some(GamepadAction::Confirm),
+        Button::East => Some(GamepadAction::Back),
+        Button::LeftTrigger => Some(GamepadAction::PreviousPage),
+        Button::RightTrigger => Some(GamepadAction::NextPage),
+        Button::Start => Some(GamepadAction::Menu),
+        _ => None,
+    }
+}
+
+/// Poll connected gamepads on a background thread and emit `gamepad-input` events.
+/// Returns false if polling was already running.
+pub fn start_polling(app_handle: tauri::AppHandle, deadzone: f32) -> bool {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+
+    std::thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(e) => {
+                eprintln!("Gamepad input unavailable: {}", e);
+                RUNNING.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+        let mut mapper = InputMapper::new(deadzone);
+
+        loop {
+            while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+                let pad_event = match event {
+                    EventType::ButtonPressed(button, _) => PadEvent::Pressed(button),
+                    EventType::AxisChanged(axis, value, _) => PadEvent::Axis(axis, value),
+                    _ => continue,
+                };
+
+                if let Some(action) = mapper.handle(pad_event, Instant::now()) {
+                    let input = GamepadInput { action, gamepad_id: id.into() };
+                    if let Err(e) = app_handle.emit(GAMEPAD_EVENT, input) {
+                        eprintln!("Failed to emit gamepad input: {}", e);
+                    }
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buttons_map_to_actions_and_debounce() {
+        let mut mapper = InputMapper::new(DEFAULT_DEADZONE);
+        let start = Instant::now();
+
+        assert_eq!(mapper.handle(PadEvent::Pressed(Button::South), start), Some(GamepadAction::Confirm));
+        assert_eq!(mapper.handle(PadEvent::Pressed(Button::RightTrigger), start), Some(GamepadAction::NextPage));
+        assert_eq!(mapper.handle(PadEvent::Pressed(Button::North), start), None);
+
+        // A bounce right after the press is dropped; a real second press isn't
+        assert_eq!(mapper.handle(PadEvent::Pressed(Button::South), start + Duration::from_millis(30)), None);
+        assert_eq!(mapper.handle(PadEvent::Pressed(Button::South), start + Duration::from_millis(300)), Some(GamepadAction::Confirm));
+    }
+
+    #[test]
+    fn test_stick_deadzone_and_edges() {
+        let mut mapper = InputMapper::new(0.4);
+        let mut now = Instant::now();
+        let mut stick = |axis, value| {
+            now += Duration::from_millis(200);
+            mapper.handle(PadEvent::Axis(axis, value), now)
+        };
+
+        assert_eq!(stick(Axis::LeftStickX, 0.2), None);
+        assert_eq!(stick(Axis::LeftStickX, 0.9), Some(GamepadAction::Right));
+        // Still held over: no repeat
+        assert_eq!(stick(Axis::LeftStickX, 1.0), None);
+        assert_eq!(stick(Axis::LeftStickX, 0.0), None);
+        assert_eq!(stick(Axis::LeftStickY, 0.8), Some(GamepadAction::Up));
+        assert_eq!(stick(Axis::LeftStickY, -0.8), Some(GamepadAction::Down));
+        assert_eq!(stick(Axis::RightStickX, 1.0), None);
+    }
+}
@@ -1,14 +1,32 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
 mod commands;
 mod db;
+mod device;
+mod focus;
+mod idle;
+mod input;
 mod models;
+mod presence;
+mod reports;
+mod retroachievements;
+mod rom_header;
+mod savestates;
 mod scraper;
+mod secrets;
+mod signatures;
+mod sync;
+#[cfg(test)]
+mod test_util;
+mod thumbnails;
+mod webp;
 
 use commands::AppState;
 use db::Database;
+use presence::DiscordPresence;
+use secrets::SecretStore;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -28,10 +46,28 @@ pub fn run() {
             // Initialize the database
             let db = Database::new(db_path).expect("Failed to initialize database");
 
+            // Secrets are encrypted with a key kept beside the database
+            let secrets = SecretStore::open(&app_data_dir).expect("Failed to open secret store");
+            if let Err(e) = secrets.migrate_plaintext_setting(&db, "igdb_client_secret") {
+                eprintln!("Failed to migrate IGDB client secret: {}", e);
+            }
+
+            // Keep rolling backups in case a crash corrupts the database
+            if let Err(e) = commands::rotate_database_backups(&db, &app_data_dir.join("backups"), chrono::Local::now()) {
+                eprintln!("Failed to back up database: {}", e);
+            }
+
+            // A now-playing file left behind by a crash would show a stale game
+            commands::clear_now_playing_file(&app_data_dir);
+
             // Create app state
             let state = AppState {
-                db,
-                active_sessions: Mutex::new(HashMap::new()),
+                db: Arc::new(db),
+                active_sessions: Arc::new(Mutex::new(HashMap::new())),
+                secrets: Arc::new(secrets),
+                presence: Arc::new(DiscordPresence::new()),
+                app_data_dir,
+                launch_queue: Arc::default(),
             };
 
             // Manage state
@@ -43,49 +79,155 @@ pub fn run() {
             // Game commands
             commands::get_all_games,
             commands::get_game,
+            commands::get_games_paged,
+            commands::get_game_by_hash,
+            commands::hash_game_rom,
             commands::add_game,
+            commands::add_games_batch,
             commands::update_game,
             commands::delete_game,
             commands::delete_games_batch,
+            commands::clear_metadata,
+            commands::merge_games,
+            commands::split_m3u_game,
+            commands::regenerate_m3u,
+            commands::validate_m3u,
+            commands::reorder_m3u,
             commands::toggle_favorite,
             commands::set_custom_cover_art,
+            commands::set_cover_art_from_url,
+            commands::match_local_artwork,
+            commands::import_emulator_screenshots,
+            commands::get_thumbnail,
+            commands::export_library_html,
+            commands::export_play_sessions_csv,
+            commands::import_play_sessions_csv,
             // Emulator commands
             commands::get_all_emulators,
             commands::get_emulator,
             commands::add_emulator,
             commands::update_emulator,
             commands::delete_emulator,
+            commands::reorder_emulators,
+            commands::list_displays,
             // Platform commands
             commands::get_all_platforms,
+            commands::get_platforms,
             commands::get_platform,
+            commands::reorder_platforms,
+            commands::add_platform,
+            commands::update_platform,
+            commands::delete_platform,
+            commands::reset_platform_defaults,
+            commands::get_platform_game_counts,
+            commands::set_platform_icon,
+            commands::set_platform_color,
+            commands::toggle_platform_favorite,
             commands::set_default_emulator,
             // Collection commands
             commands::get_all_collections,
             commands::add_collection,
             commands::update_collection,
             commands::delete_collection,
+            commands::get_collection_stats,
+            commands::get_virtual_collection,
+            commands::get_games_grouped_by_decade,
+            // Smart collection commands
+            commands::get_all_smart_collections,
+            commands::add_smart_collection,
+            commands::update_smart_collection,
+            commands::delete_smart_collection,
+            commands::get_smart_collection_games,
+            commands::get_random_game,
+            commands::get_game_of_the_day,
+            // Playlist commands
+            commands::get_all_playlists,
+            commands::add_playlist,
+            commands::update_playlist,
+            commands::delete_playlist,
+            commands::get_playlist_games,
+            commands::move_playlist_item,
             // Library scanning
             commands::scan_library,
             // Launch commands
             commands::launch_game,
             commands::launch_game_with_emulator,
+            commands::launch_random_game,
+            commands::enqueue_game,
+            commands::get_launch_queue,
+            commands::dequeue_and_launch,
             commands::end_game_session,
+            commands::get_launch_log,
             // Play session commands
             commands::get_play_sessions,
+            commands::get_playtime_report,
+            commands::get_play_streak,
+            commands::get_most_played_recent,
+            commands::add_manual_play_session,
+            commands::delete_play_session,
+            commands::update_play_session,
+            commands::clear_play_history,
+            // Save state commands
+            commands::list_save_states,
+            commands::delete_save_state,
+            commands::backup_save_states,
+            // Personal achievement commands
+            commands::get_achievements,
+            commands::add_achievement,
+            commands::toggle_achievement,
             // Utility commands
             commands::validate_emulator_path,
             commands::get_rom_info,
+            commands::get_title_cleaning_rules,
+            commands::set_title_cleaning_rules,
+            commands::validate_rom_path,
+            commands::read_internal_title,
+            commands::find_mislabeled_roms,
+            commands::get_n64_byte_order,
+            commands::convert_n64_byteorder,
+            commands::strip_smc_header,
+            commands::convert_images_to_webp,
+            commands::dedup_cover_images,
+            commands::find_corrupt_images,
+            commands::get_schema_version,
+            commands::optimize_database,
             // Settings commands
             commands::get_setting,
             commands::set_setting,
+            commands::get_setting_json,
+            commands::set_setting_json,
+            commands::get_keybindings,
+            commands::set_keybindings,
+            commands::set_secret,
+            commands::get_secret,
+            commands::export_settings,
+            commands::import_settings,
+            // Sync commands
+            commands::sync_push,
+            commands::sync_pull,
+            commands::backup_images,
+            commands::restore_images,
+            // Storage commands
+            commands::get_storage_report,
             // RetroArch commands
             commands::get_default_retroarch_cores_path,
             commands::scan_retroarch_cores,
             // Scraping commands
+            commands::ra_login,
+            commands::get_achievement_summary,
+            commands::get_game_achievements,
             commands::validate_igdb_credentials,
             commands::search_igdb,
+            commands::search_all_providers,
             commands::scrape_game_metadata,
             commands::scrape_library_metadata,
+            commands::rescrape_stale,
+            commands::repair_missing_covers,
+            // Gamepad commands
+            commands::start_gamepad_input,
+            // Device commands
+            commands::get_device_profile,
+            commands::set_ui_mode,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -23,6 +23,18 @@ pub struct Game {
     pub preferred_emulator_id: Option<String>,
     pub collection_ids: Vec<String>,
     pub created_at: Option<String>,
+    pub is_completed: bool,
+    /// Title stored in the ROM's own header, when its format has one
+    pub internal_title: Option<String>,
+    /// Metadata fields the user has edited, which re-scrapes leave alone
+    pub manually_edited_fields: Vec<String>,
+    /// Provider of the last successful scrape ("igdb" or "offline")
+    pub metadata_source: Option<String>,
+    pub last_scraped_at: Option<String>,
+    /// IGDB game the metadata was scraped from, for re-fetching artwork later
+    pub igdb_id: Option<u64>,
+    /// Byte order of an N64 ROM dump, detected when the game is scanned
+    pub n64_byte_order: Option<N64ByteOrder>,
 }
 
 impl Game {
@@ -46,6 +58,33 @@ impl Game {
             preferred_emulator_id: None,
             collection_ids: Vec::new(),
             created_at: Some(chrono::Utc::now().to_rfc3339()),
+            is_completed: false,
+            internal_title: None,
+            manually_edited_fields: Vec::new(),
+            metadata_source: None,
+            last_scraped_at: None,
+            igdb_id: None,
+            n64_byte_order: None,
+        }
+    }
+}
+
+/// Checksum algorithms stored for ROM files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Crc32,
+    Md5,
+    Sha1,
+}
+
+impl HashAlgorithm {
+    /// Column in the games table holding this checksum
+    pub fn column(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Crc32 => "crc32",
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
         }
     }
 }
@@ -59,6 +98,46 @@ pub struct Emulator {
     pub executable_path: String,
     pub launch_arguments: String,
     pub supported_platform_ids: Vec<String>,
+    pub capture_logs: bool,
+    pub fullscreen_argument: Option<String>,
+    pub launch_type: LaunchType,
+    /// Command the executable is run through, e.g., `wine` or `proton run`
+    pub wrapper_command: Option<String>,
+    /// Index (from `list_displays`) of the monitor to open on
+    pub preferred_display: Option<u32>,
+    /// Flag selecting a display, with `{display}` standing in for the index, e.g. `--display={display}`
+    pub display_argument: Option<String>,
+    /// Resolution flags, with `{w}` and `{h}` standing in for the launch resolution settings,
+    /// e.g. `--width={w} --height={h}`
+    pub resolution_argument_template: Option<String>,
+}
+
+/// How an emulator entry starts a game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchType {
+    /// Spawn the executable with the expanded launch arguments
+    #[default]
+    Executable,
+    /// Open the expanded launch arguments as a URI (e.g., steam://rungameid/...). Placeholders
+    /// are percent-encoded, and no play session is tracked since nothing reports the exit.
+    Uri,
+}
+
+impl LaunchType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LaunchType::Executable => "executable",
+            LaunchType::Uri => "uri",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "uri" => LaunchType::Uri,
+            _ => LaunchType::Executable,
+        }
+    }
 }
 
 impl Emulator {
@@ -69,6 +148,13 @@ impl Emulator {
             executable_path,
             launch_arguments: String::from("{rom}"),
             supported_platform_ids: Vec::new(),
+            capture_logs: false,
+            fullscreen_argument: None,
+            launch_type: LaunchType::Executable,
+            wrapper_command: None,
+            preferred_display: None,
+            display_argument: None,
+            resolution_argument_template: None,
         }
     }
 }
@@ -84,6 +170,9 @@ pub struct Platform {
     pub icon_path: Option<String>,
     pub default_emulator_id: Option<String>,
     pub color: String,
+    /// Pinned platforms are listed before the rest
+    #[serde(default)]
+    pub is_favorite: bool,
 }
 
 /// Represents a user-created collection of games
@@ -94,6 +183,8 @@ pub struct Collection {
     pub name: String,
     pub game_ids: Vec<String>,
     pub cover_game_id: Option<String>,
+    /// Effective cover art: the cover game's art, else the first member with art (not stored)
+    pub cover_art_path: Option<String>,
 }
 
 impl Collection {
@@ -103,6 +194,7 @@ impl Collection {
             name,
             game_ids: Vec::new(),
             cover_game_id: None,
+            cover_art_path: None,
         }
     }
 }
@@ -115,7 +207,13 @@ pub struct PlaySession {
     pub game_id: String,
     pub start_time: String,
     pub end_time: Option<String>,
+    /// Time counted as play, excluding idle stretches when idle tracking is on
     pub duration_seconds: i64,
+    pub note: Option<String>,
+    /// Wall-clock length of a tracked session, idle time included. None for sessions added
+    /// by hand or imported.
+    #[serde(default)]
+    pub gross_duration_seconds: Option<i64>,
 }
 
 impl PlaySession {
@@ -126,10 +224,48 @@ impl PlaySession {
             start_time: chrono::Utc::now().to_rfc3339(),
             end_time: None,
             duration_seconds: 0,
+            note: None,
+            gross_duration_seconds: None,
+        }
+    }
+}
+
+/// A goal the user tracks for a game themselves, for systems without RetroAchievements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomAchievement {
+    pub id: String,
+    pub game_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub unlocked: bool,
+    pub unlocked_at: Option<String>,
+}
+
+impl CustomAchievement {
+    pub fn new(game_id: String, title: String, description: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            game_id,
+            title,
+            description,
+            unlocked: false,
+            unlocked_at: None,
         }
     }
 }
 
+/// An emulator save-state file belonging to a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveState {
+    /// Slot number, or None for the emulator's automatic save
+    pub slot: Option<u32>,
+    pub path: String,
+    pub modified_at: String,
+    pub size: u64,
+}
+
 /// Result of launching a game
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -137,6 +273,90 @@ pub struct LaunchResult {
     pub success: bool,
     pub pid: Option<u32>,
     pub error: Option<String>,
+    /// Non-fatal problems, such as a failed pre-launch hook
+    pub warnings: Vec<String>,
+}
+
+/// Actions that can be bound to a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyAction {
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+    LaunchGame,
+    Back,
+    Search,
+    ToggleFavorite,
+    NextPage,
+    PreviousPage,
+    OpenSettings,
+}
+
+/// Keyboard shortcuts, as action to key (a `KeyboardEvent.key` value such as "Enter")
+pub type Keybindings = std::collections::BTreeMap<KeyAction, String>;
+
+/// Shortcuts used until the user changes them
+pub fn default_keybindings() -> Keybindings {
+    [
+        (KeyAction::NavigateUp, "ArrowUp"),
+        (KeyAction::NavigateDown, "ArrowDown"),
+        (KeyAction::NavigateLeft, "ArrowLeft"),
+        (KeyAction::NavigateRight, "ArrowRight"),
+        (KeyAction::LaunchGame, "Enter"),
+        (KeyAction::Back, "Escape"),
+        (KeyAction::Search, "/"),
+        (KeyAction::ToggleFavorite, "f"),
+        (KeyAction::NextPage, "PageDown"),
+        (KeyAction::PreviousPage, "PageUp"),
+        (KeyAction::OpenSettings, ","),
+    ]
+    .into_iter()
+    .map(|(action, key)| (action, key.to_string()))
+    .collect()
+}
+
+/// Orderings offered for paging through the library
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameSort {
+    /// Title exactly as stored
+    Title,
+    /// Title ignoring a leading "The", "A" or "An"
+    #[default]
+    SortTitle,
+    /// Most recently played first
+    LastPlayed,
+    /// Most recently added first
+    DateAdded,
+}
+
+/// Bucket size for playtime reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    /// Weeks starting on Monday, labelled by that Monday's date (YYYY-MM-DD)
+    Week,
+    /// Calendar months, labelled YYYY-MM
+    Month,
+}
+
+/// Consecutive days with at least one play session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayStreak {
+    /// Run ending today, or yesterday if nothing has been played yet today
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+}
+
+/// A game with play time counted over some window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GamePlayTime {
+    pub game: Game,
+    pub play_time_seconds: i64,
 }
 
 /// Input for creating a new game
@@ -150,6 +370,102 @@ pub struct CreateGameInput {
     pub description: Option<String>,
 }
 
+/// Pre-flight check of a ROM path before adding it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RomPathValidation {
+    pub exists: bool,
+    pub readable: bool,
+    /// Platform whose file extensions include the ROM's, even if the file is missing
+    pub detected_platform: Option<String>,
+}
+
+/// A game whose filename and ROM header disagree about its title
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MislabeledRom {
+    pub game_id: String,
+    /// Cleaned title from the ROM's filename
+    pub file_title: String,
+    pub internal_title: String,
+    /// 0.0 (nothing in common) to 1.0 (same after normalisation)
+    pub similarity: f64,
+}
+
+/// Outcome of one entry in a batch add
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddGameResult {
+    pub rom_path: String,
+    /// The new game, or None when the ROM was already in the library
+    pub game: Option<Game>,
+    pub skipped: bool,
+}
+
+/// Where scrapes get metadata from; see the `scraper_preference` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataProvider {
+    #[default]
+    Igdb,
+    /// Derived from the ROM's filename, no network needed
+    Offline,
+}
+
+/// A search match from any metadata provider. Matches for the same game from several
+/// providers are merged, keeping the first provider's details and filling gaps from the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSearchResult {
+    pub name: String,
+    pub release_date: Option<String>,
+    pub cover_url: Option<String>,
+    pub platforms: Vec<String>,
+    pub summary: Option<String>,
+    /// Set when IGDB matched, for passing to `scrape_game_metadata`
+    pub igdb_id: Option<u64>,
+    /// Providers that returned this game
+    pub sources: Vec<MetadataProvider>,
+}
+
+/// Metadata fields that scrapes fill and `clear_metadata` can wipe, named like their columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrapeField {
+    CoverArtPath,
+    BackgroundPath,
+    Screenshots,
+    Description,
+    ReleaseDate,
+    Genre,
+    Developer,
+    Publisher,
+}
+
+impl ScrapeField {
+    /// Column in the games table holding this field
+    pub fn column(&self) -> &'static str {
+        match self {
+            ScrapeField::CoverArtPath => "cover_art_path",
+            ScrapeField::BackgroundPath => "background_path",
+            ScrapeField::Screenshots => "screenshots",
+            ScrapeField::Description => "description",
+            ScrapeField::ReleaseDate => "release_date",
+            ScrapeField::Genre => "genre",
+            ScrapeField::Developer => "developer",
+            ScrapeField::Publisher => "publisher",
+        }
+    }
+
+    /// SQL value for the field once cleared; list fields are stored as JSON arrays
+    pub fn cleared_value(&self) -> &'static str {
+        match self {
+            ScrapeField::Screenshots | ScrapeField::Genre => "'[]'",
+            _ => "NULL",
+        }
+    }
+}
+
 /// Input for updating a game
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -166,6 +482,59 @@ pub struct UpdateGameInput {
     pub publisher: Option<String>,
     pub is_favorite: Option<bool>,
     pub preferred_emulator_id: Option<String>,
+    pub is_completed: Option<bool>,
+    pub collection_ids: Option<Vec<String>>,
+    pub last_played: Option<String>,
+}
+
+impl UpdateGameInput {
+    /// Metadata fields set by this update, named like their columns. Library state such as
+    /// favourites and play history isn't included.
+    pub fn metadata_fields(&self) -> Vec<&'static str> {
+        [
+            ("title", self.title.is_some()),
+            ("cover_art_path", self.cover_art_path.is_some()),
+            ("background_path", self.background_path.is_some()),
+            ("screenshots", self.screenshots.is_some()),
+            ("description", self.description.is_some()),
+            ("release_date", self.release_date.is_some()),
+            ("genre", self.genre.is_some()),
+            ("developer", self.developer.is_some()),
+            ("publisher", self.publisher.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(field, _)| field)
+        .collect()
+    }
+
+    /// This update without the named metadata fields
+    pub fn without_fields(mut self, fields: &[String]) -> Self {
+        for field in fields {
+            match field.as_str() {
+                "title" => self.title = None,
+                "cover_art_path" => self.cover_art_path = None,
+                "background_path" => self.background_path = None,
+                "screenshots" => self.screenshots = None,
+                "description" => self.description = None,
+                "release_date" => self.release_date = None,
+                "genre" => self.genre = None,
+                "developer" => self.developer = None,
+                "publisher" => self.publisher = None,
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+/// Input for adding a personal achievement
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAchievementInput {
+    pub game_id: String,
+    pub title: String,
+    pub description: Option<String>,
 }
 
 /// Input for creating an emulator
@@ -176,6 +545,13 @@ pub struct CreateEmulatorInput {
     pub executable_path: String,
     pub launch_arguments: Option<String>,
     pub supported_platform_ids: Vec<String>,
+    pub capture_logs: Option<bool>,
+    pub fullscreen_argument: Option<String>,
+    pub launch_type: Option<LaunchType>,
+    pub wrapper_command: Option<String>,
+    pub preferred_display: Option<u32>,
+    pub display_argument: Option<String>,
+    pub resolution_argument_template: Option<String>,
 }
 
 /// Input for updating an emulator
@@ -186,6 +562,165 @@ pub struct UpdateEmulatorInput {
     pub executable_path: Option<String>,
     pub launch_arguments: Option<String>,
     pub supported_platform_ids: Option<Vec<String>>,
+    pub capture_logs: Option<bool>,
+    pub fullscreen_argument: Option<String>,
+    pub launch_type: Option<LaunchType>,
+    pub wrapper_command: Option<String>,
+    /// Absent leaves the display alone; null clears it
+    #[serde(default, deserialize_with = "deserialize_clearable")]
+    pub preferred_display: Option<Option<u32>>,
+    pub display_argument: Option<String>,
+    pub resolution_argument_template: Option<String>,
+}
+
+/// For update fields that can be cleared: a present value, null included, becomes `Some`,
+/// so it can be told apart from a field left out (`None`, via `#[serde(default)]`)
+fn deserialize_clearable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A collection whose games are chosen live by a rule instead of by hand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartCollection {
+    pub id: String,
+    pub name: String,
+    pub rule: SmartCollectionRule,
+}
+
+impl SmartCollection {
+    pub fn new(name: String, rule: SmartCollectionRule) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            rule,
+        }
+    }
+}
+
+/// An ordered list of games, e.g., a series marathon. Unlike collections, order matters
+/// and a game can appear more than once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Playlist {
+    pub id: String,
+    pub name: String,
+    pub game_ids: Vec<String>,
+}
+
+impl Playlist {
+    pub fn new(name: String, game_ids: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            game_ids,
+        }
+    }
+}
+
+/// Conditions a game must meet to appear in a smart collection.
+/// Unset conditions match every game.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartCollectionRule {
+    /// Game's platform must be one of these
+    pub platform_ids: Option<Vec<String>>,
+    /// One of the game's genres must contain this text (case-insensitive)
+    pub genre_contains: Option<String>,
+    pub is_favorite: Option<bool>,
+    /// Game must have been played for less than this many seconds
+    pub max_play_time_seconds: Option<i64>,
+}
+
+impl SmartCollectionRule {
+    pub fn matches(&self, game: &Game) -> bool {
+        if let Some(platform_ids) = &self.platform_ids {
+            if !platform_ids.contains(&game.platform_id) {
+                return false;
+            }
+        }
+        if let Some(genre) = &self.genre_contains {
+            let genre = genre.to_lowercase();
+            if !game.genre.iter().any(|g| g.to_lowercase().contains(&genre)) {
+                return false;
+            }
+        }
+        if let Some(is_favorite) = self.is_favorite {
+            if game.is_favorite != is_favorite {
+                return false;
+            }
+        }
+        if let Some(max_play_time) = self.max_play_time_seconds {
+            if game.total_play_time_seconds >= max_play_time {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A randomly picked game and the outcome of launching it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomLaunchResult {
+    pub game: Game,
+    pub launch_result: LaunchResult,
+}
+
+/// Built-in collections whose membership is derived from library data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VirtualCollectionKind {
+    Favorites,
+    RecentlyPlayed,
+    MostPlayed,
+    NeverPlayed,
+}
+
+/// Aggregates over a collection's member games
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionStats {
+    pub game_count: i64,
+    pub total_play_time_seconds: i64,
+    pub completed_count: i64,
+    pub platforms: Vec<String>,
+}
+
+/// Input for creating a custom platform
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePlatformInput {
+    pub id: String,
+    pub display_name: String,
+    pub manufacturer: String,
+    pub file_extensions: Vec<String>,
+    pub color: Option<String>,
+}
+
+/// Input for updating a platform
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePlatformInput {
+    pub display_name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub file_extensions: Option<Vec<String>>,
+    pub color: Option<String>,
+}
+
+/// Outcome of a delete_platform request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePlatformResult {
+    pub deleted: bool,
+    /// Games still on the platform that prevented deletion
+    pub blocking_game_count: u32,
+    /// Games moved to the reassignment target before deleting
+    pub reassigned_count: u32,
 }
 
 /// Input for creating a collection
@@ -204,6 +739,40 @@ pub struct UpdateCollectionInput {
     pub cover_game_id: Option<String>,
 }
 
+/// Input for creating a smart collection
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSmartCollectionInput {
+    pub name: String,
+    pub rule: SmartCollectionRule,
+}
+
+/// Input for updating a smart collection
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSmartCollectionInput {
+    pub name: Option<String>,
+    pub rule: Option<SmartCollectionRule>,
+}
+
+/// Input for creating a playlist
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePlaylistInput {
+    pub name: String,
+    #[serde(default)]
+    pub game_ids: Vec<String>,
+}
+
+/// Input for updating a playlist
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePlaylistInput {
+    pub name: Option<String>,
+    /// Replaces the whole list, in order
+    pub game_ids: Option<Vec<String>>,
+}
+
 /// Scan result from library scanning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -213,3 +782,172 @@ pub struct ScanResult {
     pub games_updated: i32,
     pub errors: Vec<String>,
 }
+
+/// A connected monitor, for choosing where emulators open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayInfo {
+    pub index: u32,
+    pub name: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// Top-left corner in the desktop's coordinate space
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+/// Kind of machine the app is running on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeviceKind {
+    SteamDeck,
+    /// Another handheld PC, such as a ROG Ally or Legion Go
+    Handheld,
+    Desktop,
+}
+
+/// The user's choice of layout, stored in the `ui_mode` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiMode {
+    /// Controller-first on handhelds, desktop layout elsewhere
+    #[default]
+    Auto,
+    Desktop,
+    Controller,
+}
+
+/// What the frontend needs to pick a layout for this device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    pub device: DeviceKind,
+    /// e.g. "Steam Deck" or "ROG Ally"; None on desktops
+    pub device_name: Option<String>,
+    pub platform: String,
+    pub os_version: String,
+    pub ui_mode: UiMode,
+    /// Whether to use the controller-first layout, after applying `ui_mode`
+    pub controller_first: bool,
+}
+
+/// Result of re-encoding stored artwork as WebP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageConversionResult {
+    pub images_converted: u32,
+    pub bytes_saved: u64,
+    pub errors: Vec<String>,
+}
+
+/// A game image that exists on disk but won't display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorruptImage {
+    pub game_id: String,
+    /// cover_art_path, background_path or screenshots
+    pub field: ScrapeField,
+    pub path: String,
+    pub reason: String,
+}
+
+/// Result of re-downloading missing covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverRepairResult {
+    pub covers_restored: u32,
+    pub errors: Vec<String>,
+}
+
+/// Result of collapsing identical cover images into shared files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverDedupResult {
+    pub files_removed: u32,
+    /// Games now pointing at a shared cover file
+    pub games_updated: u32,
+    pub bytes_saved: u64,
+}
+
+/// Result of importing play sessions from a CSV file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySessionImportResult {
+    pub sessions_imported: u32,
+    /// Rows for a session the game already has, e.g. from importing the same file twice
+    pub duplicates_skipped: u32,
+    /// Rows whose game isn't in the library, or that couldn't be read
+    pub unmatched_rows: Vec<String>,
+}
+
+/// Database file size before and after running maintenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeResult {
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Result of pushing to or pulling from the sync remote
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    pub images_transferred: u32,
+    /// Images already identical on both sides
+    pub images_skipped: u32,
+}
+
+/// Disk space used under the app data directory, in bytes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub database_bytes: u64,
+    /// Images subfolder (covers, screenshots, thumbnails, ...) -> size. Backgrounds are
+    /// saved alongside covers; files directly in `images/` count under "other".
+    pub image_folders: std::collections::BTreeMap<String, u64>,
+    pub logs_bytes: u64,
+    pub backups_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Byte order of an N64 ROM dump, named after the extension it conventionally uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum N64ByteOrder {
+    /// Native order (.z64)
+    BigEndian,
+    /// Each 16-bit pair swapped (.v64)
+    ByteSwapped,
+    /// Each 32-bit word reversed (.n64)
+    LittleEndian,
+}
+
+impl N64ByteOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            N64ByteOrder::BigEndian => "big-endian",
+            N64ByteOrder::ByteSwapped => "byte-swapped",
+            N64ByteOrder::LittleEndian => "little-endian",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "big-endian" => Some(N64ByteOrder::BigEndian),
+            "byte-swapped" => Some(N64ByteOrder::ByteSwapped),
+            "little-endian" => Some(N64ByteOrder::LittleEndian),
+            _ => None,
+        }
+    }
+
+    /// Extension conventionally used for dumps in this order
+    pub fn extension(&self) -> &'static str {
+        match self {
+            N64ByteOrder::BigEndian => "z64",
+            N64ByteOrder::ByteSwapped => "v64",
+            N64ByteOrder::LittleEndian => "n64",
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use discord_rich_presence::activity::{Activity, Assets, Timestamps};
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+use std::sync::Mutex;
+
+use crate::models::Game;
+
+/// Asset key used for the large image when a game's cover can't be shown by Discord
+const DEFAULT_LARGE_IMAGE: &str = "retrovoid";
+
+/// What to show on the user's Discord profile while a game is running
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresencePayload {
+    pub details: String,
+    pub state: String,
+    pub large_image: String,
+    pub large_text: String,
+    /// Unix time in milliseconds
+    pub start_timestamp: i64,
+}
+
+/// Build the presence shown for a game: "Playing <title>" / "on <platform>", with the
+/// cover as the large image. Discord can only display remote images, so covers stored
+/// on disk fall back to the app's own asset.
+pub fn build_presence(game: &Game, platform_name: &str, started_at: chrono::DateTime<chrono::Utc>) -> PresencePayload {
+    let large_image = game.cover_art_path.as_deref()
+        .filter(|path| path.starts_with("https://") || path.starts_with("http://"))
+        .unwrap_or(DEFAULT_LARGE_IMAGE)
+        .to_string();
+
+    PresencePayload {
+        details: format!("Playing {}", game.title),
+        state: format!("on {}", platform_name),
+        large_image,
+        large_text: game.title.clone(),
+        start_timestamp: started_at.timestamp_millis(),
+    }
+}
+
+/// Connection to the local Discord client, opened on first use.
+///
+/// Every call is best-effort: if Discord isn't running or the connection drops,
+/// the error is logged and presence is skipped.
+#[derive(Default)]
+pub struct DiscordPresence {
+    client: Mutex<Option<(String, DiscordIpcClient)>>,
+}
+
+impl DiscordPresence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show a game's presence using the given Discord application ID
+    pub fn set(&self, application_id: &str, payload: &PresencePayload) {
+        let activity = Activity::new()
+            .details(payload.details.as_str())
+            .state(payload.state.as_str())
+            .assets(Assets::new()
+                .large_image(payload.large_image.as_str())
+                .large_text(payload.large_text.as_str()))
+            .timestamps(Timestamps::new().start(payload.start_timestamp));
+
+        let mut client = self.client.lock().unwrap();
+
+        // Reconnect if the application ID changed since the last connection
+        if client.as_ref().is_some_and(|(id, _)| id != application_id) {
+            if let Some((_, mut old)) = client.take() {
+                let _ = old.close();
+            }
+        }
+
+        if client.is_none() {
+            let mut ipc = DiscordIpcClient::new(application_id);
+            if let Err(e) = ipc.connect() {
+                eprintln!("Discord presence unavailable: {}", e);
+                return;
+            }
+            *client = Some((application_id.to_string(), ipc));
+        }
+
+        if let Some((_, ipc)) = client.as_mut() {
+            if let Err(e) = ipc.set_activity(activity) {
+                eprintln!("Failed to set Discord presence: {}", e);
+                // Drop the connection so the next launch tries again
+                *client = None;
+            }
+        }
+    }
+
+    /// Clear any presence previously set. Does nothing if Discord was never reached.
+    pub fn clear(&self) {
+        let mut client = self.client.lock().unwrap();
+        if let Some((_, ipc)) = client.as_mut() {
+            if let Err(e) = ipc.clear_activity() {
+                eprintln!("Failed to clear Discord presence: {}", e);
+                *client = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_presence() {
+        let mut game = Game::new("Chrono Trigger".to_string(), "/roms/ct.sfc".to_string(), "snes".to_string());
+        let started_at = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        let payload = build_presence(&game, "Super Nintendo", started_at);
+        assert_eq!(payload, PresencePayload {
+            details: "Playing Chrono Trigger".to_string(),
+            state: "on Super Nintendo".to_string(),
+            large_image: "retrovoid".to_string(),
+            large_text: "Chrono Trigger".to_string(),
+            start_timestamp: 1_704_110_400_000,
+        });
+
+        // Local cover files can't be shown by Discord
+        game.cover_art_path = Some("/covers/ct.png".to_string());
+        assert_eq!(build_presence(&game, "Super Nintendo", started_at).large_image, "retrovoid");
+
+        game.cover_art_path = Some("https://images.igdb.com/ct.jpg".to_string());
+        assert_eq!(build_presence(&game, "Super Nintendo", started_at).large_image, "https://images.igdb.com/ct.jpg");
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{Game, PlaySession, Platform};
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;background:#111;color:#eee;margin:2rem}\
+h1{font-weight:300}h2{border-bottom:1px solid #333;padding-bottom:.25rem}\
+.games{display:flex;flex-wrap:wrap;gap:1rem}\
+.game{width:160px}.game img,.game .blank{width:160px;height:213px;object-fit:cover;background:#222;border-radius:4px}\
+.title{font-weight:600;margin-top:.25rem}.meta{color:#999;font-size:.85rem}";
+
+/// Render a self-contained HTML page of the library, grouped by platform.
+///
+/// `covers` maps game IDs to image URIs (usually `data:` URIs) so the page needs no other
+/// files; games without an entry get a blank tile.
+pub fn render_library_html(games: &[Game], platforms: &[Platform], covers: &HashMap<String, String>) -> String {
+    let platform_names: HashMap<&str, &str> = platforms.iter()
+        .map(|platform| (platform.id.as_str(), platform.display_name.as_str()))
+        .collect();
+
+    let mut by_platform: BTreeMap<&str, Vec<&Game>> = BTreeMap::new();
+    for game in games {
+        let name = platform_names.get(game.platform_id.as_str()).copied().unwrap_or(&game.platform_id);
+        by_platform.entry(name).or_default().push(game);
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>RetroVoid Library</title>\n");
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str(&format!("<h1>RetroVoid Library</h1>\n<p class=\"meta\">{} games</p>\n", games.len()));
+
+    for (platform, mut games) in by_platform {
+        games.sort_by_key(|game| game.title.to_lowercase());
+        html.push_str(&format!("<h2>{} <span class=\"meta\">({})</span></h2>\n<div class=\"games\">\n", escape(platform), games.len()));
+
+        for game in games {
+            html.push_str("<div class=\"game\">");
+            match covers.get(&game.id) {
+                Some(uri) => html.push_str(&format!("<img src=\"{}\" alt=\"\">", escape(uri))),
+                None => html.push_str("<div class=\"blank\"></div>"),
+            }
+            let favorite = if game.is_favorite { "&#9733; " } else { "" };
+            html.push_str(&format!("<div class=\"title\">{}{}</div>", favorite, escape(&game.title)));
+            if game.total_play_time_seconds > 0 {
+                html.push_str(&format!("<div class=\"meta\">{}</div>", format_play_time(game.total_play_time_seconds)));
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render play sessions as CSV, one row per session with its game's title.
+/// Sessions still running have an empty end time.
+pub fn render_play_sessions_csv(sessions: &[(String, PlaySession)]) -> String {
+    let mut csv = String::from("title,start_time,end_time,duration_seconds\r\n");
+    for (title, session) in sessions {
+        csv.push_str(&format!(
+            "{},{},{},{}\r\n",
+            csv_field(title),
+            csv_field(&session.start_time),
+            csv_field(session.end_time.as_deref().unwrap_or("")),
+            session.duration_seconds,
+        ));
+    }
+    csv
+}
+
+/// A row read back from a play session CSV
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvPlaySession {
+    /// Line number of the row in the file, for error messages
+    pub line: usize,
+    pub title: String,
+    /// Matched before the title when the file has a rom_path column
+    pub rom_path: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub duration_seconds: i64,
+}
+
+/// Read play sessions in the format written by `render_play_sessions_csv`. Columns are found by
+/// header name, so extra columns (such as rom_path) and reordering are fine.
+/// Rows that can't be read are returned as messages alongside the sessions that could.
+pub fn parse_play_sessions_csv(text: &str) -> Result<(Vec<CsvPlaySession>, Vec<String>), String> {
+    let mut records = parse_csv(text).into_iter();
+    let (_, header) = records.next().ok_or("CSV file is empty")?;
+    let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+
+    let title_col = column("title").ok_or("CSV file has no title column")?;
+    let start_col = column("start_time").ok_or("CSV file has no start_time column")?;
+    let duration_col = column("duration_seconds").ok_or("CSV file has no duration_seconds column")?;
+    let end_col = column("end_time");
+    let rom_path_col = column("rom_path");
+
+    let mut sessions = Vec::new();
+    let mut invalid = Vec::new();
+    for (line, record) in records {
+        let field = |col: Option<usize>| {
+            col.and_then(|c| record.get(c)).map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+        let title = field(Some(title_col)).unwrap_or_default();
+
+        let Some(start_time) = field(Some(start_col)) else {
+            invalid.push(format!("Line {}: {} has no start time", line, title));
+            continue;
+        };
+        let Some(duration_seconds) = field(Some(duration_col)).and_then(|d| d.parse::<i64>().ok()).filter(|d| *d >= 0) else {
+            invalid.push(format!("Line {}: {} has an invalid duration", line, title));
+            continue;
+        };
+
+        sessions.push(CsvPlaySession {
+            line,
+            rom_path: field(rom_path_col),
+            end_time: field(end_col),
+            title,
+            start_time,
+            duration_seconds,
+        });
+    }
+
+    Ok((sessions, invalid))
+}
+
+/// Split CSV text into records, each with the line it starts on. Handles quoted fields
+/// containing separators, doubled quotes and line breaks; blank lines are skipped.
+fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push((record_line, std::mem::take(&mut record)));
+                } else {
+                    record.clear();
+                }
+                line += 1;
+                record_line = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push((record_line, record));
+    }
+    records
+}
+
+/// Play time as "3h 25m", or minutes alone under an hour
+fn format_play_time(seconds: i64) -> String {
+    let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes.max(1))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break, doubling inner quotes
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_lists_every_game() {
+        let mut zelda = Game::new("The Legend of Zelda".to_string(), "/roms/zelda.nes".to_string(), "nes".to_string());
+        zelda.is_favorite = true;
+        zelda.total_play_time_seconds = 3 * 3600 + 25 * 60;
+        let games = vec![
+            zelda,
+            Game::new("Sonic & Knuckles".to_string(), "/roms/sk.md".to_string(), "genesis".to_string()),
+        ];
+        let covers = HashMap::from([(games[0].id.clone(), "data:image/jpeg;base64,AAAA".to_string())]);
+
+        let html = render_library_html(&games, &[], &covers);
+        assert!(html.contains("The Legend of Zelda"));
+        assert!(html.contains("Sonic &amp; Knuckles"));
+        assert!(html.contains("3h 25m"));
+        assert!(html.contains("&#9733; The Legend of Zelda"));
+        assert!(html.contains("src=\"data:image/jpeg;base64,AAAA\""));
+    }
+
+    #[test]
+    fn test_play_sessions_csv() {
+        let session = |title: &str, duration_seconds| (title.to_string(), PlaySession {
+            id: "s".to_string(),
+            game_id: "g".to_string(),
+            start_time: "2024-01-01T10:00:00+00:00".to_string(),
+            end_time: Some("2024-01-01T11:00:00+00:00".to_string()),
+            duration_seconds,
+            note: None,
+            gross_duration_seconds: None,
+        });
+
+        let csv = render_play_sessions_csv(&[session("Metroid", 3600), session("Zelda, \"Link\"", 90)]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "title,start_time,end_time,duration_seconds");
+        assert_eq!(lines[1], "Metroid,2024-01-01T10:00:00+00:00,2024-01-01T11:00:00+00:00,3600");
+        assert!(lines[2].starts_with("\"Zelda, \"\"Link\"\"\","));
+        assert!(lines[2].ends_with(",90"));
+    }
+
+    #[test]
+    fn test_parse_play_sessions_csv_round_trip() {
+        let session = PlaySession {
+            id: "s".to_string(),
+            game_id: "g".to_string(),
+            start_time: "2024-01-01T10:00:00+00:00".to_string(),
+            end_time: None,
+            duration_seconds: 90,
+            note: None,
+            gross_duration_seconds: None,
+        };
+        let mut csv = render_play_sessions_csv(&[("Zelda, \"Link\"".to_string(), session)]);
+        csv.push_str("\r\nMetroid,2024-01-02T10:00:00+00:00,,soon\r\n");
+
+        let (sessions, invalid) = parse_play_sessions_csv(&csv).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title, "Zelda, \"Link\"");
+        assert_eq!(sessions[0].end_time, None);
+        assert_eq!(sessions[0].duration_seconds, 90);
+        assert_eq!(invalid, vec!["Line 4: Metroid has an invalid duration".to_string()]);
+
+        assert!(parse_play_sessions_csv("name,when\r\n").is_err());
+    }
+}
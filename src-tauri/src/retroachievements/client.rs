@@ -0,0 +1,312 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How many achievements a game has and how many the user has earned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementSummary {
+    pub total: u32,
+    pub earned: u32,
+}
+
+/// A single achievement and whether the user has earned it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Achievement {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub points: u32,
+    pub earned: bool,
+}
+
+/// Response from the hash lookup endpoint
+#[derive(Debug, Deserialize)]
+struct GameIdResponse {
+    #[serde(rename = "Success")]
+    success: bool,
+    #[serde(rename = "GameID", default)]
+    game_id: u64,
+}
+
+/// Entry in API_GetGameList
+#[derive(Debug, Deserialize)]
+struct GameListEntry {
+    #[serde(rename = "ID")]
+    id: u64,
+    #[serde(rename = "Title")]
+    title: String,
+}
+
+/// Subset of API_GetGameInfoAndUserProgress
+#[derive(Debug, Deserialize)]
+struct GameProgressResponse {
+    #[serde(rename = "NumAchievements", default)]
+    num_achievements: u32,
+    #[serde(rename = "NumAwardedToUser", default)]
+    num_awarded_to_user: u32,
+    /// Keyed by achievement ID; the API returns `[]` instead of `{}` for games without any
+    #[serde(rename = "Achievements", default, deserialize_with = "deserialize_achievements")]
+    achievements: HashMap<String, AchievementEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AchievementEntry {
+    #[serde(rename = "ID")]
+    id: u64,
+    #[serde(rename = "Title")]
+    title: String,
+    #[serde(rename = "Description", default)]
+    description: String,
+    #[serde(rename = "Points", default)]
+    points: u32,
+    #[serde(rename = "DisplayOrder", default)]
+    display_order: i64,
+    #[serde(rename = "DateEarned", default)]
+    date_earned: Option<String>,
+}
+
+fn deserialize_achievements<'de, D>(deserializer: D) -> Result<HashMap<String, AchievementEntry>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MapOrList {
+        Map(HashMap<String, AchievementEntry>),
+        List(Vec<AchievementEntry>),
+    }
+
+    Ok(match MapOrList::deserialize(deserializer)? {
+        MapOrList::Map(map) => map,
+        MapOrList::List(list) => list.into_iter().map(|a| (a.id.to_string(), a)).collect(),
+    })
+}
+
+/// Map our platform ID to a RetroAchievements console ID
+pub fn get_ra_console_id(platform_id: &str) -> Option<u32> {
+    let mapping: HashMap<&str, u32> = [
+        ("genesis", 1),
+        ("n64", 2),
+        ("snes", 3),
+        ("gb", 4),
+        ("gba", 5),
+        ("gbc", 6),
+        ("nes", 7),
+        ("pcengine", 8),
+        ("segacd", 9),
+        ("sega32x", 10),
+        ("mastersystem", 11),
+        ("ps1", 12),
+        ("gamegear", 15),
+        ("gamecube", 16),
+        ("atarijaguar", 17),
+        ("nds", 18),
+        ("ps2", 21),
+        ("atari2600", 25),
+        ("arcade", 27),
+        ("virtualboy", 28),
+        ("saturn", 39),
+        ("dreamcast", 40),
+        ("psp", 41),
+        ("cdi", 42),
+        ("3do", 43),
+        ("atari7800", 51),
+        ("pcenginecd", 76),
+        ("atarijaguarcd", 77),
+    ].into_iter().collect();
+
+    mapping.get(platform_id).copied()
+}
+
+/// Convert a progress response into a list of achievements in display order
+fn achievements_from_progress(progress: GameProgressResponse) -> Vec<Achievement> {
+    let mut entries: Vec<AchievementEntry> = progress.achievements.into_values().collect();
+    entries.sort_by_key(|a| (a.display_order, a.id));
+
+    entries.into_iter()
+        .map(|a| Achievement {
+            id: a.id,
+            title: a.title,
+            description: a.description,
+            points: a.points,
+            earned: a.date_earned.is_some(),
+        })
+        .collect()
+}
+
+/// RetroAchievements web API client
+pub struct RaClient {
+    client: Client,
+    username: String,
+    api_key: String,
+}
+
+impl RaClient {
+    /// Create a new RetroAchievements client
+    pub fn new(username: String, api_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            username,
+            api_key,
+        }
+    }
+
+    /// Check the username and API key by requesting the user's profile
+    pub async fn validate_credentials(&self) -> Result<bool, String> {
+        let response = self.client
+            .get("https://retroachievements.org/API/API_GetUserProfile.php")
+            .query(&[("y", self.api_key.as_str()), ("u", self.username.as_str())])
+            .send()
+            .await
+            .map_err(|e| format!("RetroAchievements request failed: {}", e))?;
+
+        Ok(response.status().is_success())
+    }
+
+    /// Look up the RetroAchievements game ID for a ROM hash. Returns `None` if the hash is unknown.
+    pub async fn resolve_game_id(&self, hash: &str) -> Result<Option<u64>, String> {
+        let response = self.client
+            .get("https://retroachievements.org/dorequest.php")
+            .query(&[("r", "gameid"), ("m", hash)])
+            .send()
+            .await
+            .map_err(|e| format!("RetroAchievements request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("RetroAchievements hash lookup failed: {}", response.status()));
+        }
+
+        let lookup: GameIdResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse RetroAchievements response: {}", e))?;
+
+        Ok((lookup.success && lookup.game_id != 0).then_some(lookup.game_id))
+    }
+
+    /// Find a game by exact title (case-insensitive) in a console's list of games with achievements.
+    /// Used when a ROM can't be hashed, e.g. disc-based systems.
+    pub async fn find_game_by_title(&self, console_id: u32, title: &str) -> Result<Option<u64>, String> {
+        let response = self.client
+            .get("https://retroachievements.org/API/API_GetGameList.php")
+            .query(&[
+                ("y", self.api_key.as_str()),
+                ("i", console_id.to_string().as_str()),
+                ("f", "1"),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("RetroAchievements request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("RetroAchievements game list request failed: {}", response.status()));
+        }
+
+        let games: Vec<GameListEntry> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse RetroAchievements response: {}", e))?;
+
+        Ok(games.into_iter().find(|g| g.title.eq_ignore_ascii_case(title)).map(|g| g.id))
+    }
+
+    async fn get_progress(&self, ra_game_id: u64) -> Result<GameProgressResponse, String> {
+        let response = self.client
+            .get("https://retroachievements.org/API/API_GetGameInfoAndUserProgress.php")
+            .query(&[
+                ("y", self.api_key.as_str()),
+                ("u", self.username.as_str()),
+                ("g", ra_game_id.to_string().as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("RetroAchievements request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("RetroAchievements progress request failed: {}", response.status()));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse RetroAchievements response: {}", e))
+    }
+
+    /// Get the achievement count and the user's earned count for a game
+    pub async fn get_summary(&self, ra_game_id: u64) -> Result<AchievementSummary, String> {
+        let progress = self.get_progress(ra_game_id).await?;
+
+        Ok(AchievementSummary {
+            total: progress.num_achievements,
+            earned: progress.num_awarded_to_user,
+        })
+    }
+
+    /// Get every achievement for a game along with whether the user has earned it
+    pub async fn get_achievements(&self, ra_game_id: u64) -> Result<Vec<Achievement>, String> {
+        let progress = self.get_progress(ra_game_id).await?;
+        Ok(achievements_from_progress(progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_id_mapping() {
+        assert_eq!(get_ra_console_id("nes"), Some(7));
+        assert_eq!(get_ra_console_id("snes"), Some(3));
+        assert_eq!(get_ra_console_id("ps1"), Some(12));
+        assert_eq!(get_ra_console_id("switch"), None);
+    }
+
+    #[test]
+    fn test_parse_achievements_response() {
+        let json = r#"{
+            "ID": 1446,
+            "Title": "Super Mario Bros.",
+            "ConsoleID": 7,
+            "NumAchievements": 2,
+            "NumAwardedToUser": 1,
+            "Achievements": {
+                "4011": {
+                    "ID": 4011, "Title": "Warp Zone", "Description": "Use a warp pipe",
+                    "Points": 10, "DisplayOrder": 2
+                },
+                "4010": {
+                    "ID": 4010, "Title": "World 1-1", "Description": "Clear World 1-1",
+                    "Points": 5, "DisplayOrder": 1, "DateEarned": "2024-01-01 12:00:00"
+                }
+            }
+        }"#;
+
+        let progress: GameProgressResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(progress.num_achievements, 2);
+        assert_eq!(progress.num_awarded_to_user, 1);
+
+        let achievements = achievements_from_progress(progress);
+        assert_eq!(achievements, vec![
+            Achievement {
+                id: 4010,
+                title: "World 1-1".to_string(),
+                description: "Clear World 1-1".to_string(),
+                points: 5,
+                earned: true,
+            },
+            Achievement {
+                id: 4011,
+                title: "Warp Zone".to_string(),
+                description: "Use a warp pipe".to_string(),
+                points: 10,
+                earned: false,
+            },
+        ]);
+
+        // Games without achievements come back with an empty list instead of an object
+        let empty: GameProgressResponse = serde_json::from_str(r#"{"Achievements": []}"#).unwrap();
+        assert!(achievements_from_progress(empty).is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use crate::signatures;
+
+/// Size of the iNES header that RetroAchievements strips before hashing NES ROMs
+const INES_HEADER_LEN: u64 = 16;
+
+/// Platforms whose RetroAchievements hash is the MD5 of the whole file
+const WHOLE_FILE_PLATFORMS: &[&str] = &[
+    "gb", "gbc", "gba", "virtualboy", "genesis", "sega32x", "mastersystem", "gamegear",
+    "pcengine", "atari2600", "atari7800", "atarijaguar",
+];
+
+/// How many leading bytes RetroAchievements leaves out of the hash, given the ROM's length
+/// and first bytes. Returns `None` for platforms whose rules aren't supported yet.
+fn hash_skip(platform_id: &str, start: &[u8], len: u64) -> Option<u64> {
+    match platform_id {
+        "nes" if len > INES_HEADER_LEN && start.starts_with(b"NES\x1a") => Some(INES_HEADER_LEN),
+        "snes" if signatures::is_smc_headered_len(len) => Some(signatures::SMC_HEADER_LEN),
+        "nes" | "snes" => Some(0),
+        id if WHOLE_FILE_PLATFORMS.contains(&id) => Some(0),
+        _ => None,
+    }
+}
+
+/// Compute the RetroAchievements hash for a ROM file, reading it in chunks.
+///
+/// RetroAchievements identifies games by an MD5 with per-system rules (for example,
+/// NES ROMs are hashed without their iNES header). Returns `None` for platforms whose
+/// rules aren't supported yet, such as disc-based systems.
+pub fn hash_rom_file(platform_id: &str, path: &Path) -> Result<Option<String>, String> {
+    let (header, len) = signatures::read_header(path).map_err(|e| format!("Failed to read ROM: {}", e))?;
+    let Some(skip) = hash_skip(platform_id, &header, len) else {
+        return Ok(None);
+    };
+
+    file_md5(path, skip)
+        .map(Some)
+        .map_err(|e| format!("Failed to read ROM: {}", e))
+}
+
+/// MD5 of a file as lowercase hex, ignoring the first `skip` bytes. Read in chunks so large
+/// disc images aren't loaded whole.
+pub fn file_md5(path: &Path, skip: u64) -> std::io::Result<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(skip))?;
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.consume(&buffer[..read]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_rom(platform_id: &str, data: &[u8]) -> Option<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rom.bin");
+        std::fs::write(&path, data).unwrap();
+        hash_rom_file(platform_id, &path).unwrap()
+    }
+
+    #[test]
+    fn test_nes_hash_skips_ines_header() {
+        let prg = vec![0u8; 16384];
+        let mut rom = b"NES\x1a\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        rom.extend(&prg);
+
+        let expected = "ce338fe6899778aacfc28414f2d9498b";
+        assert_eq!(hash_rom("nes", &rom).as_deref(), Some(expected));
+        assert_eq!(hash_rom("nes", &prg).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_snes_hash_skips_copier_header() {
+        let rom = vec![0x42u8; 8192];
+        let mut headered = vec![0u8; 512];
+        headered.extend(&rom);
+
+        let expected = hash_rom("snes", &rom);
+        assert_eq!(hash_rom("snes", &headered), expected);
+        // Only sizes the shared rule calls headered are trimmed
+        assert_ne!(hash_rom("snes", &headered[..512 + 1024]), hash_rom("snes", &headered[512..512 + 1024]));
+    }
+
+    #[test]
+    fn test_unsupported_platform_has_no_hash() {
+        assert_eq!(hash_rom("ps2", b"disc"), None);
+        assert!(hash_rom_file("nes", Path::new("/no/such/rom.nes")).is_err());
+    }
+}
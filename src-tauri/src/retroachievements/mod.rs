@@ -0,0 +1,5 @@
+pub mod client;
+pub mod hash;
+
+pub use client::*;
+pub use hash::*;
@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use crate::signatures;
+
+/// Platforms whose cartridge headers carry a title we can read
+pub const TITLED_PLATFORMS: &[&str] = &["gb", "gbc", "gba", "snes", "genesis", "sega32x"];
+
+/// Read the title stored in a ROM's header. Returns None for formats without one (such as
+/// NES) and for files we don't recognise.
+pub fn read_internal_title(path: &Path) -> Option<String> {
+    let (header, file_len) = signatures::read_header(path).ok()?;
+    internal_title(&header, file_len)
+}
+
+fn internal_title(header: &[u8], file_len: u64) -> Option<String> {
+    let field = |start: usize, len: usize| header.get(start..start + len).and_then(decode_title);
+
+    match signatures::detect_from_header(header, file_len)? {
+        // Colour games give the last title byte over to the CGB flag
+        "gb" => field(0x134, 16),
+        "gbc" => field(0x134, 15),
+        "gba" => field(0xA0, 12),
+        // Prefer the overseas name, which is more often in plain ASCII than the domestic one
+        "genesis" | "sega32x" => field(0x150, 48).or_else(|| field(0x120, 48)),
+        "snes" => {
+            let base = signatures::snes_header_offset(header)?;
+            field(base, 21)
+        }
+        _ => None,
+    }
+}
+
+/// Header titles are NUL- or space-padded ASCII; anything outside printable ASCII ends the title
+fn decode_title(bytes: &[u8]) -> Option<String> {
+    let text: String = bytes
+        .iter()
+        .take_while(|&&b| b == b' ' || b.is_ascii_graphic())
+        .map(|&b| b as char)
+        .collect();
+    let title = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gb_title() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x104..0x10C].copy_from_slice(&[0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B]);
+        rom[0x134..0x134 + 11].copy_from_slice(b"POKEMON RED");
+        assert_eq!(internal_title(&rom, rom.len() as u64).as_deref(), Some("POKEMON RED"));
+
+        // On Color games 0x143 is the CGB flag, not part of the title
+        rom[0x134..0x144].copy_from_slice(b"POKEMON_CRYSTAL\xC0");
+        rom[0x143] = 0xC0;
+        assert_eq!(internal_title(&rom, rom.len() as u64).as_deref(), Some("POKEMON_CRYSTAL"));
+    }
+
+    #[test]
+    fn test_snes_title() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x7FC0..0x7FC0 + 21].copy_from_slice(b"SUPER METROID        ");
+        rom[0x7FDC..0x7FDE].copy_from_slice(&0x5A5Au16.to_le_bytes());
+        rom[0x7FDE..0x7FE0].copy_from_slice(&0xA5A5u16.to_le_bytes());
+        assert_eq!(internal_title(&rom, rom.len() as u64).as_deref(), Some("SUPER METROID"));
+
+        // No internal header means no title, rather than garbage
+        assert_eq!(internal_title(&[0u8; 0x8000], 0x8000), None);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::models::{Emulator, SaveState};
+
+/// Directory an emulator keeps save states in.
+///
+/// An entry in `overrides` (emulator id -> folder) wins; otherwise a `states` folder beside
+/// the executable is used, which is where portable RetroArch installs put them.
+pub fn save_state_dir(emulator: &Emulator, overrides: &HashMap<String, String>) -> Option<PathBuf> {
+    if let Some(dir) = overrides.get(&emulator.id) {
+        return Some(PathBuf::from(dir));
+    }
+
+    let dir = Path::new(&emulator.executable_path).parent()?.join("states");
+    dir.is_dir().then_some(dir)
+}
+
+/// Find the save states for a ROM, newest first.
+///
+/// RetroArch can sort states into a subfolder per core, so one level of subfolders is searched.
+pub fn find_save_states(dir: &Path, rom_path: &str) -> Vec<SaveState> {
+    let Some(rom_stem) = Path::new(rom_path).file_stem().and_then(|s| s.to_str()) else {
+        return Vec::new();
+    };
+
+    let mut states: Vec<SaveState> = WalkDir::new(dir)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?;
+            let slot = parse_slot(file_name, rom_stem)?;
+            let metadata = entry.metadata().ok()?;
+            let modified_at = metadata.modified().ok()
+                .map(|m| chrono::DateTime::<chrono::Utc>::from(m).to_rfc3339())
+                .unwrap_or_default();
+
+            Some(SaveState {
+                slot,
+                path: entry.path().to_string_lossy().to_string(),
+                modified_at,
+                size: metadata.len(),
+            })
+        })
+        .collect();
+
+    states.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    states
+}
+
+/// In-game save and memory card extensions (RetroArch SRAM, raw saves, PlayStation cards)
+const SAVE_FILE_EXTENSIONS: &[&str] = &["srm", "sav", "mcd", "mcr"];
+
+/// Folders to back up alongside a save-state folder: RetroArch keeps in-game saves in a
+/// `saves` folder next to `states`
+pub fn backup_dirs(state_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![state_dir.to_path_buf()];
+    if let Some(saves) = state_dir.parent().map(|p| p.join("saves")).filter(|p| p.is_dir() && p != state_dir) {
+        dirs.push(saves);
+    }
+    dirs
+}
+
+/// Zip a ROM's save states and in-game saves from `dirs` into a timestamped archive in `dest_dir`.
+///
+/// Entries are stored under the name of the folder they came from, e.g. `states/Snes9x/Game.state`,
+/// so same-named files from different folders don't collide.
+pub fn backup_save_files(dirs: &[PathBuf], rom_path: &str, dest_dir: &Path, now: chrono::DateTime<chrono::Local>) -> Result<PathBuf, String> {
+    let rom_stem = Path::new(rom_path).file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| "Invalid ROM path".to_string())?;
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for dir in dirs {
+        let folder = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        for entry in WalkDir::new(dir).max_depth(2).into_iter().filter_map(|e| e.ok()) {
+            let Some(file_name) = entry.file_name().to_str() else {
+                continue;
+            };
+            if !entry.file_type().is_file() || !(parse_slot(file_name, rom_stem).is_some() || is_save_file(file_name, rom_stem)) {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(dir) else {
+                continue;
+            };
+            let name = Path::new(&folder).join(relative).to_string_lossy().replace('\\', "/");
+            files.push((name, entry.path().to_path_buf()));
+        }
+    }
+
+    if files.is_empty() {
+        return Err("No save states found for this game".to_string());
+    }
+
+    std::fs::create_dir_all(dest_dir).map_err(|e| format!("Failed to create backup folder: {}", e))?;
+    let archive_path = dest_dir.join(format!("{} saves {}.zip", rom_stem, now.format("%Y-%m-%d_%H%M%S")));
+    let archive = std::fs::File::create(&archive_path).map_err(|e| format!("Failed to create backup: {}", e))?;
+
+    let mut zip = zip::ZipWriter::new(archive);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, path) in &files {
+        let contents = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(&contents).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    Ok(archive_path)
+}
+
+/// Whether `file_name` is an in-game save or memory card named after `rom_stem`
+fn is_save_file(file_name: &str, rom_stem: &str) -> bool {
+    Path::new(file_name).file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.eq_ignore_ascii_case(rom_stem))
+        && Path::new(file_name).extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SAVE_FILE_EXTENSIONS.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Delete a save state, refusing anything that isn't a save-state file inside one of `known_dirs`
+pub fn delete_save_state(path: &Path, known_dirs: &[PathBuf]) -> Result<(), String> {
+    let canonical = path.canonicalize().map_err(|e| format!("Save state not found: {}", e))?;
+
+    let inside_known_dir = known_dirs.iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| canonical.starts_with(dir));
+    if !inside_known_dir {
+        return Err("Path is not in a save-state directory".to_string());
+    }
+
+    // ROM names can contain dots, so try each one as the end of the ROM stem
+    let is_state_file = canonical.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name.match_indices('.').any(|(i, _)| parse_slot(name, &name[..i]).is_some()));
+    if !is_state_file {
+        return Err("Path is not a save-state file".to_string());
+    }
+
+    std::fs::remove_file(&canonical).map_err(|e| format!("Failed to delete save state: {}", e))
+}
+
+/// Slot of a save-state file named after `rom_stem`, e.g. `Game.state` (0), `Game.state3` (3),
+/// `Game.state.auto` (auto) or Snes9x/mGBA style `Game.ss1`. Returns None for other files,
+/// including RetroArch's `.png` state thumbnails.
+fn parse_slot(file_name: &str, rom_stem: &str) -> Option<Option<u32>> {
+    let extension = file_name.get(rom_stem.len()..)?.strip_prefix('.')?;
+    if !file_name.get(..rom_stem.len())?.eq_ignore_ascii_case(rom_stem) {
+        return None;
+    }
+
+    let extension = extension.to_ascii_lowercase();
+    if extension == "state.auto" {
+        return Some(None);
+    }
+    if extension == "state" {
+        return Some(Some(0));
+    }
+
+    let digits = extension.strip_prefix("state").or_else(|| extension.strip_prefix("ss"))?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_delete_save_states() {
+        let dir = tempfile::tempdir().unwrap();
+        let core_dir = dir.path().join("Snes9x");
+        std::fs::create_dir_all(&core_dir).unwrap();
+
+        std::fs::write(core_dir.join("Super Metroid.state"), b"slot0").unwrap();
+        std::fs::write(core_dir.join("Super Metroid.state2"), b"slot2!").unwrap();
+        std::fs::write(core_dir.join("Super Metroid.state.auto"), b"auto").unwrap();
+        std::fs::write(core_dir.join("Super Metroid.state2.png"), b"thumb").unwrap();
+        std::fs::write(core_dir.join("Super Metroid.srm"), b"sram").unwrap();
+        std::fs::write(core_dir.join("Chrono Trigger.state"), b"other").unwrap();
+
+        let states = find_save_states(dir.path(), "/roms/snes/Super Metroid.sfc");
+        let mut slots: Vec<Option<u32>> = states.iter().map(|s| s.slot).collect();
+        slots.sort();
+        assert_eq!(slots, vec![None, Some(0), Some(2)]);
+
+        let slot2 = states.iter().find(|s| s.slot == Some(2)).unwrap();
+        assert_eq!(slot2.size, 6);
+
+        // Files outside the save-state directory, or that aren't states, are refused
+        let outside = tempfile::tempdir().unwrap();
+        let stray = outside.path().join("Super Metroid.state");
+        std::fs::write(&stray, b"x").unwrap();
+        let known = vec![dir.path().to_path_buf()];
+        assert!(delete_save_state(&stray, &known).is_err());
+        assert!(delete_save_state(&core_dir.join("Super Metroid.srm"), &known).is_err());
+
+        delete_save_state(Path::new(&slot2.path), &known).unwrap();
+        assert!(!Path::new(&slot2.path).exists());
+        assert!(stray.exists());
+    }
+
+    #[test]
+    fn test_backup_save_files() {
+        let root = tempfile::tempdir().unwrap();
+        let states = root.path().join("states");
+        let saves = root.path().join("saves");
+        std::fs::create_dir_all(states.join("Snes9x")).unwrap();
+        std::fs::create_dir_all(&saves).unwrap();
+
+        std::fs::write(states.join("Snes9x").join("Super Metroid.state1"), b"state one").unwrap();
+        std::fs::write(states.join("Super Metroid.state.auto"), b"auto").unwrap();
+        std::fs::write(saves.join("Super Metroid.srm"), b"sram").unwrap();
+        std::fs::write(saves.join("Chrono Trigger.srm"), b"other game").unwrap();
+
+        let dirs = backup_dirs(&states);
+        assert_eq!(dirs, vec![states.clone(), saves.clone()]);
+
+        let dest = root.path().join("backups");
+        let now = chrono::Local::now();
+        let archive_path = backup_save_files(&dirs, "/roms/Super Metroid.sfc", &dest, now).unwrap();
+        assert_eq!(
+            archive_path.file_name().unwrap().to_string_lossy(),
+            format!("Super Metroid saves {}.zip", now.format("%Y-%m-%d_%H%M%S"))
+        );
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(names, vec!["saves/Super Metroid.srm", "states/Snes9x/Super Metroid.state1", "states/Super Metroid.state.auto"]);
+
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("states/Snes9x/Super Metroid.state1").unwrap(), &mut contents).unwrap();
+        assert_eq!(contents, "state one");
+
+        // Nothing to back up for a game without saves
+        assert!(backup_save_files(&dirs, "/roms/Metroid Fusion.gba", &dest, now).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -5,12 +6,48 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+/// Images fetched at once by `download_images_with`; enough to overlap latency without
+/// hammering the image CDN
+const MAX_CONCURRENT_DOWNLOADS: usize = 3;
+
+/// Spaces out requests to a provider. Shared by every client, so concurrent searches and
+/// scrapes together stay under the provider's limit.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub const fn new(interval: Duration) -> Self {
+        Self { interval, next_slot: Mutex::new(None) }
+    }
+
+    /// Wait for this caller's turn
+    pub async fn wait(&self) {
+        let delay = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot - now
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// IGDB allows 4 requests per second
+static IGDB_RATE_LIMIT: RateLimiter = RateLimiter::new(Duration::from_millis(250));
+
 /// IGDB API client with OAuth token management
 pub struct IgdbClient {
     client: Client,
     client_id: String,
     client_secret: String,
     token: Mutex<Option<TokenData>>,
+    /// Our platform ID -> IGDB platform ID, consulted before the built-in table
+    platform_overrides: HashMap<String, u64>,
 }
 
 struct TokenData {
@@ -52,6 +89,10 @@ pub struct ScrapeResult {
     pub game_id: String,
     pub fields_updated: Vec<String>,
     pub error: Option<String>,
+    /// Where the metadata came from: "igdb", or "offline" when no provider is configured
+    pub source: String,
+    /// What could be read from the filename, for offline scrapes
+    pub offline_metadata: Option<super::OfflineMetadata>,
 }
 
 /// Result of batch scraping
@@ -194,9 +235,23 @@ impl IgdbClient {
             client_id,
             client_secret,
             token: Mutex::new(None),
+            platform_overrides: HashMap::new(),
         }
     }
 
+    /// Map platforms to IGDB platform IDs for searches, e.g. custom platforms the built-in
+    /// table doesn't know. Overrides win over the built-in mapping.
+    pub fn with_platform_overrides(mut self, overrides: HashMap<String, u64>) -> Self {
+        self.platform_overrides = overrides;
+        self
+    }
+
+    /// IGDB platform ID to filter searches on `platform_id` by
+    fn igdb_platform_id(&self, platform_id: &str) -> Option<u64> {
+        self.platform_overrides.get(platform_id).copied()
+            .or_else(|| get_igdb_platform_id(platform_id))
+    }
+
     /// Get a valid access token, refreshing if necessary
     async fn get_token(&self) -> Result<String, String> {
         // Check if we have a valid cached token
@@ -259,25 +314,10 @@ impl IgdbClient {
     pub async fn search_games(&self, query: &str, platform_id: Option<&str>) -> Result<Vec<IgdbSearchResult>, String> {
         let token = self.get_token().await?;
 
-        // Escape the query for IGDB
-        let escaped_query = query.replace("\"", "\\\"");
-
         // Get the IGDB platform ID if we have a platform filter
-        let igdb_platform_id = platform_id.and_then(get_igdb_platform_id);
-
-        // Build query - if we have a platform, filter by it
-        let body = if let Some(plat_id) = igdb_platform_id {
-            format!(
-                "search \"{}\"; fields name, summary, first_release_date, cover.image_id, platforms.name, platforms; where platforms = ({}); limit 20;",
-                escaped_query, plat_id
-            )
-        } else {
-            format!(
-                "search \"{}\"; fields name, summary, first_release_date, cover.image_id, platforms.name, platforms; limit 20;",
-                escaped_query
-            )
-        };
+        let body = search_body(query, platform_id.and_then(|id| self.igdb_platform_id(id)));
 
+        IGDB_RATE_LIMIT.wait().await;
         let response = self.client
             .post("https://api.igdb.com/v4/games")
             .header("Client-ID", &self.client_id)
@@ -364,6 +404,7 @@ impl IgdbClient {
             igdb_id
         );
 
+        IGDB_RATE_LIMIT.wait().await;
         let response = self.client
             .post("https://api.igdb.com/v4/games")
             .header("Client-ID", &self.client_id)
@@ -440,33 +481,9 @@ impl IgdbClient {
         })
     }
 
-    /// Download an image from a URL and save it to the specified path
-    pub async fn download_image(&self, url: &str, save_path: &PathBuf) -> Result<(), String> {
-        // Create parent directories if they don't exist
-        if let Some(parent) = save_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
-        }
-
-        let response = self.client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download image: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("Image download failed: {}", response.status()));
-        }
-
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| format!("Failed to read image bytes: {}", e))?;
-
-        std::fs::write(save_path, bytes)
-            .map_err(|e| format!("Failed to save image: {}", e))?;
-
-        Ok(())
+    /// Download several images at once; see `download_images_with`
+    pub async fn download_images(&self, downloads: Vec<(String, PathBuf)>, as_webp: bool) -> Vec<Result<PathBuf, String>> {
+        download_images_with(&self.client, downloads, as_webp).await
     }
 
     /// Download a cover image with fallback from high-res to standard resolution
@@ -529,9 +546,88 @@ impl IgdbClient {
     }
 }
 
+/// Download an image from a URL and save it to the specified path.
+/// With `as_webp`, the image is re-encoded as WebP beside `save_path` when that is smaller.
+/// Returns the path the image ended up at.
+/// Responses whose content type isn't an image (e.g. an HTML error page) are rejected.
+pub async fn download_image_with(client: &Client, url: &str, save_path: &PathBuf, as_webp: bool) -> Result<PathBuf, String> {
+    // Create parent directories if they don't exist
+    if let Some(parent) = save_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download image: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Image download failed: {}", response.status()));
+    }
+
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !content_type.starts_with("image/") {
+        return Err(format!("URL did not return an image (content type '{}')", content_type));
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read image bytes: {}", e))?;
+
+    std::fs::write(save_path, bytes)
+        .map_err(|e| format!("Failed to save image: {}", e))?;
+
+    if as_webp {
+        match crate::webp::convert_to_webp(save_path) {
+            Ok(Some((webp_path, _))) => return Ok(webp_path),
+            Ok(None) => {}
+            // Keep the downloaded original rather than losing the image
+            Err(e) => eprintln!("Failed to convert image to WebP: {}", e),
+        }
+    }
+
+    Ok(save_path.clone())
+}
+
+/// Query body for a game search, filtered to one IGDB platform when given
+fn search_body(query: &str, igdb_platform_id: Option<u64>) -> String {
+    // Escape the query for IGDB
+    let escaped_query = query.replace("\"", "\\\"");
+
+    // Build query - if we have a platform, filter by it
+    if let Some(plat_id) = igdb_platform_id {
+        format!(
+            "search \"{}\"; fields name, summary, first_release_date, cover.image_id, platforms.name, platforms; where platforms = ({}); limit 20;",
+            escaped_query, plat_id
+        )
+    } else {
+        format!(
+            "search \"{}\"; fields name, summary, first_release_date, cover.image_id, platforms.name, platforms; limit 20;",
+            escaped_query
+        )
+    }
+}
+
+/// Download `(url, save_path)` pairs a few at a time. Each image succeeds or fails on its
+/// own, and results come back in the order the downloads were given.
+pub async fn download_images_with(client: &Client, downloads: Vec<(String, PathBuf)>, as_webp: bool) -> Vec<Result<PathBuf, String>> {
+    futures_util::stream::iter(downloads)
+        .map(|(url, save_path)| async move { download_image_with(client, &url, &save_path, as_webp).await })
+        .buffered(MAX_CONCURRENT_DOWNLOADS)
+        .collect()
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     #[test]
     fn test_platform_mapping() {
@@ -539,4 +635,75 @@ mod tests {
         assert_eq!(get_igdb_platform_id("ps2"), Some(8));
         assert_eq!(get_igdb_platform_id("unknown"), None);
     }
+
+    #[test]
+    fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::new(Duration::from_millis(40));
+
+        let start = Instant::now();
+        test_util::block_on(async {
+            futures_util::future::join3(limiter.wait(), limiter.wait(), limiter.wait()).await;
+        });
+        assert!(start.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[test]
+    fn test_platform_overrides_route_searches() {
+        let overrides = HashMap::from([("pico8".to_string(), 82), ("snes".to_string(), 58)]);
+        let client = IgdbClient::new("id".to_string(), "secret".to_string()).with_platform_overrides(overrides);
+
+        assert_eq!(client.igdb_platform_id("pico8"), Some(82));
+        assert_eq!(client.igdb_platform_id("snes"), Some(58));
+        assert_eq!(client.igdb_platform_id("n64"), Some(4));
+        assert_eq!(client.igdb_platform_id("unknown"), None);
+
+        let body = search_body("Celeste", client.igdb_platform_id("pico8"));
+        assert!(body.contains("where platforms = (82);"));
+        assert!(!search_body("Celeste", None).contains("where platforms"));
+    }
+
+    #[test]
+    fn test_download_images_concurrently_in_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Each response is held back a little so overlapping requests are visible
+        let (in_flight, peak) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let (server_in_flight, server_peak) = (in_flight.clone(), peak.clone());
+        let base_url = test_util::serve(move |request| {
+            let now = server_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            server_peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(150));
+            server_in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if request.path.contains("broken") {
+                ("404 Not Found", "text/plain", Vec::new())
+            } else {
+                ("200 OK", "image/jpeg", request.path.into_bytes())
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let names = ["shot0", "shot1", "broken", "shot3", "shot4"];
+        let downloads = names.iter()
+            .map(|name| (format!("{}/{}.jpg", base_url, name), dir.path().join(format!("{}.jpg", name))))
+            .collect();
+
+        let results = test_util::block_on(download_images_with(&Client::new(), downloads, false));
+
+        assert_eq!(results.len(), names.len());
+        for (name, result) in names.iter().zip(&results) {
+            if *name == "broken" {
+                assert!(result.is_err());
+            } else {
+                let path = result.as_ref().unwrap();
+                assert_eq!(path, &dir.path().join(format!("{}.jpg", name)));
+                assert_eq!(std::fs::read_to_string(path).unwrap(), format!("/{}.jpg", name));
+            }
+        }
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "downloads ran one at a time");
+        assert!(peak <= MAX_CONCURRENT_DOWNLOADS);
+    }
 }
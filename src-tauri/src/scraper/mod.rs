@@ -1,3 +1,5 @@
 pub mod igdb;
+pub mod offline;
 
 pub use igdb::*;
+pub use offline::*;
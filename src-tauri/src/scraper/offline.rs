@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Region names used in No-Intro and TOSEC filename tags
+const REGIONS: &[&str] = &[
+    "Argentina", "Asia", "Australia", "Brazil", "Canada", "China", "Europe", "France", "Germany",
+    "Hong Kong", "Italy", "Japan", "Korea", "Mexico", "Netherlands", "Russia", "Spain", "Sweden",
+    "Taiwan", "UK", "USA", "World",
+];
+
+/// Metadata derived from a ROM's filename when no online provider is available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineMetadata {
+    pub title: String,
+    /// Region tag, e.g. "USA" or "USA, Europe"
+    pub region: Option<String>,
+    pub year: Option<i32>,
+    pub platform_name: Option<String>,
+}
+
+/// Region and release year from a filename's parenthesised tags, e.g.
+/// "Chrono Trigger (USA) (1995)" gives ("USA", 1995)
+pub fn parse_filename_tags(file_name: &str) -> (Option<String>, Option<i32>) {
+    let mut region = None;
+    let mut year = None;
+
+    for tag in file_name.split('(').skip(1).filter_map(|rest| rest.split(')').next()) {
+        let tag = tag.trim();
+
+        if region.is_none() && tag.split(',').all(|part| REGIONS.contains(&part.trim())) {
+            region = Some(tag.to_string());
+        }
+
+        // A bare year, or a TOSEC-style date such as 1995-03-11
+        if year.is_none() {
+            let digits = tag.get(..4).filter(|_| tag.len() == 4 || tag[4..].starts_with('-'));
+            year = digits.and_then(|y| y.parse::<i32>().ok()).filter(|y| (1970..2100).contains(y));
+        }
+    }
+
+    (region, year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filename_tags() {
+        assert_eq!(parse_filename_tags("Chrono Trigger (USA) (1995)"), (Some("USA".to_string()), Some(1995)));
+        assert_eq!(parse_filename_tags("Tetris (USA, Europe) (Rev A)"), (Some("USA, Europe".to_string()), None));
+        assert_eq!(parse_filename_tags("Elite (1984-09-20)(Acornsoft)"), (None, Some(1984)));
+        assert_eq!(parse_filename_tags("Homebrew"), (None, None));
+    }
+}
@@ -0,0 +1,164 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::path::Path;
+
+use crate::db::Database;
+
+/// Prefix for secret rows in the settings table, keeping them apart from plain settings
+const SECRET_KEY_PREFIX: &str = "secret.";
+
+/// Name of the key file inside the app data directory
+const KEY_FILE_NAME: &str = "secret.key";
+
+/// Nonce length for ChaCha20-Poly1305
+const NONCE_LEN: usize = 12;
+
+/// Encrypts secrets (API keys, client secrets) before they are written to the settings table.
+///
+/// The key lives in its own file in the app data directory, so a copied database or a
+/// settings export never contains credentials in plaintext.
+pub struct SecretStore {
+    cipher: ChaCha20Poly1305,
+}
+
+impl SecretStore {
+    /// Load the key from the app data directory, generating it on first use
+    pub fn open(app_data_dir: &Path) -> Result<Self, String> {
+        let key_path = app_data_dir.join(KEY_FILE_NAME);
+
+        let key_bytes = if key_path.exists() {
+            let bytes = std::fs::read(&key_path)
+                .map_err(|e| format!("Failed to read secret key: {}", e))?;
+            if bytes.len() != 32 {
+                return Err("Secret key file is corrupt".to_string());
+            }
+            bytes
+        } else {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            std::fs::write(&key_path, key.as_slice())
+                .map_err(|e| format!("Failed to write secret key: {}", e))?;
+            restrict_permissions(&key_path);
+            key.to_vec()
+        };
+
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key_bytes)),
+        })
+    }
+
+    /// Encrypt and store a secret
+    pub fn set_secret(&self, db: &Database, name: &str, value: &str) -> Result<(), String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher.encrypt(&nonce, value.as_bytes())
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+
+        db.set_setting(&secret_setting_key(name), &to_hex(&sealed))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Read and decrypt a secret, if it has been set
+    pub fn get_secret(&self, db: &Database, name: &str) -> Result<Option<String>, String> {
+        let Some(stored) = db.get_setting(&secret_setting_key(name)).map_err(|e| e.to_string())? else {
+            return Ok(None);
+        };
+
+        let sealed = from_hex(&stored).ok_or("Stored secret is corrupt")?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Stored secret is corrupt".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        let plaintext = self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt secret".to_string())?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| "Stored secret is not valid UTF-8".to_string())
+    }
+
+    /// Move a secret that was previously stored as a plain setting into the secret store
+    pub fn migrate_plaintext_setting(&self, db: &Database, name: &str) -> Result<(), String> {
+        if let Some(value) = db.get_setting(name).map_err(|e| e.to_string())? {
+            self.set_secret(db, name, &value)?;
+            db.delete_setting(name).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a settings-table key holds an encrypted secret
+pub fn is_secret_setting(key: &str) -> bool {
+    key.starts_with(SECRET_KEY_PREFIX)
+}
+
+fn secret_setting_key(name: &str) -> String {
+    format!("{}{}", SECRET_KEY_PREFIX, name)
+}
+
+/// Make the key file readable only by the current user
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) {}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_secret_not_stored_in_plaintext() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let store = SecretStore::open(dir.path()).unwrap();
+
+        store.set_secret(&db, "igdb_client_secret", "hunter2-client-secret").unwrap();
+
+        let settings = db.get_all_settings().unwrap();
+        assert!(settings.iter().all(|(k, v)| !k.contains("hunter2") && !v.contains("hunter2")));
+        assert_eq!(
+            store.get_secret(&db, "igdb_client_secret").unwrap().as_deref(),
+            Some("hunter2-client-secret")
+        );
+
+        // Reopening uses the same key file
+        let reopened = SecretStore::open(dir.path()).unwrap();
+        assert_eq!(
+            reopened.get_secret(&db, "igdb_client_secret").unwrap().as_deref(),
+            Some("hunter2-client-secret")
+        );
+    }
+
+    #[test]
+    fn test_migrate_plaintext_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let store = SecretStore::open(dir.path()).unwrap();
+
+        db.set_setting("igdb_client_secret", "legacy").unwrap();
+        store.migrate_plaintext_setting(&db, "igdb_client_secret").unwrap();
+
+        assert_eq!(db.get_setting("igdb_client_secret").unwrap(), None);
+        assert_eq!(store.get_secret(&db, "igdb_client_secret").unwrap().as_deref(), Some("legacy"));
+    }
+}
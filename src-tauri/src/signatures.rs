@@ -0,0 +1,220 @@
+use std::io::Read;
+use std::path::Path;
+
+use crate::models::N64ByteOrder;
+
+/// Enough of the file to reach the Xbox media header at 0x10000 and a HiROM SNES header
+/// behind a 512-byte copier header
+const HEADER_LEN: usize = 0x10220;
+
+/// Size of the header some SNES copier devices prepend to their dumps
+pub const SMC_HEADER_LEN: u64 = 512;
+
+/// Whether a SNES dump of `len` bytes starts with a copier header. ROM sizes are multiples
+/// of 8 KiB, so 512 spare bytes can only be a header. RetroAchievements hashes by the same
+/// rule, so everything that skips the header goes through here.
+pub fn is_smc_headered_len(len: u64) -> bool {
+    len % 8192 == SMC_HEADER_LEN
+}
+
+/// Whether a SNES dump file starts with a copier header
+pub fn has_smc_header(path: &Path) -> bool {
+    std::fs::metadata(path).map(|meta| is_smc_headered_len(meta.len())).unwrap_or(false)
+}
+
+/// Suggest a platform from a ROM's contents, for files whose extension is wrong, missing
+/// or shared by several platforms. Returns None when nothing recognisable is found.
+pub fn detect_platform_by_content(path: &Path) -> Option<String> {
+    let (header, file_len) = read_header(path).ok()?;
+    detect_from_header(&header, file_len).map(String::from)
+}
+
+/// The start of a file, long enough for every signature we check, and the file's full length
+pub fn read_header(path: &Path) -> std::io::Result<(Vec<u8>, u64)> {
+    let file = std::fs::File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    file.take(HEADER_LEN as u64).read_to_end(&mut header)?;
+    Ok((header, file_len))
+}
+
+/// Check the signatures we know, most specific first
+pub fn detect_from_header(header: &[u8], file_len: u64) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+
+    // iNES header
+    if at(0, b"NES\x1A") {
+        return Some("nes");
+    }
+
+    if n64_byte_order(header).is_some() {
+        return Some("n64");
+    }
+
+    // GameCube and Wii disc magic words
+    if at(0x1C, &[0xC2, 0x33, 0x9F, 0x3D]) {
+        return Some("gamecube");
+    }
+    if at(0x18, &[0x5D, 0x1C, 0x9E, 0xA3]) {
+        return Some("wii");
+    }
+
+    // Start of the Nintendo logo every GBA cartridge carries at 0x04, plus the fixed byte at 0xB2
+    if at(0x04, &[0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21]) && header.get(0xB2) == Some(&0x96) {
+        return Some("gba");
+    }
+
+    // Game Boy logo at 0x104; the CGB flag at 0x143 marks Color games
+    if at(0x104, &[0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B]) {
+        return match header.get(0x143) {
+            Some(0x80) | Some(0xC0) => Some("gbc"),
+            _ => Some("gb"),
+        };
+    }
+
+    // Sega console headers
+    if at(0x100, b"SEGA 32X") {
+        return Some("sega32x");
+    }
+    if at(0x100, b"SEGA") {
+        return Some("genesis");
+    }
+    if at(0, b"SEGA SEGASATURN") {
+        return Some("saturn");
+    }
+    if at(0, b"SEGA SEGAKATANA") {
+        return Some("dreamcast");
+    }
+
+    // Xbox DVD filesystem
+    if at(0x10000, b"MICROSOFT*XBOX*MEDIA") {
+        return Some("xbox360");
+    }
+
+    // ISO 9660 primary volume descriptor, identified by its system identifier
+    if at(0x8001, b"CD001") {
+        let system_id = header.get(0x8008..0x8028).map(|id| String::from_utf8_lossy(id).trim().to_string())?;
+        if system_id == "PSP GAME" {
+            return Some("psp");
+        }
+        if system_id == "PLAYSTATION" {
+            // PS1 and PS2 share the identifier; PS1 games fit on a CD, almost all PS2 games are DVDs
+            return Some(if file_len > 900 * 1024 * 1024 { "ps2" } else { "ps1" });
+        }
+        return None;
+    }
+
+    if snes_header_offset(header).is_some() {
+        return Some("snes");
+    }
+
+    None
+}
+
+/// Work out an N64 dump's byte order from the first word of its boot header
+pub fn n64_byte_order(header: &[u8]) -> Option<N64ByteOrder> {
+    match header.get(..4)? {
+        [0x80, 0x37, 0x12, 0x40] => Some(N64ByteOrder::BigEndian),
+        [0x37, 0x80, 0x40, 0x12] => Some(N64ByteOrder::ByteSwapped),
+        [0x40, 0x12, 0x37, 0x80] => Some(N64ByteOrder::LittleEndian),
+        _ => None,
+    }
+}
+
+/// Byte order of an N64 ROM file, or None when it can't be read or isn't a dump we recognise
+pub fn read_n64_byte_order(path: &Path) -> Option<N64ByteOrder> {
+    let mut header = [0u8; 4];
+    std::fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    n64_byte_order(&header)
+}
+
+/// Re-order an N64 dump in place from one byte order to another. Both swaps are their own
+/// inverse, so undoing `from` yields big-endian and applying `to` gives the target.
+pub fn convert_n64_bytes(data: &mut [u8], from: N64ByteOrder, to: N64ByteOrder) {
+    for order in [from, to] {
+        match order {
+            N64ByteOrder::BigEndian => {}
+            N64ByteOrder::ByteSwapped => data.chunks_exact_mut(2).for_each(|pair| pair.swap(0, 1)),
+            N64ByteOrder::LittleEndian => data.chunks_exact_mut(4).for_each(|word| word.reverse()),
+        }
+    }
+}
+
+/// Offset of the SNES internal header at the LoROM (0x7FC0) or HiROM (0xFFC0) address,
+/// optionally behind a 512-byte copier header: the checksum and its complement must add up to 0xFFFF
+pub fn snes_header_offset(header: &[u8]) -> Option<usize> {
+    [0x7FC0, 0xFFC0, 0x7FC0 + 0x200, 0xFFC0 + 0x200].into_iter().find(|&base| {
+        let word = |offset: usize| header.get(base + offset..base + offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+        match (word(0x1C), word(0x1E)) {
+            // An all-zero or all-0xFF region isn't a header
+            (Some(complement), Some(checksum)) => complement ^ checksum == 0xFFFF && checksum != 0 && complement != 0,
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_nes_header() {
+        let mut header = b"NES\x1A".to_vec();
+        header.extend([0x02, 0x01, 0x00, 0x00]);
+        header.resize(0x4010, 0);
+        assert_eq!(detect_from_header(&header, header.len() as u64), Some("nes"));
+    }
+
+    #[test]
+    fn test_detect_n64_byte_orders() {
+        for magic in [[0x80, 0x37, 0x12, 0x40], [0x37, 0x80, 0x40, 0x12], [0x40, 0x12, 0x37, 0x80]] {
+            let mut header = magic.to_vec();
+            header.resize(0x1000, 0);
+            assert_eq!(detect_from_header(&header, 8 * 1024 * 1024), Some("n64"));
+        }
+    }
+
+    #[test]
+    fn test_n64_byte_order_and_conversion() {
+        let big = vec![0x80, 0x37, 0x12, 0x40, 0x00, 0x00, 0x00, 0x0F];
+        let swapped = vec![0x37, 0x80, 0x40, 0x12, 0x00, 0x00, 0x0F, 0x00];
+        let little = vec![0x40, 0x12, 0x37, 0x80, 0x0F, 0x00, 0x00, 0x00];
+
+        assert_eq!(n64_byte_order(&big), Some(N64ByteOrder::BigEndian));
+        assert_eq!(n64_byte_order(&swapped), Some(N64ByteOrder::ByteSwapped));
+        assert_eq!(n64_byte_order(&little), Some(N64ByteOrder::LittleEndian));
+        assert_eq!(n64_byte_order(b"NES\x1A"), None);
+
+        let mut data = swapped.clone();
+        convert_n64_bytes(&mut data, N64ByteOrder::ByteSwapped, N64ByteOrder::LittleEndian);
+        assert_eq!(data, little);
+        convert_n64_bytes(&mut data, N64ByteOrder::LittleEndian, N64ByteOrder::BigEndian);
+        assert_eq!(data, big);
+    }
+
+    #[test]
+    fn test_has_smc_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let headered = dir.path().join("headered.smc");
+        let clean = dir.path().join("clean.smc");
+        std::fs::write(&headered, vec![0u8; 512 + 8 * 1024]).unwrap();
+        std::fs::write(&clean, vec![0u8; 8 * 1024]).unwrap();
+
+        assert!(has_smc_header(&headered));
+        assert!(!has_smc_header(&clean));
+        assert!(!has_smc_header(&dir.path().join("missing.smc")));
+        // 512 past a 1 KiB boundary but not an 8 KiB one isn't a header
+        assert!(!is_smc_headered_len(512 + 5 * 1024));
+    }
+
+    #[test]
+    fn test_detect_snes_and_unknown() {
+        let mut header = vec![0u8; 0x8000];
+        header[0x7FDC..0x7FDE].copy_from_slice(&0x5A5Au16.to_le_bytes());
+        header[0x7FDE..0x7FE0].copy_from_slice(&0xA5A5u16.to_le_bytes());
+        assert_eq!(detect_from_header(&header, header.len() as u64), Some("snes"));
+
+        assert_eq!(detect_from_header(&[0u8; 0x8000], 0x8000), None);
+    }
+}
@@ -0,0 +1,420 @@
+use reqwest::{Client, Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Component, Path};
+use walkdir::WalkDir;
+
+use crate::models::SyncResult;
+
+/// Remote name of the database backup
+const REMOTE_DATABASE: &str = "retrovoid.db";
+
+/// Remote name of the manifest listing every image and its content hash
+const REMOTE_MANIFEST: &str = "retrovoid-sync.json";
+
+/// Remote folder holding the mirrored images directory
+const REMOTE_IMAGES: &str = "images";
+
+/// What the last push uploaded, so the other side can compare hashes without downloading images
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncManifest {
+    /// Images directory on the machine that pushed, for rewriting stored image paths on pull
+    pub images_dir: String,
+    /// Image path relative to the images directory (with `/` separators) -> MD5 of its contents
+    pub images: BTreeMap<String, String>,
+}
+
+/// Minimal WebDAV client: GET, PUT and MKCOL with basic auth
+pub struct WebDavClient {
+    client: Client,
+    base_url: Url,
+    username: String,
+    password: String,
+}
+
+impl WebDavClient {
+    pub fn new(base_url: &str, username: String, password: String) -> Result<Self, String> {
+        let base_url = Url::parse(base_url).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        if base_url.cannot_be_a_base() {
+            return Err("Invalid WebDAV URL".to_string());
+        }
+
+        Ok(Self {
+            client: Client::new(),
+            base_url,
+            username,
+            password,
+        })
+    }
+
+    /// URL of a `/`-separated path below the base URL, with each segment percent-encoded
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base URL checked in new")
+            .pop_if_empty()
+            .extend(path.split('/').filter(|s| !s.is_empty()));
+        url
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, self.url(path))
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    /// Download a file, or `None` if it doesn't exist on the remote
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = self.request(Method::GET, path)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Failed to download {}: {}", path, response.status()));
+        }
+
+        let bytes = response.bytes().await.map_err(|e| format!("Failed to download {}: {}", path, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn put(&self, path: &str, body: Vec<u8>) -> Result<(), String> {
+        let response = self.request(Method::PUT, path)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to upload {}: {}", path, response.status()));
+        }
+        Ok(())
+    }
+
+    /// Create a collection (folder). Servers answer 405 when it already exists.
+    async fn mkcol(&self, path: &str) -> Result<(), String> {
+        let method = Method::from_bytes(b"MKCOL").expect("valid method");
+        let response = self.request(method, path)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+
+        if !response.status().is_success() && response.status() != StatusCode::METHOD_NOT_ALLOWED {
+            return Err(format!("Failed to create folder {}: {}", path, response.status()));
+        }
+        Ok(())
+    }
+
+    async fn get_manifest(&self) -> Result<Option<SyncManifest>, String> {
+        match self.get(REMOTE_MANIFEST).await? {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| format!("Remote sync manifest is invalid: {}", e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Hash every file under the images directory
+pub fn hash_images(images_dir: &Path) -> Result<BTreeMap<String, String>, String> {
+    let mut hashes = BTreeMap::new();
+    if !images_dir.exists() {
+        return Ok(hashes);
+    }
+
+    for entry in WalkDir::new(images_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(images_dir) else {
+            continue;
+        };
+        let contents = std::fs::read(entry.path())
+            .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+        hashes.insert(
+            relative.to_string_lossy().replace('\\', "/"),
+            format!("{:x}", md5::compute(&contents)),
+        );
+    }
+
+    Ok(hashes)
+}
+
+/// Upload a database backup and every image whose hash differs from the remote manifest
+pub async fn push(client: &WebDavClient, database_backup: &Path, images_dir: &Path) -> Result<SyncResult, String> {
+    let local = hash_images(images_dir)?;
+    let remote = client.get_manifest().await?.unwrap_or_default();
+
+    let mut result = SyncResult::default();
+    let mut created_dirs = HashSet::new();
+    client.mkcol(REMOTE_IMAGES).await?;
+
+    for (relative, hash) in &local {
+        if remote.images.get(relative) == Some(hash) {
+            result.images_skipped += 1;
+            continue;
+        }
+
+        // Create each parent folder once, outermost first
+        let mut parent = String::from(REMOTE_IMAGES);
+        for segment in relative.split('/').rev().skip(1).collect::<Vec<_>>().into_iter().rev() {
+            parent = format!("{}/{}", parent, segment);
+            if created_dirs.insert(parent.clone()) {
+                client.mkcol(&parent).await?;
+            }
+        }
+
+        let contents = std::fs::read(images_dir.join(relative))
+            .map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+        client.put(&format!("{}/{}", REMOTE_IMAGES, relative), contents).await?;
+        result.images_transferred += 1;
+    }
+
+    let database = std::fs::read(database_backup).map_err(|e| format!("Failed to read database backup: {}", e))?;
+    client.put(REMOTE_DATABASE, database).await?;
+
+    // The manifest goes last so an interrupted push never advertises images that weren't uploaded
+    let manifest = SyncManifest {
+        images_dir: images_dir.to_string_lossy().to_string(),
+        images: local,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    client.put(REMOTE_MANIFEST, manifest).await?;
+
+    Ok(result)
+}
+
+/// Download changed images into `images_dir` and the database backup to `database_dest`.
+/// Returns the remote manifest so the caller can rewrite image paths after restoring.
+pub async fn pull(client: &WebDavClient, database_dest: &Path, images_dir: &Path) -> Result<(SyncResult, SyncManifest), String> {
+    let remote = client.get_manifest().await?
+        .ok_or_else(|| "Nothing has been pushed to this remote yet".to_string())?;
+    let local = hash_images(images_dir)?;
+
+    // Keys become paths under the images directory, so a bad manifest mustn't point elsewhere
+    if let Some(bad) = remote.images.keys().find(|relative| !is_enclosed(relative)) {
+        return Err(format!("Remote sync manifest has an invalid image path: {}", bad));
+    }
+
+    let mut result = SyncResult::default();
+    for (relative, hash) in &remote.images {
+        if local.get(relative) == Some(hash) {
+            result.images_skipped += 1;
+            continue;
+        }
+
+        let contents = client.get(&format!("{}/{}", REMOTE_IMAGES, relative)).await?
+            .ok_or_else(|| format!("Remote image {} is missing", relative))?;
+        let dest = images_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create images directory: {}", e))?;
+        }
+        std::fs::write(&dest, contents).map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+        result.images_transferred += 1;
+    }
+
+    let database = client.get(REMOTE_DATABASE).await?
+        .ok_or_else(|| "Remote database backup is missing".to_string())?;
+    std::fs::write(database_dest, database).map_err(|e| format!("Failed to write database backup: {}", e))?;
+
+    Ok((result, remote))
+}
+
+/// Whether `relative` stays inside the directory it's joined to: no `..`, root or drive prefix
+fn is_enclosed(relative: &str) -> bool {
+    let path = Path::new(relative);
+    !relative.is_empty() && path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// Zip every file under the images directory, stored relative to it, into `dest_zip`.
+/// Returns how many files were archived.
+pub fn archive_images(images_dir: &Path, dest_zip: &Path) -> Result<usize, String> {
+    let archive = std::fs::File::create(dest_zip).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(archive);
+    // Covers are already compressed, so deflating them again only costs time
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    let mut count = 0;
+    if images_dir.exists() {
+        for entry in WalkDir::new(images_dir).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(images_dir) else {
+                continue;
+            };
+            let contents = std::fs::read(entry.path())
+                .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+            zip.start_file(relative.to_string_lossy().replace('\\', "/"), options).map_err(|e| e.to_string())?;
+            zip.write_all(&contents).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+    }
+
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(count)
+}
+
+/// Extract an archive made by `archive_images` into the images directory, overwriting files
+/// with the same name. Entries that would land outside the directory are skipped.
+/// Returns how many files were restored.
+pub fn extract_images(src_zip: &Path, images_dir: &Path) -> Result<usize, String> {
+    let archive = std::fs::File::open(src_zip).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut zip = zip::ZipArchive::new(archive).map_err(|e| format!("Not a valid zip archive: {}", e))?;
+
+    let mut count = 0;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(|e| e.to_string())?;
+        let Some(relative) = file.enclosed_name() else {
+            continue;
+        };
+        if file.is_dir() {
+            continue;
+        }
+
+        let dest = images_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create images directory: {}", e))?;
+        }
+        let mut out = std::fs::File::create(&dest).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        std::io::copy(&mut file, &mut out).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{block_on, serve};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+    type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Start a WebDAV server that keeps uploaded files in memory and logs every request
+    fn mock_webdav() -> (String, Files, Requests) {
+        let files: Files = Arc::default();
+        let requests: Requests = Arc::default();
+
+        let (server_files, server_requests) = (files.clone(), requests.clone());
+        let base_url = serve(move |request| {
+            server_requests.lock().unwrap().push((request.method.clone(), request.path.clone()));
+            let mut files = server_files.lock().unwrap();
+            match request.method.as_str() {
+                "PUT" => {
+                    files.insert(request.path, request.body);
+                    ("201 Created", "text/plain", Vec::new())
+                }
+                "MKCOL" => ("201 Created", "text/plain", Vec::new()),
+                "GET" => match files.get(&request.path) {
+                    Some(contents) => ("200 OK", "application/octet-stream", contents.clone()),
+                    None => ("404 Not Found", "text/plain", Vec::new()),
+                },
+                _ => ("405 Method Not Allowed", "text/plain", Vec::new()),
+            }
+        });
+
+        (format!("{}/dav/", base_url), files, requests)
+    }
+
+    #[test]
+    fn test_push_uploads_only_changed_images() {
+        let (url, files, requests) = mock_webdav();
+        let client = WebDavClient::new(&url, "user".to_string(), "pass".to_string()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let images_dir = dir.path().join("images");
+        std::fs::create_dir_all(images_dir.join("covers")).unwrap();
+        std::fs::write(images_dir.join("covers").join("Super Metroid.jpg"), b"cover").unwrap();
+        std::fs::write(images_dir.join("logo.png"), b"logo").unwrap();
+        let backup = dir.path().join("backup.db");
+        std::fs::write(&backup, b"database").unwrap();
+
+        let first = block_on(push(&client, &backup, &images_dir)).unwrap();
+        assert_eq!((first.images_transferred, first.images_skipped), (2, 0));
+        {
+            let files = files.lock().unwrap();
+            assert_eq!(files["/dav/images/covers/Super%20Metroid.jpg"], b"cover");
+            assert_eq!(files["/dav/images/logo.png"], b"logo");
+            assert_eq!(files["/dav/retrovoid.db"], b"database");
+
+            let manifest: SyncManifest = serde_json::from_slice(&files["/dav/retrovoid-sync.json"]).unwrap();
+            assert_eq!(manifest.images["covers/Super Metroid.jpg"], format!("{:x}", md5::compute(b"cover")));
+        }
+
+        // A second push re-uploads only the image whose contents changed
+        std::fs::write(images_dir.join("logo.png"), b"new logo").unwrap();
+        requests.lock().unwrap().clear();
+        let second = block_on(push(&client, &backup, &images_dir)).unwrap();
+        assert_eq!((second.images_transferred, second.images_skipped), (1, 1));
+
+        let image_puts: Vec<String> = requests.lock().unwrap().iter()
+            .filter(|(method, path)| method == "PUT" && path.starts_with("/dav/images/"))
+            .map(|(_, path)| path.clone())
+            .collect();
+        assert_eq!(image_puts, vec!["/dav/images/logo.png"]);
+        assert_eq!(files.lock().unwrap()["/dav/images/logo.png"], b"new logo");
+    }
+
+    #[test]
+    fn test_pull_rejects_paths_outside_images_dir() {
+        let (url, files, _) = mock_webdav();
+        let client = WebDavClient::new(&url, "user".to_string(), "pass".to_string()).unwrap();
+
+        let mut manifest = SyncManifest::default();
+        manifest.images.insert("covers/ok.jpg".to_string(), format!("{:x}", md5::compute(b"ok")));
+        manifest.images.insert("../evil".to_string(), format!("{:x}", md5::compute(b"evil")));
+        {
+            let mut files = files.lock().unwrap();
+            files.insert("/dav/retrovoid-sync.json".to_string(), serde_json::to_vec(&manifest).unwrap());
+            files.insert("/dav/images/covers/ok.jpg".to_string(), b"ok".to_vec());
+            files.insert("/dav/evil".to_string(), b"evil".to_vec());
+            files.insert("/dav/retrovoid.db".to_string(), b"database".to_vec());
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let images_dir = dir.path().join("images");
+        let result = block_on(pull(&client, &dir.path().join("pulled.db"), &images_dir));
+
+        assert!(result.unwrap_err().contains("../evil"));
+        assert!(!dir.path().join("evil").exists());
+        assert!(!images_dir.join("covers").join("ok.jpg").exists());
+
+        assert!(is_enclosed("covers/Super Metroid.jpg"));
+        assert!(!is_enclosed("/etc/passwd"));
+        assert!(!is_enclosed("covers/../../evil"));
+    }
+
+    #[test]
+    fn test_archive_and_restore_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let images_dir = dir.path().join("images");
+        std::fs::create_dir_all(images_dir.join("covers")).unwrap();
+        std::fs::create_dir_all(images_dir.join("screenshots")).unwrap();
+        std::fs::write(images_dir.join("covers").join("Super Metroid.jpg"), b"cover").unwrap();
+        std::fs::write(images_dir.join("screenshots").join("shot.png"), b"shot").unwrap();
+
+        let archive = dir.path().join("images.zip");
+        assert_eq!(archive_images(&images_dir, &archive).unwrap(), 2);
+
+        let mut names: Vec<String> = zip::ZipArchive::new(std::fs::File::open(&archive).unwrap())
+            .unwrap()
+            .file_names()
+            .map(String::from)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["covers/Super Metroid.jpg", "screenshots/shot.png"]);
+
+        let restored_dir = dir.path().join("fresh").join("images");
+        assert_eq!(extract_images(&archive, &restored_dir).unwrap(), 2);
+        assert_eq!(hash_images(&restored_dir).unwrap(), hash_images(&images_dir).unwrap());
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers shared by the unit tests: a tiny HTTP server and a blocking runtime
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+
+/// A request received by [`serve`]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// Status line, content type and body sent back for a request
+pub type Response = (&'static str, &'static str, Vec<u8>);
+
+/// Serve HTTP from a background thread, answering each connection on its own thread.
+/// Returns the base URL, e.g. `http://127.0.0.1:12345`
+pub fn serve<F>(handler: F) -> String
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().filter_map(|s| s.ok()) {
+            let handler = handler.clone();
+            std::thread::spawn(move || {
+                let Some(request) = read_request(&stream) else { return };
+                let (status, content_type, body) = handler(request);
+                write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, content_type, body.len()).unwrap();
+                stream.write_all(&body).unwrap();
+            });
+        }
+    });
+    base_url
+}
+
+/// Serve `routes` (path -> content type, body), answering 404 for anything else
+pub fn serve_routes(routes: Vec<(&'static str, &'static str, Vec<u8>)>) -> String {
+    serve(move |request| match routes.iter().find(|(route, _, _)| *route == request.path) {
+        Some((_, content_type, body)) => ("200 OK", *content_type, body.clone()),
+        None => ("404 Not Found", "text/plain", Vec::new()),
+    })
+}
+
+fn read_request(stream: &std::net::TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next()?.to_string(), parts.next()?.to_string());
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request { method, path, body })
+}
+
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Largest thumbnail width we will generate
+const MAX_THUMBNAIL_WIDTH: u32 = 1024;
+
+/// Return a cached thumbnail of `source` scaled to `width`, generating it on first request.
+///
+/// Thumbnails are named after a hash of the source path, the width and the source's mtime,
+/// so replacing a cover produces a fresh thumbnail and the stale one is removed.
+pub fn get_or_create_thumbnail(thumbnails_dir: &Path, source: &Path, width: u32) -> Result<PathBuf, String> {
+    if width == 0 || width > MAX_THUMBNAIL_WIDTH {
+        return Err(format!("Thumbnail width must be between 1 and {}", MAX_THUMBNAIL_WIDTH));
+    }
+
+    let modified = std::fs::metadata(source)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read cover image: {}", e))?;
+    let mtime = modified.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    let prefix = format!("{}{}-", source_key(source), width);
+    let thumbnail_path = thumbnails_dir.join(format!("{}{}.jpg", prefix, mtime));
+
+    if thumbnail_path.exists() {
+        return Ok(thumbnail_path);
+    }
+
+    std::fs::create_dir_all(thumbnails_dir)
+        .map_err(|e| format!("Failed to create thumbnails directory: {}", e))?;
+    remove_thumbnails_with_prefix(thumbnails_dir, &prefix);
+
+    let image = image::open(source).map_err(|e| format!("Failed to decode cover image: {}", e))?;
+
+    // Never upscale; a small cover is served at its own size
+    let thumbnail = if image.width() > width {
+        let height = ((image.height() as u64 * width as u64) / image.width() as u64).max(1) as u32;
+        image.thumbnail(width, height)
+    } else {
+        image
+    };
+
+    // JPEG has no alpha channel
+    thumbnail.to_rgb8()
+        .save_with_format(&thumbnail_path, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+
+    Ok(thumbnail_path)
+}
+
+/// Delete every cached thumbnail of `source`, at any width
+pub fn remove_thumbnails(thumbnails_dir: &Path, source: &Path) {
+    remove_thumbnails_with_prefix(thumbnails_dir, &source_key(source));
+}
+
+/// Thumbnail filename prefix identifying a source image
+fn source_key(source: &Path) -> String {
+    format!("{:x}-", md5::compute(source.to_string_lossy().as_bytes()))
+}
+
+/// Delete thumbnails matching a filename prefix, e.g. older versions of the same source and width
+fn remove_thumbnails_with_prefix(thumbnails_dir: &Path, prefix: &str) {
+    let Ok(entries) = std::fs::read_dir(thumbnails_dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_string_lossy().starts_with(prefix) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_generated_then_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("cover.png");
+        image::RgbImage::from_pixel(400, 600, image::Rgb([200, 30, 30])).save(&source).unwrap();
+
+        let thumbnails_dir = dir.path().join("thumbnails");
+        let first = get_or_create_thumbnail(&thumbnails_dir, &source, 100).unwrap();
+
+        let generated = image::open(&first).unwrap();
+        assert_eq!((generated.width(), generated.height()), (100, 150));
+
+        // A second call returns the cached file without re-encoding it
+        let first_modified = std::fs::metadata(&first).unwrap().modified().unwrap();
+        let second = get_or_create_thumbnail(&thumbnails_dir, &source, 100).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(std::fs::metadata(&second).unwrap().modified().unwrap(), first_modified);
+        assert_eq!(std::fs::read_dir(&thumbnails_dir).unwrap().count(), 1);
+    }
+}
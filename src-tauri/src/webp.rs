@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+/// Lossy WebP quality, 0-100. Covers and screenshots look unchanged at this level.
+pub const WEBP_QUALITY: f32 = 80.0;
+
+/// Re-encode an image as lossy WebP beside the original, deleting the original on success.
+///
+/// Returns the new path and how many bytes were saved. An image that is already well
+/// compressed can still come out larger; in that case the original is kept and `None`
+/// is returned.
+pub fn convert_to_webp(source: &Path) -> Result<Option<(PathBuf, u64)>, String> {
+    let original_size = std::fs::metadata(source)
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .len();
+
+    let image = image::open(source).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let encoded = encode_lossy(&image, WEBP_QUALITY);
+
+    if encoded.len() as u64 >= original_size {
+        return Ok(None);
+    }
+
+    let dest = source.with_extension("webp");
+    std::fs::write(&dest, &encoded).map_err(|e| format!("Failed to write WebP image: {}", e))?;
+
+    std::fs::remove_file(source).map_err(|e| format!("Failed to remove original image: {}", e))?;
+    Ok(Some((dest, original_size - encoded.len() as u64)))
+}
+
+/// Encode as lossy WebP, keeping transparency when the image has any channel for it
+fn encode_lossy(image: &image::DynamicImage, quality: f32) -> Vec<u8> {
+    let (width, height) = (image.width(), image.height());
+    if image.color().has_alpha() {
+        let rgba = image.to_rgba8();
+        webp::Encoder::from_rgba(&rgba, width, height).encode(quality).to_vec()
+    } else {
+        let rgb = image.to_rgb8();
+        webp::Encoder::from_rgb(&rgb, width, height).encode(quality).to_vec()
+    }
+}
+
+/// Whether a path already points at a WebP image
+pub fn is_webp(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("webp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_jpeg_to_webp() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("cover.jpg");
+        image::RgbImage::from_pixel(64, 64, image::Rgb([10, 120, 200]))
+            .save_with_format(&source, image::ImageFormat::Jpeg)
+            .unwrap();
+
+        let (dest, saved) = convert_to_webp(&source).unwrap().unwrap();
+        assert_eq!(dest, dir.path().join("cover.webp"));
+        assert!(saved > 0);
+        assert!(!source.exists());
+        assert!(is_webp(&dest));
+        assert_eq!(image::open(&dest).unwrap().width(), 64);
+    }
+
+    /// A photo-like image: smooth gradients with sensor-style noise, which lossless
+    /// encoding can't shrink much
+    fn noisy_photo(width: u32, height: u32) -> image::RgbImage {
+        let mut seed: u32 = 12345;
+        image::RgbImage::from_fn(width, height, |x, y| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let noise = (seed >> 16) as u8 % 24;
+            image::Rgb([
+                (x * 255 / width) as u8 / 2 + noise,
+                (y * 255 / height) as u8 / 2 + noise,
+                ((x + y) * 255 / (width + height)) as u8 / 2 + noise,
+            ])
+        })
+    }
+
+    #[test]
+    fn test_convert_noisy_png_is_lossy() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("screenshot.png");
+        let photo = noisy_photo(128, 96);
+        photo.save_with_format(&source, image::ImageFormat::Png).unwrap();
+        let png_size = std::fs::metadata(&source).unwrap().len();
+
+        let mut lossless = Vec::new();
+        image::DynamicImage::ImageRgb8(photo.clone())
+            .write_to(&mut std::io::Cursor::new(&mut lossless), image::ImageFormat::WebP)
+            .unwrap();
+
+        let (dest, saved) = convert_to_webp(&source).unwrap().unwrap();
+        let webp_size = std::fs::metadata(&dest).unwrap().len();
+        assert_eq!(png_size - webp_size, saved);
+        assert!(webp_size < lossless.len() as u64);
+
+        let decoded = image::open(&dest).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (128, 96));
+        // Close to the original, though not identical
+        let max_diff = decoded.pixels().zip(photo.pixels())
+            .flat_map(|(a, b)| a.0.iter().zip(b.0).map(|(a, b)| a.abs_diff(b)))
+            .max()
+            .unwrap();
+        assert!(max_diff > 0 && max_diff < 64);
+    }
+}
@@ -13,6 +13,8 @@ import {
   scanRetroArchCores,
   getSetting,
   setSetting,
+  getSecret,
+  setSecret,
   addGame,
   type CreateGameInput,
 } from '../../services/library';
@@ -1766,7 +1768,7 @@ function MetadataTab() {
   useEffect(() => {
     const loadCredentials = async () => {
       const savedClientId = await getSetting('igdb_client_id');
-      const savedClientSecret = await getSetting('igdb_client_secret');
+      const savedClientSecret = await getSecret('igdb_client_secret');
       if (savedClientId) setClientId(savedClientId);
       if (savedClientSecret) setClientSecret(savedClientSecret);
     };
@@ -1775,7 +1777,7 @@ function MetadataTab() {
 
   const handleSaveCredentials = async () => {
     await setSetting('igdb_client_id', clientId);
-    await setSetting('igdb_client_secret', clientSecret);
+    await setSecret('igdb_client_secret', clientSecret);
     setCredentialsValid(null);
   };
 
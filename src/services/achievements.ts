@@ -0,0 +1,72 @@
+import { invoke } from '@tauri-apps/api/core';
+
+// ==================== TYPES ====================
+
+export interface AchievementSummary {
+  total: number;
+  earned: number;
+}
+
+export interface Achievement {
+  id: number;
+  title: string;
+  description: string;
+  points: number;
+  earned: boolean;
+}
+
+// Personal goals tracked by the user, separate from RetroAchievements
+export interface CustomAchievement {
+  id: string;
+  gameId: string;
+  title: string;
+  description?: string;
+  unlocked: boolean;
+  unlockedAt?: string;
+}
+
+// ==================== LOGIN ====================
+
+/**
+ * Verify and store RetroAchievements credentials (the API key is encrypted at rest).
+ * Returns false if RetroAchievements rejects them.
+ */
+export async function raLogin(username: string, apiKey: string): Promise<boolean> {
+  return invoke<boolean>('ra_login', { username, apiKey });
+}
+
+// ==================== PROGRESS ====================
+
+/**
+ * Get RetroAchievements progress for a game.
+ * Returns null if the ROM can't be hashed or isn't recognized by RetroAchievements.
+ */
+export async function getAchievementSummary(gameId: string): Promise<AchievementSummary | null> {
+  return invoke<AchievementSummary | null>('get_achievement_summary', { gameId });
+}
+
+/**
+ * Get every achievement for a game with earned flags.
+ * Returns an empty list if RetroAchievements doesn't recognize the game.
+ */
+export async function getGameAchievements(gameId: string): Promise<Achievement[]> {
+  return invoke<Achievement[]>('get_game_achievements', { gameId });
+}
+
+// ==================== PERSONAL CHECKLIST ====================
+
+export async function getAchievements(gameId: string): Promise<CustomAchievement[]> {
+  return invoke<CustomAchievement[]>('get_achievements', { gameId });
+}
+
+export async function addAchievement(gameId: string, title: string, description?: string): Promise<CustomAchievement> {
+  return invoke<CustomAchievement>('add_achievement', { input: { gameId, title, description } });
+}
+
+/**
+ * Lock or unlock a personal achievement.
+ * Returns true if it is now unlocked.
+ */
+export async function toggleAchievement(id: string): Promise<boolean> {
+  return invoke<boolean>('toggle_achievement', { id });
+}
@@ -1,9 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { Game, SmartCollectionRule } from '../types';
 
 export interface LaunchResult {
   success: boolean;
   pid?: number;
   error?: string;
+  warnings: string[];  // Non-fatal problems, e.g. a failed pre-launch command
 }
 
 // Launch a game with its configured or default emulator
@@ -19,12 +21,65 @@ export async function launchGameWithEmulator(
   return invoke<LaunchResult>('launch_game_with_emulator', { gameId, emulatorId });
 }
 
+export interface RandomLaunchResult {
+  game: Game;
+  launchResult: LaunchResult;
+}
+
+// Launch a random game matching the filter; null when nothing matches
+export async function launchRandomGame(filter?: SmartCollectionRule): Promise<RandomLaunchResult | null> {
+  return invoke<RandomLaunchResult | null>('launch_random_game', { filter });
+}
+
+// Add a game to the end of the "play next" queue; returns the queue length
+export async function enqueueGame(gameId: string): Promise<number> {
+  return invoke<number>('enqueue_game', { gameId });
+}
+
+// Queued games, next up first
+export async function getLaunchQueue(): Promise<Game[]> {
+  return invoke<Game[]>('get_launch_queue');
+}
+
+// Launch the next queued game; null when the queue is empty.
+// With the auto_launch_queue setting on, this also happens when an emulator exits.
+export async function dequeueAndLaunch(): Promise<LaunchResult | null> {
+  return invoke<LaunchResult | null>('dequeue_and_launch');
+}
+
 // End tracking a game session
-export async function endGameSession(gameId: string): Promise<void> {
-  return invoke('end_game_session', { gameId });
+export async function endGameSession(gameId: string, note?: string): Promise<void> {
+  return invoke('end_game_session', { gameId, note });
+}
+
+// Get the captured stdout/stderr from the latest launch of a game
+export async function getLaunchLog(gameId: string): Promise<string | null> {
+  return invoke<string | null>('get_launch_log', { gameId });
 }
 
 // Validate that an emulator executable exists
 export async function validateEmulatorPath(path: string): Promise<boolean> {
   return invoke<boolean>('validate_emulator_path', { path });
 }
+
+export interface SaveState {
+  slot: number | null;   // null for the emulator's automatic save
+  path: string;
+  modifiedAt: string;
+  size: number;
+}
+
+// List the save states for a game in its emulator's save-state folder
+export async function listSaveStates(gameId: string): Promise<SaveState[]> {
+  return invoke<SaveState[]>('list_save_states', { gameId });
+}
+
+// Delete a save state; only files inside a known save-state folder are allowed
+export async function deleteSaveState(path: string): Promise<void> {
+  return invoke('delete_save_state', { path });
+}
+
+// Zip a game's save states and in-game saves into a timestamped archive; returns its path
+export async function backupSaveStates(gameId: string, destDir: string): Promise<string> {
+  return invoke<string>('backup_save_states', { gameId, destDir });
+}
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Game, Emulator, Platform, Collection, PlaySession } from '../types';
+import type { Game, Emulator, Platform, DeletePlatformResult, Collection, CollectionStats, VirtualCollectionKind, SmartCollection, SmartCollectionRule, Playlist, PlaySession, LaunchType, HashAlgorithm, N64ByteOrder, ReportPeriod, PlayStreak, GamePlayTime, GameSort, ScrapeField } from '../types';
 
 // ==================== GAME OPERATIONS ====================
 
@@ -11,6 +11,21 @@ export async function getGame(id: string): Promise<Game | null> {
   return invoke<Game | null>('get_game', { id });
 }
 
+// One page of the library; 'sort-title' (the default) ignores leading articles
+export async function getGamesPaged(offset: number, limit: number, sort?: GameSort): Promise<Game[]> {
+  return invoke<Game[]>('get_games_paged', { sort, offset, limit });
+}
+
+export async function getGameByHash(algo: HashAlgorithm, hash: string): Promise<Game | null> {
+  return invoke<Game | null>('get_game_by_hash', { algo, hash });
+}
+
+// Computes and stores the MD5 of the game's ROM, returning the hash.
+// skipCopierHeader hashes SNES dumps without their 512-byte copier header, for DAT matching.
+export async function hashGameRom(gameId: string, skipCopierHeader = false): Promise<string> {
+  return invoke<string>('hash_game_rom', { gameId, skipCopierHeader });
+}
+
 export interface CreateGameInput {
   title: string;
   romPath: string;
@@ -23,6 +38,17 @@ export async function addGame(input: CreateGameInput): Promise<Game> {
   return invoke<Game>('add_game', { input });
 }
 
+export interface AddGameResult {
+  romPath: string;
+  game: Game | null;   // null when the ROM was already in the library
+  skipped: boolean;
+}
+
+// Adds many games in one transaction, skipping ROMs already in the library
+export async function addGamesBatch(inputs: CreateGameInput[]): Promise<AddGameResult[]> {
+  return invoke<AddGameResult[]>('add_games_batch', { inputs });
+}
+
 export interface UpdateGameInput {
   title?: string;
   platformId?: string;
@@ -35,6 +61,9 @@ export interface UpdateGameInput {
   publisher?: string;
   isFavorite?: boolean;
   preferredEmulatorId?: string;
+  isCompleted?: boolean;
+  collectionIds?: string[];
+  lastPlayed?: string;
 }
 
 export async function updateGame(id: string, updates: UpdateGameInput): Promise<void> {
@@ -49,6 +78,36 @@ export async function deleteGamesBatch(ids: string[]): Promise<number> {
   return invoke<number>('delete_games_batch', { ids });
 }
 
+// Wipes the chosen metadata fields (title and ROM path are kept); returns games updated
+export async function clearMetadata(gameIds: string[], fields: ScrapeField[], deleteImages = false): Promise<number> {
+  return invoke<number>('clear_metadata', { gameIds, fields, deleteImages });
+}
+
+// Folds duplicates into keepId: sessions, play time and collections move over, the rest are deleted
+export async function mergeGames(keepId: string, mergeIds: string[]): Promise<Game> {
+  return invoke<Game>('merge_games', { keepId, mergeIds });
+}
+
+// Replaces an .m3u game with one game per disc; disc 1 keeps the play history
+export async function splitM3uGame(gameId: string, deletePlaylist: boolean): Promise<Game[]> {
+  return invoke<Game[]>('split_m3u_game', { gameId, deletePlaylist });
+}
+
+// Rewrites a game's .m3u from the disc files beside it; returns the discs in playlist order
+export async function regenerateM3u(gameId: string): Promise<string[]> {
+  return invoke<string[]>('regenerate_m3u', { gameId });
+}
+
+// Returns the disc files an .m3u references that are missing; accepts a game id or playlist path
+export async function validateM3u(gameIdOrPath: string): Promise<string[]> {
+  return invoke<string[]>('validate_m3u', { gameIdOrPath });
+}
+
+// Rewrites a game's .m3u in the given order; the entries must match the playlist's existing ones
+export async function reorderM3u(gameId: string, orderedFilenames: string[]): Promise<void> {
+  return invoke('reorder_m3u', { gameId, orderedFilenames });
+}
+
 export async function toggleFavorite(id: string): Promise<boolean> {
   return invoke<boolean>('toggle_favorite', { id });
 }
@@ -63,6 +122,47 @@ export async function setCustomCoverArt(gameId: string, sourcePath: string): Pro
   return invoke<string>('set_custom_cover_art', { gameId, sourcePath });
 }
 
+// Downloads an image URL as the game's cover; returns the stored path
+export async function setCoverArtFromUrl(gameId: string, url: string): Promise<string> {
+  return invoke<string>('set_cover_art_from_url', { gameId, url });
+}
+
+// Sets covers from images stored beside the ROMs; returns how many games were matched
+export async function matchLocalArtwork(scanRoot: string): Promise<number> {
+  return invoke<number>('match_local_artwork', { scanRoot });
+}
+
+// Copies matching screenshots from an emulator's folder into the game; returns the new paths
+export async function importEmulatorScreenshots(gameId: string, screenshotDir: string): Promise<string[]> {
+  return invoke<string[]>('import_emulator_screenshots', { gameId, screenshotDir });
+}
+
+// Returns the path of a cached, downscaled copy of the cover
+export async function getThumbnail(coverPath: string, width: number): Promise<string> {
+  return invoke<string>('get_thumbnail', { coverPath, width });
+}
+
+// Writes a self-contained HTML page of the library, grouped by platform
+export async function exportLibraryHtml(destPath: string): Promise<void> {
+  return invoke<void>('export_library_html', { destPath });
+}
+
+// Omit gameId to export every session; returns the number of sessions written
+export async function exportPlaySessionsCsv(destPath: string, gameId?: string): Promise<number> {
+  return invoke<number>('export_play_sessions_csv', { destPath, gameId });
+}
+
+export interface PlaySessionImportResult {
+  sessionsImported: number;
+  duplicatesSkipped: number;     // Sessions the game already had, e.g. from a repeated import
+  unmatchedRows: string[];       // e.g. "Line 4: no game matches Kid Icarus"
+}
+
+// Reads the export format; games are matched by a rom_path column if present, else by title
+export async function importPlaySessionsCsv(srcPath: string): Promise<PlaySessionImportResult> {
+  return invoke<PlaySessionImportResult>('import_play_sessions_csv', { srcPath });
+}
+
 // ==================== LIBRARY SCANNING ====================
 
 export interface ScanResult {
@@ -96,6 +196,13 @@ export interface CreateEmulatorInput {
   executablePath: string;
   launchArguments?: string;
   supportedPlatformIds: string[];
+  captureLogs?: boolean;
+  fullscreenArgument?: string;
+  launchType?: LaunchType;
+  wrapperCommand?: string;
+  preferredDisplay?: number;
+  displayArgument?: string;
+  resolutionArgumentTemplate?: string;
 }
 
 export async function addEmulator(input: CreateEmulatorInput): Promise<Emulator> {
@@ -107,6 +214,13 @@ export interface UpdateEmulatorInput {
   executablePath?: string;
   launchArguments?: string;
   supportedPlatformIds?: string[];
+  captureLogs?: boolean;
+  fullscreenArgument?: string;
+  launchType?: LaunchType;
+  wrapperCommand?: string;
+  preferredDisplay?: number | null;  // null clears it
+  displayArgument?: string;
+  resolutionArgumentTemplate?: string;
 }
 
 export async function updateEmulator(id: string, updates: UpdateEmulatorInput): Promise<void> {
@@ -117,16 +231,97 @@ export async function deleteEmulator(id: string): Promise<void> {
   return invoke('delete_emulator', { id });
 }
 
+// ids lists emulators in the order they should be shown
+export async function reorderEmulators(ids: string[]): Promise<void> {
+  return invoke('reorder_emulators', { ids });
+}
+
+export interface DisplayInfo {
+  index: number;                 // Value for an emulator's preferredDisplay
+  name?: string;
+  width: number;
+  height: number;
+  x: number;
+  y: number;
+  scaleFactor: number;
+  isPrimary: boolean;
+}
+
+export async function listDisplays(): Promise<DisplayInfo[]> {
+  return invoke<DisplayInfo[]>('list_displays');
+}
+
 // ==================== PLATFORM OPERATIONS ====================
 
 export async function getAllPlatforms(): Promise<Platform[]> {
   return invoke<Platform[]>('get_all_platforms');
 }
 
+// Pass includeEmpty = false to leave out platforms without any games
+export async function getPlatforms(includeEmpty = true): Promise<Platform[]> {
+  return invoke<Platform[]>('get_platforms', { includeEmpty });
+}
+
 export async function getPlatform(id: string): Promise<Platform | null> {
   return invoke<Platform | null>('get_platform', { id });
 }
 
+// ids lists platforms in the order they should be shown
+export async function reorderPlatforms(ids: string[]): Promise<void> {
+  return invoke('reorder_platforms', { ids });
+}
+
+export interface CreatePlatformInput {
+  id: string;
+  displayName: string;
+  manufacturer: string;
+  fileExtensions: string[];
+  color?: string;
+}
+
+export async function addPlatform(input: CreatePlatformInput): Promise<Platform> {
+  return invoke<Platform>('add_platform', { input });
+}
+
+export interface UpdatePlatformInput {
+  displayName?: string;
+  manufacturer?: string;
+  fileExtensions?: string[];     // Each must start with a dot, e.g. ".gdi"
+  color?: string;
+}
+
+export async function updatePlatform(id: string, updates: UpdatePlatformInput): Promise<void> {
+  return invoke('update_platform', { id, updates });
+}
+
+export async function deletePlatform(id: string, reassignTo?: string): Promise<DeletePlatformResult> {
+  return invoke<DeletePlatformResult>('delete_platform', { id, reassignTo });
+}
+
+export async function resetPlatformDefaults(id: string): Promise<Platform> {
+  return invoke<Platform>('reset_platform_defaults', { id });
+}
+
+// Returns [platformId, gameCount] pairs
+export async function getPlatformGameCounts(includeEmpty = false): Promise<[string, number][]> {
+  return invoke<[string, number][]>('get_platform_game_counts', { includeEmpty });
+}
+
+// Copies the image into app data and returns the stored icon path
+export async function setPlatformIcon(platformId: string, sourcePath: string): Promise<string> {
+  return invoke<string>('set_platform_icon', { platformId, sourcePath });
+}
+
+// Returns whether the platform is now pinned
+export async function togglePlatformFavorite(platformId: string): Promise<boolean> {
+  return invoke<boolean>('toggle_platform_favorite', { platformId });
+}
+
+// Color must be a #rrggbb hex value
+export async function setPlatformColor(platformId: string, color: string): Promise<void> {
+  return invoke('set_platform_color', { platformId, color });
+}
+
 export async function setDefaultEmulator(platformId: string, emulatorId: string): Promise<void> {
   return invoke('set_default_emulator', { platformId, emulatorId });
 }
@@ -159,18 +354,288 @@ export async function deleteCollection(id: string): Promise<void> {
   return invoke('delete_collection', { id });
 }
 
+export async function getVirtualCollection(kind: VirtualCollectionKind): Promise<Game[]> {
+  return invoke<Game[]>('get_virtual_collection', { kind });
+}
+
+// Returns [decade, games] pairs such as ["1990s", [...]], with undated games last under "Unknown"
+export async function getGamesGroupedByDecade(): Promise<[string, Game[]][]> {
+  return invoke<[string, Game[]][]>('get_games_grouped_by_decade');
+}
+
+export async function getCollectionStats(collectionId: string): Promise<CollectionStats> {
+  return invoke<CollectionStats>('get_collection_stats', { collectionId });
+}
+
+// ==================== SMART COLLECTION OPERATIONS ====================
+
+export async function getAllSmartCollections(): Promise<SmartCollection[]> {
+  return invoke<SmartCollection[]>('get_all_smart_collections');
+}
+
+export interface CreateSmartCollectionInput {
+  name: string;
+  rule: SmartCollectionRule;
+}
+
+export async function addSmartCollection(input: CreateSmartCollectionInput): Promise<SmartCollection> {
+  return invoke<SmartCollection>('add_smart_collection', { input });
+}
+
+export interface UpdateSmartCollectionInput {
+  name?: string;
+  rule?: SmartCollectionRule;
+}
+
+export async function updateSmartCollection(id: string, updates: UpdateSmartCollectionInput): Promise<void> {
+  return invoke('update_smart_collection', { id, updates });
+}
+
+export async function deleteSmartCollection(id: string): Promise<void> {
+  return invoke('delete_smart_collection', { id });
+}
+
+export async function getSmartCollectionGames(id: string): Promise<Game[]> {
+  return invoke<Game[]>('get_smart_collection_games', { id });
+}
+
+// filter uses the same conditions as smart collections; null when nothing matches
+export async function getRandomGame(filter?: SmartCollectionRule): Promise<Game | null> {
+  return invoke<Game | null>('get_random_game', { filter });
+}
+
+// Same game all day; changes at local midnight
+export async function getGameOfTheDay(favoritesOnly?: boolean): Promise<Game | null> {
+  return invoke<Game | null>('get_game_of_the_day', { favoritesOnly });
+}
+
+// ==================== PLAYLIST OPERATIONS ====================
+
+export async function getAllPlaylists(): Promise<Playlist[]> {
+  return invoke<Playlist[]>('get_all_playlists');
+}
+
+export interface CreatePlaylistInput {
+  name: string;
+  gameIds?: string[];
+}
+
+export async function addPlaylist(input: CreatePlaylistInput): Promise<Playlist> {
+  return invoke<Playlist>('add_playlist', { input });
+}
+
+export interface UpdatePlaylistInput {
+  name?: string;
+  gameIds?: string[];            // Replaces the whole list, in order
+}
+
+export async function updatePlaylist(id: string, updates: UpdatePlaylistInput): Promise<void> {
+  return invoke('update_playlist', { id, updates });
+}
+
+export async function deletePlaylist(id: string): Promise<void> {
+  return invoke('delete_playlist', { id });
+}
+
+// Games in playlist order
+export async function getPlaylistGames(id: string): Promise<Game[]> {
+  return invoke<Game[]>('get_playlist_games', { id });
+}
+
+// Moves the item at index from to index to; returns the updated playlist
+export async function movePlaylistItem(playlistId: string, from: number, to: number): Promise<Playlist> {
+  return invoke<Playlist>('move_playlist_item', { playlistId, from, to });
+}
+
 // ==================== PLAY SESSION OPERATIONS ====================
 
 export async function getPlaySessions(gameId: string): Promise<PlaySession[]> {
   return invoke<PlaySession[]>('get_play_sessions', { gameId });
 }
 
+export async function addManualPlaySession(
+  gameId: string,
+  startTime: string,
+  durationSeconds: number,
+  note?: string
+): Promise<PlaySession> {
+  return invoke<PlaySession>('add_manual_play_session', { gameId, startTime, durationSeconds, note });
+}
+
+export async function deletePlaySession(sessionId: string): Promise<void> {
+  return invoke('delete_play_session', { sessionId });
+}
+
+export async function updatePlaySession(sessionId: string, durationSeconds: number): Promise<void> {
+  return invoke('update_play_session', { sessionId, durationSeconds });
+}
+
+// Omit gameId to clear the whole library; returns the number of sessions deleted
+export async function clearPlayHistory(gameId?: string): Promise<number> {
+  return invoke<number>('clear_play_history', { gameId });
+}
+
+// Returns [bucket, seconds] pairs, oldest first; from/to are inclusive YYYY-MM-DD dates
+export async function getPlaytimeReport(period: ReportPeriod, from?: string, to?: string): Promise<[string, number][]> {
+  return invoke<[string, number][]>('get_playtime_report', { period, from, to });
+}
+
+export async function getPlayStreak(): Promise<PlayStreak> {
+  return invoke<PlayStreak>('get_play_streak');
+}
+
+// Games ranked by time played over the last `days` days
+export async function getMostPlayedRecent(days: number, limit: number): Promise<GamePlayTime[]> {
+  return invoke<GamePlayTime[]>('get_most_played_recent', { days, limit });
+}
+
 // ==================== UTILITY OPERATIONS ====================
 
 export async function getRomInfo(romPath: string): Promise<[string, string] | null> {
   return invoke<[string, string] | null>('get_rom_info', { romPath });
 }
 
+// Regexes removed from filenames when building titles, in order (the built-in ones if unset)
+export async function getTitleCleaningRules(): Promise<string[]> {
+  return invoke<string[]>('get_title_cleaning_rules');
+}
+
+// Replaces the title cleaning rules (null restores the built-in ones); returns invalid patterns
+export async function setTitleCleaningRules(rules: string[] | null): Promise<string[]> {
+  return invoke<string[]>('set_title_cleaning_rules', { rules });
+}
+
+export interface RomPathValidation {
+  exists: boolean;
+  readable: boolean;
+  detectedPlatform: string | null;   // Platform claiming the file's extension
+}
+
+// Checks a dropped ROM before adding it
+export async function validateRomPath(path: string): Promise<RomPathValidation> {
+  return invoke<RomPathValidation>('validate_rom_path', { path });
+}
+
+// Title from the ROM's header (GB, GBA, SNES, Genesis); null when the format has none
+export async function readInternalTitle(path: string): Promise<string | null> {
+  return invoke<string | null>('read_internal_title', { path });
+}
+
+export interface MislabeledRom {
+  gameId: string;
+  fileTitle: string;       // Cleaned title from the filename
+  internalTitle: string;
+  similarity: number;      // 0 to 1
+}
+
+// Games whose filename and header titles disagree, e.g. misnamed dumps
+export async function findMislabeledRoms(): Promise<MislabeledRom[]> {
+  return invoke<MislabeledRom[]>('find_mislabeled_roms');
+}
+
+// Byte order of an N64 game's ROM (null for other platforms)
+export async function getN64ByteOrder(gameId: string): Promise<N64ByteOrder | null> {
+  return invoke<N64ByteOrder | null>('get_n64_byte_order', { gameId });
+}
+
+// Writes a copy of an N64 ROM in the given byte order, returning the new file's path
+export async function convertN64Byteorder(path: string, target: N64ByteOrder): Promise<string> {
+  return invoke<string>('convert_n64_byteorder', { path, target });
+}
+
+// Writes a headerless .sfc copy of a SNES ROM that has a copier header, returning its path
+export async function stripSmcHeader(path: string): Promise<string> {
+  return invoke<string>('strip_smc_header', { path });
+}
+
+export interface ImageConversionResult {
+  imagesConverted: number;
+  bytesSaved: number;
+  errors: string[];
+}
+
+// Re-encodes downloaded artwork as WebP and updates the stored paths
+export async function convertImagesToWebp(): Promise<ImageConversionResult> {
+  return invoke<ImageConversionResult>('convert_images_to_webp');
+}
+
+export interface CorruptImage {
+  gameId: string;
+  field: ScrapeField;       // cover_art_path, background_path or screenshots
+  path: string;
+  reason: string;
+}
+
+// Images that are empty or fail to decode; with clear, their paths are removed and the files deleted
+export async function findCorruptImages(clear?: boolean): Promise<CorruptImage[]> {
+  return invoke<CorruptImage[]>('find_corrupt_images', { clear });
+}
+
+export interface CoverDedupResult {
+  filesRemoved: number;
+  gamesUpdated: number;     // Games moved to a shared cover file
+  bytesSaved: number;
+}
+
+// Points games with byte-identical covers at one shared file (covers/shared) and deletes the copies
+export async function dedupCoverImages(): Promise<CoverDedupResult> {
+  return invoke<CoverDedupResult>('dedup_cover_images');
+}
+
+// Version of the last database migration applied
+export async function getSchemaVersion(): Promise<number> {
+  return invoke<number>('get_schema_version');
+}
+
+export interface OptimizeResult {
+  sizeBefore: number;   // Database file size in bytes
+  sizeAfter: number;
+}
+
+// Compacts the database file (VACUUM) and refreshes query planner statistics
+export async function optimizeDatabase(): Promise<OptimizeResult> {
+  return invoke<OptimizeResult>('optimize_database');
+}
+
+// ==================== GAMEPAD ====================
+
+export type GamepadAction =
+  | 'up' | 'down' | 'left' | 'right'
+  | 'confirm' | 'back' | 'previous-page' | 'next-page' | 'menu';
+
+// Payload of the 'gamepad-input' event
+export interface GamepadInput {
+  action: GamepadAction;
+  gamepadId: number;
+}
+
+// Starts emitting 'gamepad-input' events; false if already running
+export async function startGamepadInput(): Promise<boolean> {
+  return invoke<boolean>('start_gamepad_input');
+}
+
+// ==================== DEVICE ====================
+
+export interface DeviceProfile {
+  device: 'steam-deck' | 'handheld' | 'desktop';
+  deviceName?: string;           // e.g. "Steam Deck" or "ROG Ally"
+  platform: string;
+  osVersion: string;
+  uiMode: UiMode;
+  controllerFirst: boolean;      // Layout to use, after applying uiMode
+}
+
+// auto = controller-first on handhelds only
+export type UiMode = 'auto' | 'desktop' | 'controller';
+
+export async function getDeviceProfile(): Promise<DeviceProfile> {
+  return invoke<DeviceProfile>('get_device_profile');
+}
+
+export async function setUiMode(mode: UiMode): Promise<void> {
+  return invoke('set_ui_mode', { mode });
+}
+
 // ==================== SETTINGS ====================
 
 export async function getSetting(key: string): Promise<string | null> {
@@ -181,6 +646,104 @@ export async function setSetting(key: string, value: string): Promise<void> {
   return invoke('set_setting', { key, value });
 }
 
+// Secrets (API keys, client secrets) are encrypted at rest, separate from plain settings
+export async function getSecret(name: string): Promise<string | null> {
+  return invoke<string | null>('get_secret', { name });
+}
+
+export async function setSecret(name: string, value: string): Promise<void> {
+  return invoke('set_secret', { name, value });
+}
+
+// Structured settings stored as JSON on the backend
+export async function getSettingJson<T>(key: string): Promise<T | null> {
+  return invoke<T | null>('get_setting_json', { key });
+}
+
+export async function setSettingJson<T>(key: string, value: T): Promise<void> {
+  return invoke('set_setting_json', { key, value });
+}
+
+export type KeyAction =
+  | 'navigate-up'
+  | 'navigate-down'
+  | 'navigate-left'
+  | 'navigate-right'
+  | 'launch-game'
+  | 'back'
+  | 'search'
+  | 'toggle-favorite'
+  | 'next-page'
+  | 'previous-page'
+  | 'open-settings';
+
+// Values are KeyboardEvent.key names, e.g. "Enter" or "f"
+export type Keybindings = Record<KeyAction, string>;
+
+export async function getKeybindings(): Promise<Keybindings> {
+  return invoke('get_keybindings');
+}
+
+// Actions left out keep their defaults. Rejects if two actions end up sharing a key
+export async function setKeybindings(bindings: Keybindings): Promise<void> {
+  return invoke('set_keybindings', { bindings });
+}
+
+// Exports everything except schema_version and credentials
+export async function exportSettings(destPath: string): Promise<void> {
+  return invoke('export_settings', { destPath });
+}
+
+// Returns the number of settings applied
+export async function importSettings(srcPath: string): Promise<number> {
+  return invoke<number>('import_settings', { srcPath });
+}
+
+// ==================== SYNC ====================
+
+// The remote is configured with the sync_webdav_url and sync_webdav_username settings
+// and the sync_webdav_password secret
+export interface SyncResult {
+  imagesTransferred: number;
+  imagesSkipped: number;   // Already identical on both sides
+}
+
+// Uploads the database and changed images to the WebDAV remote
+export async function syncPush(): Promise<SyncResult> {
+  return invoke<SyncResult>('sync_push');
+}
+
+// Downloads changed images and replaces the local database with the remote one;
+// this machine's secrets and sync settings are kept
+export async function syncPull(): Promise<SyncResult> {
+  return invoke<SyncResult>('sync_pull');
+}
+
+// Zips the whole images folder; returns the number of files archived
+export async function backupImages(destZip: string): Promise<number> {
+  return invoke<number>('backup_images', { destZip });
+}
+
+// Returns the number of files restored
+export async function restoreImages(srcZip: string): Promise<number> {
+  return invoke<number>('restore_images', { srcZip });
+}
+
+// ==================== STORAGE ====================
+
+// All sizes in bytes
+export interface StorageReport {
+  databaseBytes: number;
+  imageFolders: Record<string, number>;   // covers, screenshots, thumbnails, ...; loose files under "other"
+  logsBytes: number;
+  backupsBytes: number;
+  totalBytes: number;
+}
+
+export async function getStorageReport(): Promise<StorageReport> {
+  return invoke<StorageReport>('get_storage_report');
+}
+
 // ==================== RETROARCH ====================
 
 export interface RetroArchCore {
@@ -11,11 +11,34 @@ export interface IgdbSearchResult {
   summary: string | null;
 }
 
+export interface OfflineMetadata {
+  title: string;
+  region: string | null;
+  year: number | null;
+  platformName: string | null;
+}
+
 export interface ScrapeResult {
   success: boolean;
   gameId: string;
   fieldsUpdated: string[];
   error: string | null;
+  source: 'igdb' | 'offline';              // offline = derived from the filename, no provider configured
+  offlineMetadata: OfflineMetadata | null;
+}
+
+// offline = derived from the ROM's filename, no network needed
+export type MetadataProvider = 'igdb' | 'offline';
+
+// The same game found by several providers is merged into one result
+export interface ProviderSearchResult {
+  name: string;
+  releaseDate: string | null;
+  coverUrl: string | null;
+  platforms: string[];
+  summary: string | null;
+  igdbId: number | null;                  // Pass to scrapeGameMetadata when set
+  sources: MetadataProvider[];
 }
 
 export interface BatchScrapeResult {
@@ -55,31 +78,102 @@ export async function searchIgdb(
   return invoke<IgdbSearchResult[]>('search_igdb', { query, platformId: effectivePlatformId });
 }
 
+/**
+ * Search every provider that supports searching at once, merging matches for
+ * the same game. Only IGDB can search today; fails if it isn't configured.
+ */
+export async function searchAllProviders(
+  query: string,
+  platformId?: string
+): Promise<ProviderSearchResult[]> {
+  return invoke<ProviderSearchResult[]>('search_all_providers', { query, platformId });
+}
+
+/**
+ * Map platforms to IGDB platform IDs for searches, e.g. custom platforms
+ * the built-in table doesn't know. Overrides win over the built-in mapping.
+ */
+export async function setIgdbPlatformOverrides(
+  overrides: Record<string, number>
+): Promise<void> {
+  return invoke('set_setting', { key: 'igdb_platform_overrides', value: JSON.stringify(overrides) });
+}
+
 // ==================== SCRAPING ====================
 
+/**
+ * Choose the provider per platform; platforms not listed use defaultProvider (IGDB if unset)
+ */
+export async function setScraperPreference(
+  preferences: Record<string, MetadataProvider>,
+  defaultProvider?: MetadataProvider
+): Promise<void> {
+  await invoke('set_setting', { key: 'scraper_preference', value: JSON.stringify(preferences) });
+  if (defaultProvider !== undefined) {
+    await invoke('set_setting', { key: 'default_scraper', value: defaultProvider });
+  }
+}
+
+/**
+ * Providers to fall back through, in order, when the preferred one finds no match
+ */
+export async function setProviderChain(chain: MetadataProvider[]): Promise<void> {
+  return invoke('set_setting', { key: 'provider_chain', value: JSON.stringify(chain) });
+}
+
 /**
  * Scrape metadata for a single game
  * If igdbId is provided, uses that specific IGDB entry
- * Otherwise, auto-matches based on game title and platform
+ * Otherwise, auto-matches based on game title and platform, trying the
+ * provider chain in order until one finds the game
+ * Fields the user edited are kept unless overwrite is true
  */
 export async function scrapeGameMetadata(
   gameId: string,
-  igdbId?: number
+  igdbId?: number,
+  overwrite?: boolean
 ): Promise<ScrapeResult> {
-  // Only include igdbId in params if it's defined (avoid sending undefined/null)
-  const params: { gameId: string; igdbId?: number } = { gameId };
+  // Only include optional params if they're defined (avoid sending undefined/null)
+  const params: { gameId: string; igdbId?: number; overwrite?: boolean } = { gameId };
   if (igdbId !== undefined) {
     params.igdbId = igdbId;
   }
+  if (overwrite !== undefined) {
+    params.overwrite = overwrite;
+  }
   return invoke<ScrapeResult>('scrape_game_metadata', params);
 }
 
 /**
  * Scrape metadata for all games in the library
  * If onlyMissing is true, only scrapes games without existing metadata
+ * If resume is true, skips games an interrupted run already finished
  */
 export async function scrapeLibraryMetadata(
-  onlyMissing: boolean
+  onlyMissing: boolean,
+  resume?: boolean
+): Promise<BatchScrapeResult> {
+  return invoke<BatchScrapeResult>('scrape_library_metadata', { onlyMissing, resume });
+}
+
+/**
+ * Re-scrape games never scraped or last scraped more than olderThanDays ago
+ */
+export async function rescrapeStale(
+  olderThanDays: number
 ): Promise<BatchScrapeResult> {
-  return invoke<BatchScrapeResult>('scrape_library_metadata', { onlyMissing });
+  return invoke<BatchScrapeResult>('rescrape_stale', { olderThanDays });
+}
+
+export interface CoverRepairResult {
+  coversRestored: number;
+  errors: string[];
+}
+
+/**
+ * Re-download covers that went missing, using each game's stored IGDB ID
+ * instead of a full re-scrape
+ */
+export async function repairMissingCovers(): Promise<CoverRepairResult> {
+  return invoke<CoverRepairResult>('repair_missing_covers');
 }
@@ -19,6 +19,13 @@ export interface Game {
   preferredEmulatorId?: string;
   collectionIds: string[];
   createdAt?: string;            // ISO datetime - when game was added to library
+  isCompleted: boolean;
+  internalTitle?: string;        // Title read from the cartridge header
+  manuallyEditedFields: string[]; // Fields re-scrapes leave alone
+  metadataSource?: string;       // 'igdb' or 'offline'
+  lastScrapedAt?: string;        // ISO datetime
+  igdbId?: number;               // IGDB game it was scraped from
+  n64ByteOrder?: N64ByteOrder;   // Detected when an N64 game is scanned
 }
 
 export interface Emulator {
@@ -27,8 +34,28 @@ export interface Emulator {
   executablePath: string;
   launchArguments: string;       // e.g., "--fullscreen {rom}"
   supportedPlatformIds: string[];
+  captureLogs: boolean;          // Write emulator stdout/stderr to logs/
+  fullscreenArgument?: string;   // e.g., "--fullscreen", appended when launch_fullscreen is on
+  launchType: LaunchType;        // "uri" opens launchArguments (e.g., "steam://rungameid/{rom}")
+  wrapperCommand?: string;       // e.g., "wine" or "proton run", prepended to the executable
+  preferredDisplay?: number;     // Index from listDisplays()
+  displayArgument?: string;      // e.g., "--display={display}", appended when preferredDisplay is set
+  resolutionArgumentTemplate?: string;  // e.g., "--width={w} --height={h}", filled from the launch resolution settings
 }
 
+export type LaunchType = 'executable' | 'uri';
+
+export type HashAlgorithm = 'crc32' | 'md5' | 'sha1';
+
+// z64 = big-endian, v64 = byte-swapped, n64 = little-endian
+export type N64ByteOrder = 'big-endian' | 'byte-swapped' | 'little-endian';
+
+export type ScrapeField =
+  | 'cover_art_path' | 'background_path' | 'screenshots' | 'description'
+  | 'release_date' | 'genre' | 'developer' | 'publisher';
+
+export type GameSort = 'title' | 'sort-title' | 'last-played' | 'date-added';   // sort-title skips The/A/An
+
 export interface Platform {
   id: string;                    // e.g., "nes", "ps1"
   displayName: string;
@@ -37,6 +64,13 @@ export interface Platform {
   iconPath?: string;
   defaultEmulatorId?: string;
   color: string;                 // Accent color for UI theming
+  isFavorite: boolean;           // Pinned to the top of the sidebar
+}
+
+export interface DeletePlatformResult {
+  deleted: boolean;
+  blockingGameCount: number;     // Games that prevented deletion
+  reassignedCount: number;       // Games moved to the reassign target
 }
 
 export interface Collection {
@@ -44,6 +78,37 @@ export interface Collection {
   name: string;
   gameIds: string[];
   coverGameId?: string;          // Game whose art represents collection
+  coverArtPath?: string;         // Effective cover, falls back to first member with art
+}
+
+export type VirtualCollectionKind = 'favorites' | 'recently-played' | 'most-played' | 'never-played';
+
+export interface CollectionStats {
+  gameCount: number;
+  totalPlayTimeSeconds: number;
+  completedCount: number;
+  platforms: string[];           // Platform IDs present in the collection
+}
+
+// Ordered, and a game may appear more than once
+export interface Playlist {
+  id: string;
+  name: string;
+  gameIds: string[];
+}
+
+export interface SmartCollection {
+  id: string;
+  name: string;
+  rule: SmartCollectionRule;
+}
+
+// Unset conditions match every game
+export interface SmartCollectionRule {
+  platformIds?: string[];
+  genreContains?: string;        // Case-insensitive substring of any genre
+  isFavorite?: boolean;
+  maxPlayTimeSeconds?: number;   // Played for less than this
 }
 
 export interface PlaySession {
@@ -51,7 +116,21 @@ export interface PlaySession {
   gameId: string;
   startTime: string;
   endTime: string;
-  durationSeconds: number;
+  durationSeconds: number;        // Excludes idle time when idle tracking is on
+  note?: string;
+  grossDurationSeconds?: number;  // Wall-clock length including idle time; unset for manual/imported sessions
+}
+
+export type ReportPeriod = 'week' | 'month';   // week buckets are labelled by their Monday
+
+export interface GamePlayTime {
+  game: Game;
+  playTimeSeconds: number;       // Time played within the requested window
+}
+
+export interface PlayStreak {
+  currentStreakDays: number;     // 0 if nothing was played today or yesterday
+  longestStreakDays: number;
 }
 
 // UI Types